@@ -0,0 +1,159 @@
+//! Controller components backed by the real platform implementations
+
+use anyhow::{Context, Result};
+use enigo::Key;
+use tray_icon::menu::MenuEvent;
+
+use super::components::{AudioSource, StatusDisplay, TextOutput};
+use super::state::{AppEvent, AppState};
+use crate::audio::capture::AudioCapture;
+use crate::output::commands::EditKey;
+use crate::output::{OutputMethod, TextInjector};
+use crate::ui::Tray;
+
+/// Default input device, opened when recording first starts
+///
+/// Opening lazily lets the app run (and be controlled) without a microphone
+/// plugged in; a missing device surfaces as an error when recording.
+#[derive(Default)]
+pub struct MicrophoneSource {
+    capture: Option<AudioCapture>,
+    scratch: Vec<f32>,
+}
+
+impl AudioSource for MicrophoneSource {
+    fn start(&mut self) -> Result<()> {
+        if self.capture.is_none() {
+            self.capture = Some(AudioCapture::new().context("No usable microphone")?);
+        }
+        match self.capture.as_mut() {
+            Some(capture) => capture.start(),
+            None => Ok(()),
+        }
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        match self.capture.as_mut() {
+            Some(capture) => capture.stop(),
+            None => Ok(()),
+        }
+    }
+
+    fn take_samples(&mut self) -> Vec<f32> {
+        let Some(capture) = &self.capture else {
+            return Vec::new();
+        };
+
+        self.scratch.resize(4096, 0.0);
+        let mut samples = Vec::new();
+        loop {
+            let read = capture.read_samples(&mut self.scratch);
+            samples.extend_from_slice(&self.scratch[..read]);
+            if read < self.scratch.len() {
+                break;
+            }
+        }
+        samples
+    }
+}
+
+/// Modifier of the select all / undo shortcuts
+#[cfg(target_os = "macos")]
+const SHORTCUT_MODIFIER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+const SHORTCUT_MODIFIER: Key = Key::Control;
+
+/// Text injection into the focused application
+pub struct InjectorOutput {
+    injector: TextInjector,
+    method: OutputMethod,
+}
+
+impl InjectorOutput {
+    /// Create an output using the given method
+    pub fn new(method: OutputMethod) -> Result<Self> {
+        Ok(Self {
+            injector: TextInjector::new(method)?,
+            method,
+        })
+    }
+}
+
+impl TextOutput for InjectorOutput {
+    fn type_text(&mut self, text: &str) -> Result<()> {
+        self.injector.type_text(text)
+    }
+
+    fn copy(&mut self, text: &str) -> Result<()> {
+        self.injector.copy_to_clipboard(text)
+    }
+
+    fn method(&self) -> OutputMethod {
+        self.method
+    }
+
+    fn press(&mut self, key: EditKey, times: usize) -> Result<()> {
+        for _ in 0..times {
+            match key {
+                EditKey::Enter => self.injector.press_key(Key::Return)?,
+                EditKey::Backspace => self.injector.press_key(Key::Backspace)?,
+                EditKey::SelectAll => self
+                    .injector
+                    .press_combination(&[SHORTCUT_MODIFIER, Key::Unicode('a')])?,
+                EditKey::Undo => self
+                    .injector
+                    .press_combination(&[SHORTCUT_MODIFIER, Key::Unicode('z')])?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// System tray icon and menu
+pub struct TrayStatus {
+    tray: Tray,
+    notifications: bool,
+}
+
+impl TrayStatus {
+    /// Wrap a tray; notifications are only shown if `notifications` is set
+    pub fn new(tray: Tray, notifications: bool) -> Self {
+        Self {
+            tray,
+            notifications,
+        }
+    }
+}
+
+impl StatusDisplay for TrayStatus {
+    fn set_state(&mut self, state: AppState) -> Result<()> {
+        self.tray.set_state(state)
+    }
+
+    fn notify(&mut self, title: &str, message: &str) -> Result<()> {
+        if self.notifications {
+            self.tray.show_notification(title, message)?;
+        }
+        Ok(())
+    }
+
+    fn show_preview(&mut self, text: Option<&str>) -> Result<()> {
+        self.tray.set_preview(text)
+    }
+
+    fn poll_event(&mut self) -> Option<AppEvent> {
+        let event = MenuEvent::receiver().try_recv().ok()?;
+        self.tray.menu_action(event).map(AppEvent::from)
+    }
+
+    fn open_settings(&mut self) -> Result<()> {
+        // The settings window needs its own event loop, so it runs as a
+        // separate process
+        let exe = std::env::current_exe().context("Failed to locate the VoxAI executable")?;
+        std::process::Command::new(exe)
+            .arg("settings")
+            .spawn()
+            .context("Failed to open settings")?;
+        Ok(())
+    }
+}
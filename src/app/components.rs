@@ -0,0 +1,111 @@
+//! Components driven by the app controller
+//!
+//! The controller only talks to these traits, so tests can swap the
+//! microphone, text injection and tray for fakes. The implementations for
+//! the real platform components live in `adapters`.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+
+use super::state::{AppEvent, AppState};
+use crate::config::Config;
+use crate::output::commands::EditKey;
+use crate::output::OutputMethod;
+use crate::transcription::engine::TranscriptionEngine;
+use crate::transcription::models::configured_model;
+use crate::transcription::whisper::{WhisperContext, WhisperOptions};
+
+/// Source of recorded audio (16kHz mono)
+pub trait AudioSource {
+    /// Start recording
+    fn start(&mut self) -> Result<()>;
+
+    /// Stop recording; audio recorded until now is still returned by
+    /// [`AudioSource::take_samples`]
+    fn stop(&mut self) -> Result<()>;
+
+    /// Take the audio recorded since the last call
+    fn take_samples(&mut self) -> Vec<f32>;
+}
+
+/// Destination of transcribed text
+pub trait TextOutput {
+    /// Type text into the focused application
+    fn type_text(&mut self, text: &str) -> Result<()>;
+
+    /// Put text on the clipboard, replacing its content
+    fn copy(&mut self, text: &str) -> Result<()>;
+
+    /// How the text is delivered
+    fn method(&self) -> OutputMethod;
+
+    /// Press an editing key `times` times, for voice commands
+    fn press(&mut self, key: EditKey, times: usize) -> Result<()>;
+}
+
+/// Visible application status (tray icon, notifications)
+pub trait StatusDisplay {
+    /// Reflect a new application state
+    fn set_state(&mut self, state: AppState) -> Result<()>;
+
+    /// Tell the user about something important (e.g. an error)
+    fn notify(&mut self, title: &str, message: &str) -> Result<()>;
+
+    /// Show the live transcription of the utterance being spoken, or clear
+    /// it with `None`
+    fn show_preview(&mut self, _text: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Next user action taken on the display (e.g. a menu click), if any
+    fn poll_event(&mut self) -> Option<AppEvent> {
+        None
+    }
+
+    /// Show the settings window
+    fn open_settings(&mut self) -> Result<()> {
+        anyhow::bail!("Settings are not available")
+    }
+}
+
+/// Status display for headless use: state changes are only logged
+#[derive(Debug, Default)]
+pub struct LogStatusDisplay;
+
+impl StatusDisplay for LogStatusDisplay {
+    fn set_state(&mut self, state: AppState) -> Result<()> {
+        log::info!("State: {:?}", state);
+        Ok(())
+    }
+
+    fn notify(&mut self, title: &str, message: &str) -> Result<()> {
+        log::warn!("{}: {}", title, message);
+        Ok(())
+    }
+
+    fn show_preview(&mut self, text: Option<&str>) -> Result<()> {
+        if let Some(text) = text {
+            log::debug!("Preview: {}", text);
+        }
+        Ok(())
+    }
+}
+
+/// Creates the transcription engine for the configured model
+///
+/// Called whenever recording starts without an engine, i.e. on first use and
+/// after the model was changed.
+pub type EngineFactory = Box<dyn Fn(&Config) -> Result<Arc<dyn TranscriptionEngine>>>;
+
+/// Engine factory loading the configured Whisper model
+pub fn whisper_engine_factory() -> EngineFactory {
+    Box::new(|config: &Config| {
+        let model = configured_model(config)?;
+        let engine = WhisperContext::with_options(
+            &model,
+            WhisperOptions::from_config(&config.transcription),
+        )
+        .with_context(|| format!("Failed to load the {} model", model))?;
+        Ok(Arc::new(engine) as Arc<dyn TranscriptionEngine>)
+    })
+}
@@ -0,0 +1,1333 @@
+//! Application controller
+//!
+//! Owns the audio source, transcription pipeline, text output and status
+//! display, and moves the app through its states in response to hotkeys,
+//! menu clicks and control commands:
+//!
+//! ```text
+//! Idle --start--> Recording --stop--> Processing --done--> Idle
+//!   \________________\___________________\______failure--> Error --retry/dismiss-->
+//! ```
+//!
+//! A new pipeline is started for every recording. Stopping finishes its
+//! input, so the last words are transcribed before the app returns to idle.
+//!
+//! The hotkey either toggles recording or acts as push-to-talk, depending on
+//! the configured [`ActivationMode`]. In hybrid mode the time between press
+//! and release decides: a short tap toggles, a longer hold records until the
+//! key is released.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+
+use super::components::{AudioSource, EngineFactory, StatusDisplay, TextOutput};
+use super::state::{AppEvent, AppState};
+use crate::config::{save_config, ActivationMode, CommandAction, Config, Language};
+use crate::history::store::{HistoryEntry, HistoryStore};
+use crate::hotkeys::HotkeyEvent;
+use crate::ipc::protocol::{ControlCommand, ControlResponse, DaemonStatus};
+use crate::output::commands::{CommandGrammar, EditKey, Segment};
+use crate::output::formatter::{format_text, FormattingOptions};
+use crate::output::OutputMethod;
+use crate::transcription::engine::TranscriptionEngine;
+use crate::transcription::models::{is_auto_model, ModelRegistry, AUTO_MODEL};
+use crate::transcription::pipeline::{PipelineConfig, TranscriptionPipeline, TranscriptionResult};
+
+/// How often recorded audio is forwarded and the status display is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Hotkey press made while the previous recording was still being
+/// transcribed
+#[derive(Debug, Clone, Copy)]
+struct QueuedPress {
+    pressed_at: Instant,
+    released_at: Option<Instant>,
+}
+
+/// Central application controller
+pub struct AppController {
+    state: AppState,
+    config: Config,
+    audio: Box<dyn AudioSource>,
+    output: Box<dyn TextOutput>,
+    status: Box<dyn StatusDisplay>,
+    engine_factory: EngineFactory,
+    /// Loaded engine, kept across recordings until the model changes
+    engine: Option<Arc<dyn TranscriptionEngine>>,
+    /// Pipeline of the current recording
+    pipeline: Option<TranscriptionPipeline>,
+    /// Explicit pipeline settings (derived from `config` when `None`)
+    pipeline_config: Option<PipelineConfig>,
+    formatting: FormattingOptions,
+    history: Option<HistoryStore>,
+    /// Save model changes to the configuration file
+    persist_config: bool,
+    /// Whether text was already output during the current recording
+    has_output: bool,
+    /// Text output last and the `has_output` before it, for the "delete
+    /// that" voice command
+    last_output: Option<(String, bool)>,
+    /// Text written during the current recording, copied to the clipboard
+    /// once the recording is transcribed
+    recorded: String,
+    /// When the hotkey was pressed to start the current recording, while it
+    /// is held
+    pressed_at: Option<Instant>,
+    /// Press that starts the next recording once the current one is
+    /// transcribed
+    queued_press: Option<QueuedPress>,
+    last_error: Option<String>,
+}
+
+impl AppController {
+    /// Create a controller in the idle state
+    pub fn new(
+        config: Config,
+        audio: Box<dyn AudioSource>,
+        output: Box<dyn TextOutput>,
+        status: Box<dyn StatusDisplay>,
+        engine_factory: EngineFactory,
+    ) -> Self {
+        Self {
+            state: AppState::Idle,
+            formatting: FormattingOptions::from_config(&config),
+            config,
+            audio,
+            output,
+            status,
+            engine_factory,
+            engine: None,
+            pipeline: None,
+            pipeline_config: None,
+            history: None,
+            persist_config: true,
+            has_output: false,
+            last_output: None,
+            recorded: String::new(),
+            pressed_at: None,
+            queued_press: None,
+            last_error: None,
+        }
+    }
+
+    /// Record every output text in `history`
+    pub fn with_history(mut self, history: HistoryStore) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Use explicit pipeline settings instead of deriving them from the
+    /// configuration
+    pub fn with_pipeline_config(mut self, pipeline_config: PipelineConfig) -> Self {
+        self.pipeline_config = Some(pipeline_config);
+        self
+    }
+
+    /// Choose whether model changes are saved to the configuration file
+    /// (enabled by default)
+    pub fn with_config_persistence(mut self, persist: bool) -> Self {
+        self.persist_config = persist;
+        self
+    }
+
+    /// Current state
+    pub fn state(&self) -> AppState {
+        self.state
+    }
+
+    /// Message of the failure that put the app in the `Error` state
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// State reported to control clients
+    pub fn status(&self) -> DaemonStatus {
+        DaemonStatus {
+            recording: self.state == AppState::Recording,
+            state: self.state,
+            model: self.config.transcription.model.clone(),
+        }
+    }
+
+    /// Process events until `Quit` is received or every sender is dropped
+    pub async fn run(mut self, mut events: mpsc::UnboundedReceiver<AppEvent>) -> Result<()> {
+        log::info!("App controller started");
+
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                event = events.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+                    if !self.handle_event(event).await {
+                        break;
+                    }
+                }
+                result = next_result(&mut self.pipeline), if self.pipeline.is_some() => {
+                    self.handle_result(result);
+                }
+                _ = ticker.tick() => {
+                    if !self.poll().await {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.shutdown();
+        log::info!("App controller stopped");
+        Ok(())
+    }
+
+    /// Handle a single event
+    ///
+    /// Returns `false` once the app should quit.
+    pub async fn handle_event(&mut self, event: AppEvent) -> bool {
+        log::debug!("Event: {:?} (state: {:?})", event, self.state);
+
+        match event {
+            AppEvent::StartRecording => self.start_recording().await,
+            AppEvent::StopRecording => self.stop_recording(),
+            AppEvent::ToggleRecording => self.toggle_recording().await,
+            AppEvent::Hotkey(event) => self.handle_hotkey(event, Instant::now()).await,
+            AppEvent::Control(request) => {
+                let response = self.handle_control(&request.command).await;
+                request.respond(response);
+            }
+            AppEvent::OpenSettings => {
+                if let Err(e) = self.status.open_settings() {
+                    log::error!("Failed to open settings: {:#}", e);
+                }
+            }
+            AppEvent::Quit => return false,
+        }
+        true
+    }
+
+    /// Start recording (from `Idle`, or `Error` to retry)
+    pub async fn start_recording(&mut self) {
+        if !self.state.can_transition_to(AppState::Recording) {
+            log::warn!("Cannot start recording while {:?}", self.state);
+            return;
+        }
+
+        if let Err(e) = self.try_start_recording().await {
+            self.fail(e.context("Failed to start recording"));
+        }
+    }
+
+    /// Stop recording and transcribe the remaining audio, or dismiss an error
+    pub fn stop_recording(&mut self) {
+        if self.state == AppState::Error {
+            self.set_state(AppState::Idle);
+            return;
+        }
+        if !self.state.can_transition_to(AppState::Processing) {
+            log::warn!("Cannot stop recording while {:?}", self.state);
+            return;
+        }
+
+        if let Err(e) = self.try_stop_recording() {
+            self.fail(e.context("Failed to stop recording"));
+        }
+    }
+
+    /// React to the hotkey according to the activation mode
+    async fn handle_hotkey(&mut self, event: HotkeyEvent, now: Instant) {
+        let mode = self.config.hotkeys.activation_mode;
+        match (mode, event) {
+            (ActivationMode::Toggle, HotkeyEvent::Pressed) => self.toggle_recording().await,
+            (ActivationMode::Toggle, HotkeyEvent::Released) => {}
+            (_, HotkeyEvent::Pressed) => {
+                // Key repeat while held
+                if self.pressed_at.is_some() || self.queued_press.is_some() {
+                    return;
+                }
+                match self.state {
+                    AppState::Idle | AppState::Error => {
+                        self.start_recording().await;
+                        if self.state == AppState::Recording {
+                            self.pressed_at = Some(now);
+                        }
+                    }
+                    // Recording latched by a hybrid tap
+                    AppState::Recording => self.stop_recording(),
+                    AppState::Processing => self.queue_press(now),
+                }
+            }
+            (_, HotkeyEvent::Released) => {
+                if let Some(queued) = self.queued_press.as_mut() {
+                    queued.released_at.get_or_insert(now);
+                    return;
+                }
+                self.release_hotkey(now);
+            }
+        }
+    }
+
+    /// Stop a push-to-talk recording when the hotkey is released
+    fn release_hotkey(&mut self, now: Instant) {
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return;
+        };
+        let hold_threshold = Duration::from_millis(self.config.hotkeys.hold_threshold_ms);
+        if self.config.hotkeys.activation_mode == ActivationMode::Hybrid
+            && now.saturating_duration_since(pressed_at) < hold_threshold
+        {
+            log::debug!("Hotkey tapped, recording until pressed again");
+            return;
+        }
+
+        // Finishing the pipeline transcribes the last utterance right
+        // away instead of waiting for a full chunk
+        if self.state == AppState::Recording {
+            self.stop_recording();
+        }
+    }
+
+    /// Keep a press made while transcribing for when the pipeline drains,
+    /// capturing audio right away so that nothing said meanwhile is lost
+    fn queue_press(&mut self, now: Instant) {
+        log::info!("Still transcribing, recording once done");
+        if let Err(e) = self.audio.start() {
+            // Retried when the recording starts
+            log::warn!("Failed to start audio: {:#}", e);
+        }
+        self.queued_press = Some(QueuedPress {
+            pressed_at: now,
+            released_at: None,
+        });
+    }
+
+    /// Start the recording of a queued press once the previous one is
+    /// transcribed, and stop it if the hotkey was released meanwhile
+    async fn start_queued_recording(&mut self) {
+        let Some(queued) = self.queued_press else {
+            return;
+        };
+        if self.state != AppState::Idle {
+            return;
+        }
+
+        self.start_recording().await;
+        self.queued_press = None;
+        if self.state != AppState::Recording {
+            return;
+        }
+        self.pressed_at = Some(queued.pressed_at);
+        if let Some(released_at) = queued.released_at {
+            self.release_hotkey(released_at);
+        }
+    }
+
+    async fn toggle_recording(&mut self) {
+        match self.state {
+            AppState::Idle | AppState::Error => self.start_recording().await,
+            AppState::Recording => self.stop_recording(),
+            AppState::Processing => log::info!("Still transcribing, ignoring toggle"),
+        }
+    }
+
+    async fn try_start_recording(&mut self) -> Result<()> {
+        let engine = match &self.engine {
+            Some(engine) => Arc::clone(engine),
+            None => {
+                let engine = (self.engine_factory)(&self.config)?;
+                self.engine = Some(Arc::clone(&engine));
+                engine
+            }
+        };
+
+        let pipeline_config = match &self.pipeline_config {
+            Some(pipeline_config) => pipeline_config.clone(),
+            None => PipelineConfig::from_config(&self.config)?,
+        };
+        let mut pipeline = TranscriptionPipeline::new(pipeline_config, engine)?;
+        pipeline.start().await?;
+
+        // Drop audio left over from a previous recording, but keep what was
+        // said since a queued press
+        if self.queued_press.is_none() {
+            self.audio.take_samples();
+        }
+        self.audio.start()?;
+
+        self.pipeline = Some(pipeline);
+        self.has_output = false;
+        self.last_output = None;
+        self.recorded.clear();
+        self.last_error = None;
+        self.set_state(AppState::Recording);
+        Ok(())
+    }
+
+    fn try_stop_recording(&mut self) -> Result<()> {
+        self.audio.stop()?;
+        self.forward_audio()?;
+        if let Some(pipeline) = self.pipeline.as_mut() {
+            pipeline.finish();
+        }
+
+        self.set_state(AppState::Processing);
+        Ok(())
+    }
+
+    /// Send the audio recorded since the last call to the pipeline
+    fn forward_audio(&mut self) -> Result<()> {
+        let samples = self.audio.take_samples();
+        match &self.pipeline {
+            Some(pipeline) if !samples.is_empty() => pipeline.send_audio(samples),
+            _ => Ok(()),
+        }
+    }
+
+    /// Periodic work: forward audio and pick up actions from the display
+    ///
+    /// Returns `false` once the app should quit.
+    async fn poll(&mut self) -> bool {
+        self.start_queued_recording().await;
+        if self.state == AppState::Recording {
+            if let Err(e) = self.forward_audio() {
+                self.fail(e);
+            }
+        }
+
+        while let Some(event) = self.status.poll_event() {
+            if !self.handle_event(event).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Handle a pipeline result, `None` meaning the pipeline has finished
+    fn handle_result(&mut self, result: Option<TranscriptionResult>) {
+        match result {
+            // Partial text is only previewed, never output
+            Some(result) if !result.is_final() => {
+                if let Err(e) = self.status.show_preview(Some(&result.text)) {
+                    log::debug!("Failed to show preview: {:#}", e);
+                }
+            }
+            Some(result) => {
+                self.clear_preview();
+                if let Err(e) = self.deliver(&result) {
+                    self.fail(e);
+                }
+            }
+            None => {
+                self.clear_preview();
+                self.pipeline = None;
+                if let Err(e) = self.copy_recorded() {
+                    self.fail(e);
+                    return;
+                }
+                match self.state {
+                    AppState::Processing => {
+                        self.set_state(AppState::Idle);
+                    }
+                    AppState::Recording => {
+                        self.fail(anyhow::anyhow!("Transcription stopped unexpectedly"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn clear_preview(&mut self) {
+        if let Err(e) = self.status.show_preview(None) {
+            log::debug!("Failed to clear preview: {:#}", e);
+        }
+    }
+
+    /// Output a result, running the voice commands it contains, and record
+    /// the text in the history
+    fn deliver(&mut self, result: &TranscriptionResult) -> Result<()> {
+        let language = self.spoken_language(result);
+        let grammar = CommandGrammar::for_language(&self.config.commands, &language);
+
+        // Text as it ends up in the focused application
+        let mut written = String::new();
+        let mut capitalize = true;
+        for segment in grammar.parse(&result.text) {
+            match segment {
+                Segment::Text(text) => {
+                    let options = FormattingOptions {
+                        capitalize_sentences: capitalize && self.formatting.capitalize_sentences,
+                        language: language.clone(),
+                        ..self.formatting.clone()
+                    };
+                    let text = format_text(&text, &options)?;
+                    if text.is_empty() {
+                        continue;
+                    }
+                    // Consecutive results of one recording continue the same text
+                    let output = if self.has_output {
+                        format!(" {}", text)
+                    } else {
+                        text
+                    };
+                    self.write(&output, &mut written)?;
+                }
+                Segment::Command(CommandAction::Insert { text }) => {
+                    self.write(&text, &mut written)?;
+                    capitalize = text.ends_with(['.', '!', '?']);
+                }
+                Segment::Command(action) => {
+                    self.run_command(&action, &mut written)?;
+                    capitalize = true;
+                }
+            }
+        }
+
+        let text = written.trim();
+        if text.is_empty() || !self.config.history.enabled {
+            return Ok(());
+        }
+        if let Some(history) = self.history.as_mut() {
+            let entry = HistoryEntry::from_result(
+                result,
+                text.to_string(),
+                self.config.transcription.model.clone(),
+                self.output.method(),
+            );
+            if let Err(e) = history.append(entry) {
+                log::warn!("Failed to record history: {:#}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the text of the recording to the clipboard
+    ///
+    /// The clipboard only holds one text, so it gets the whole recording at
+    /// once rather than each result.
+    fn copy_recorded(&mut self) -> Result<()> {
+        let text = std::mem::take(&mut self.recorded);
+        if self.output.method() == OutputMethod::TypeText || text.is_empty() {
+            return Ok(());
+        }
+        self.output
+            .copy(&text)
+            .context("Failed to copy text to the clipboard")
+    }
+
+    /// Type text, remembering it for "delete that"
+    fn write(&mut self, text: &str, written: &mut String) -> Result<()> {
+        if self.output.method() != OutputMethod::Clipboard {
+            self.output.type_text(text).context("Failed to type text")?;
+        }
+        written.push_str(text);
+        self.recorded.push_str(text);
+        self.last_output = Some((text.to_string(), self.has_output));
+        self.has_output = true;
+        Ok(())
+    }
+
+    /// Perform an editing command with key presses
+    fn run_command(&mut self, action: &CommandAction, written: &mut String) -> Result<()> {
+        log::debug!("Voice command: {:?}", action);
+        match action {
+            CommandAction::NewLine => {
+                self.press(EditKey::Enter, 1)?;
+                written.push('\n');
+                self.recorded.push('\n');
+                self.has_output = false;
+            }
+            CommandAction::NewParagraph => {
+                self.press(EditKey::Enter, 2)?;
+                written.push_str("\n\n");
+                self.recorded.push_str("\n\n");
+                self.has_output = false;
+            }
+            CommandAction::DeleteThat => {
+                let Some((last, had_output)) = self.last_output.take() else {
+                    return Ok(());
+                };
+                self.press(EditKey::Backspace, last.chars().count())?;
+                if written.ends_with(&last) {
+                    written.truncate(written.len() - last.len());
+                }
+                if self.recorded.ends_with(&last) {
+                    self.recorded.truncate(self.recorded.len() - last.len());
+                }
+                self.has_output = had_output;
+            }
+            CommandAction::SelectAll => {
+                self.press(EditKey::SelectAll, 1)?;
+                self.has_output = false;
+            }
+            CommandAction::Undo => self.press(EditKey::Undo, 1)?,
+            CommandAction::Insert { text } => self.write(text, written)?,
+        }
+        Ok(())
+    }
+
+    fn press(&mut self, key: EditKey, times: usize) -> Result<()> {
+        self.last_output = None;
+        if self.output.method() == OutputMethod::Clipboard {
+            // Nothing was typed, so there is nothing to edit
+            log::debug!("Ignoring {:?}: text only goes to the clipboard", key);
+            return Ok(());
+        }
+        self.output
+            .press(key, times)
+            .with_context(|| format!("Failed to press {:?}", key))
+    }
+
+    /// Language spoken in a result: the configured one, or the detected one
+    /// in automatic mode
+    fn spoken_language(&self, result: &TranscriptionResult) -> Language {
+        match Language::from_code(&self.config.transcription.language) {
+            Language::Auto => result
+                .language
+                .as_deref()
+                .map(Language::from_code)
+                .unwrap_or(Language::English),
+            language => language,
+        }
+    }
+
+    /// Apply a command received on the control socket
+    pub async fn handle_control(&mut self, command: &ControlCommand) -> ControlResponse {
+        match command {
+            ControlCommand::Start => {
+                if self.state != AppState::Recording {
+                    self.start_recording().await;
+                }
+            }
+            ControlCommand::Stop => {
+                if matches!(self.state, AppState::Recording | AppState::Error) {
+                    self.stop_recording();
+                }
+            }
+            ControlCommand::Toggle => self.toggle_recording().await,
+            ControlCommand::Status => {}
+            ControlCommand::SetModel { model } => {
+                if let Err(e) = self.set_model(model) {
+                    return ControlResponse::error(format!("{:#}", e));
+                }
+            }
+        }
+
+        match (&self.last_error, command) {
+            (Some(error), ControlCommand::Start | ControlCommand::Toggle)
+                if self.state == AppState::Error =>
+            {
+                ControlResponse::error(error.clone())
+            }
+            _ => ControlResponse::Ok {
+                status: self.status(),
+            },
+        }
+    }
+
+    /// Switch the Whisper model (while not recording)
+    pub fn set_model(&mut self, model: &str) -> Result<()> {
+        if matches!(self.state, AppState::Recording | AppState::Processing) {
+            anyhow::bail!("Cannot change the model while recording");
+        }
+        let model = if is_auto_model(model) {
+            AUTO_MODEL.to_string()
+        } else {
+            ModelRegistry::from_config(&self.config.models)
+                .resolve(model)?
+                .name
+                .clone()
+        };
+
+        let mut config = self.config.clone();
+        config.transcription.model = model.clone();
+        if self.persist_config {
+            save_config(&config)?;
+        }
+
+        log::info!("Model changed to {}", model);
+        self.config = config;
+        // The new model is loaded when recording next starts
+        self.engine = None;
+        Ok(())
+    }
+
+    /// Move to `state` and update the display
+    fn set_state(&mut self, state: AppState) {
+        if !self.state.can_transition_to(state) {
+            log::warn!("Invalid state transition {:?} -> {:?}", self.state, state);
+            return;
+        }
+
+        log::info!("State: {:?} -> {:?}", self.state, state);
+        self.state = state;
+        if let Err(e) = self.status.set_state(state) {
+            log::warn!("Failed to update status display: {:#}", e);
+        }
+    }
+
+    /// Abort the current recording and enter the `Error` state
+    fn fail(&mut self, error: anyhow::Error) {
+        let message = format!("{:#}", error);
+        log::error!("{}", message);
+
+        if let Err(e) = self.audio.stop() {
+            log::warn!("Failed to stop audio: {:#}", e);
+        }
+        self.pipeline = None;
+        self.queued_press = None;
+
+        self.set_state(AppState::Error);
+        if let Err(e) = self.status.notify("VoxAI error", &message) {
+            log::warn!("Failed to show notification: {:#}", e);
+        }
+        self.last_error = Some(message);
+    }
+
+    /// Release the audio device and pipeline
+    fn shutdown(&mut self) {
+        if self.state == AppState::Recording || self.queued_press.is_some() {
+            if let Err(e) = self.audio.stop() {
+                log::warn!("Failed to stop audio: {:#}", e);
+            }
+        }
+        if let Some(mut pipeline) = self.pipeline.take() {
+            pipeline.stop();
+        }
+    }
+}
+
+/// Wait for the next result of the pipeline
+async fn next_result(pipeline: &mut Option<TranscriptionPipeline>) -> Option<TranscriptionResult> {
+    match pipeline {
+        Some(pipeline) => pipeline.receive_text().await,
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::components::LogStatusDisplay;
+    use crate::history::store::RetentionPolicy;
+    use crate::ipc::ControlRequest;
+    use crate::transcription::engine::{MockEngine, Segment};
+    use crate::transcription::pipeline::ResultKind;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tokio::sync::oneshot;
+
+    /// Audio source replaying queued samples
+    #[derive(Clone, Default)]
+    struct FakeAudio {
+        queued: Arc<Mutex<Vec<f32>>>,
+        running: Arc<AtomicBool>,
+        fail_start: bool,
+    }
+
+    impl FakeAudio {
+        fn speak(&self, seconds: usize) {
+            self.queued
+                .lock()
+                .unwrap()
+                .extend(vec![0.1; 16000 * seconds]);
+        }
+    }
+
+    impl AudioSource for FakeAudio {
+        fn start(&mut self) -> Result<()> {
+            if self.fail_start {
+                anyhow::bail!("no microphone");
+            }
+            self.running.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn stop(&mut self) -> Result<()> {
+            self.running.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn take_samples(&mut self) -> Vec<f32> {
+            std::mem::take(&mut *self.queued.lock().unwrap())
+        }
+    }
+
+    /// Output collecting typed text, the clipboard content and key presses
+    #[derive(Clone, Default)]
+    struct FakeOutput {
+        method: OutputMethod,
+        typed: Arc<Mutex<Vec<String>>>,
+        clipboard: Arc<Mutex<Option<String>>>,
+        keys: Arc<Mutex<Vec<(EditKey, usize)>>>,
+    }
+
+    impl TextOutput for FakeOutput {
+        fn type_text(&mut self, text: &str) -> Result<()> {
+            self.typed.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+
+        fn copy(&mut self, text: &str) -> Result<()> {
+            *self.clipboard.lock().unwrap() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn method(&self) -> OutputMethod {
+            self.method
+        }
+
+        fn press(&mut self, key: EditKey, times: usize) -> Result<()> {
+            self.keys.lock().unwrap().push((key, times));
+            Ok(())
+        }
+    }
+
+    /// Display recording every state and notification
+    #[derive(Clone, Default)]
+    struct FakeStatus {
+        states: Arc<Mutex<Vec<AppState>>>,
+        notifications: Arc<Mutex<Vec<String>>>,
+        previews: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl StatusDisplay for FakeStatus {
+        fn set_state(&mut self, state: AppState) -> Result<()> {
+            self.states.lock().unwrap().push(state);
+            Ok(())
+        }
+
+        fn notify(&mut self, _title: &str, message: &str) -> Result<()> {
+            self.notifications.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        fn show_preview(&mut self, text: Option<&str>) -> Result<()> {
+            self.previews.lock().unwrap().push(text.map(str::to_string));
+            Ok(())
+        }
+    }
+
+    /// Factory for an engine transcribing every chunk as its length
+    fn mock_factory(created: Arc<AtomicUsize>) -> EngineFactory {
+        Box::new(move |_config: &Config| {
+            created.fetch_add(1, Ordering::SeqCst);
+            let engine = MockEngine::new().with_responder(|samples| {
+                let seconds = samples.len() / 16000;
+                vec![Segment::new(
+                    format!("said {} seconds", seconds),
+                    Duration::ZERO,
+                    Duration::from_secs(seconds as u64),
+                )]
+            });
+            Ok(Arc::new(engine) as Arc<dyn TranscriptionEngine>)
+        })
+    }
+
+    fn controller(audio: &FakeAudio, output: &FakeOutput, status: &FakeStatus) -> AppController {
+        AppController::new(
+            Config::default(),
+            Box::new(audio.clone()),
+            Box::new(output.clone()),
+            Box::new(status.clone()),
+            mock_factory(Arc::new(AtomicUsize::new(0))),
+        )
+        .with_pipeline_config(PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        })
+        .with_config_persistence(false)
+    }
+
+    /// Let the controller deliver results until it is idle again
+    async fn wait_until_idle(controller: &mut AppController) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while controller.state() != AppState::Idle {
+                let result = next_result(&mut controller.pipeline).await;
+                controller.handle_result(result);
+            }
+        })
+        .await
+        .expect("controller did not return to idle");
+    }
+
+    #[tokio::test]
+    async fn test_record_and_transcribe() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller.handle_event(AppEvent::ToggleRecording).await;
+        assert_eq!(controller.state(), AppState::Recording);
+        assert!(audio.running.load(Ordering::SeqCst));
+
+        audio.speak(3);
+        assert!(controller.poll().await);
+        controller.handle_event(AppEvent::ToggleRecording).await;
+        assert_eq!(controller.state(), AppState::Processing);
+        assert!(!audio.running.load(Ordering::SeqCst));
+
+        wait_until_idle(&mut controller).await;
+        assert_eq!(*output.typed.lock().unwrap(), vec!["Said 3 seconds"]);
+        assert_eq!(
+            *status.states.lock().unwrap(),
+            vec![AppState::Recording, AppState::Processing, AppState::Idle]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partials_are_previewed_not_output() {
+        let (audio, output, status) = Default::default();
+        let mut controller =
+            controller(&audio, &output, &status).with_pipeline_config(PipelineConfig {
+                enable_vad: false,
+                enable_noise_reduction: false,
+                partial_interval: Some(Duration::from_secs(1)),
+                ..Default::default()
+            });
+
+        controller.start_recording().await;
+        audio.speak(3);
+        assert!(controller.poll().await);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while status.previews.lock().unwrap().is_empty() {
+                let result = next_result(&mut controller.pipeline).await;
+                controller.handle_result(result);
+            }
+        })
+        .await
+        .expect("no preview shown");
+        assert!(output.typed.lock().unwrap().is_empty());
+
+        controller.stop_recording();
+        wait_until_idle(&mut controller).await;
+        assert_eq!(*output.typed.lock().unwrap(), vec!["Said 3 seconds"]);
+
+        let previews = status.previews.lock().unwrap();
+        assert_eq!(previews[0].as_deref(), Some("said 3 seconds"));
+        assert_eq!(previews.last(), Some(&None));
+    }
+
+    fn final_result(text: &str, language: Option<&str>) -> TranscriptionResult {
+        TranscriptionResult {
+            text: text.to_string(),
+            segments: Vec::new(),
+            start: Duration::ZERO,
+            end: Duration::from_secs(1),
+            language: language.map(str::to_string),
+            timestamp: Instant::now(),
+            sequence: 0,
+            confidence: 1.0,
+            kind: ResultKind::Final,
+        }
+    }
+
+    #[test]
+    fn test_voice_commands_press_keys() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller
+            .deliver(&final_result(
+                "Dear Anna comma. New paragraph. Thanks for the notes. Delete that.",
+                None,
+            ))
+            .unwrap();
+        controller
+            .deliver(&final_result("see you soon period", None))
+            .unwrap();
+
+        assert_eq!(
+            *output.typed.lock().unwrap(),
+            vec![
+                "Dear Anna",
+                ",",
+                "Thanks for the notes.",
+                "See you soon",
+                "."
+            ]
+        );
+        assert_eq!(
+            *output.keys.lock().unwrap(),
+            vec![(EditKey::Enter, 2), (EditKey::Backspace, 21)]
+        );
+    }
+
+    #[test]
+    fn test_clipboard_gets_whole_recording() {
+        for method in [OutputMethod::Clipboard, OutputMethod::Both] {
+            let output = FakeOutput {
+                method,
+                ..Default::default()
+            };
+            let (audio, status) = Default::default();
+            let mut controller = controller(&audio, &output, &status);
+
+            controller
+                .deliver(&final_result(
+                    "Dear Anna comma. New paragraph. Thanks for the notes. Delete that.",
+                    None,
+                ))
+                .unwrap();
+            controller
+                .deliver(&final_result("see you soon period", None))
+                .unwrap();
+            assert!(output.clipboard.lock().unwrap().is_none());
+
+            // Copied when the pipeline ends
+            controller.handle_result(None);
+            assert_eq!(
+                output.clipboard.lock().unwrap().as_deref(),
+                Some("Dear Anna,\n\nSee you soon.")
+            );
+
+            let typed = output.typed.lock().unwrap();
+            let keys = output.keys.lock().unwrap();
+            if method == OutputMethod::Clipboard {
+                assert!(typed.is_empty());
+                assert!(keys.is_empty());
+            } else {
+                assert_eq!(typed.len(), 5);
+                assert_eq!(keys.len(), 2);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_joins_results() {
+        let output = FakeOutput {
+            method: OutputMethod::Clipboard,
+            ..Default::default()
+        };
+        let (audio, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller.start_recording().await;
+        controller.stop_recording();
+        controller.handle_result(Some(final_result("first.", None)));
+        controller.handle_result(Some(final_result("second.", None)));
+        controller.handle_result(None);
+
+        assert_eq!(controller.state(), AppState::Idle);
+        assert_eq!(
+            output.clipboard.lock().unwrap().as_deref(),
+            Some("First. Second.")
+        );
+        assert!(output.typed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_voice_commands_follow_detected_language() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller
+            .deliver(&final_result("Bonjour. À la ligne.", Some("fr")))
+            .unwrap();
+        controller
+            .deliver(&final_result("new line", Some("de")))
+            .unwrap();
+
+        assert_eq!(*output.typed.lock().unwrap(), vec!["Bonjour.", "New line"]);
+        assert_eq!(*output.keys.lock().unwrap(), vec![(EditKey::Enter, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_is_ignored_while_processing() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller.start_recording().await;
+        audio.speak(1);
+        controller.stop_recording();
+        controller.handle_event(AppEvent::ToggleRecording).await;
+        controller.stop_recording();
+        assert_eq!(controller.state(), AppState::Processing);
+
+        wait_until_idle(&mut controller).await;
+        assert_eq!(output.typed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audio_failure_enters_error_state() {
+        let audio = FakeAudio {
+            fail_start: true,
+            ..Default::default()
+        };
+        let (output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        controller.start_recording().await;
+        assert_eq!(controller.state(), AppState::Error);
+        assert!(controller.last_error().unwrap().contains("no microphone"));
+        assert_eq!(status.notifications.lock().unwrap().len(), 1);
+        assert!(controller.pipeline.is_none());
+
+        // Stopping dismisses the error
+        controller.handle_control(&ControlCommand::Stop).await;
+        assert_eq!(controller.state(), AppState::Idle);
+        assert!(controller.last_error().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_engine_is_reused_until_model_changes() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let (audio, output, status): (FakeAudio, FakeOutput, FakeStatus) = Default::default();
+        let mut controller = AppController::new(
+            Config::default(),
+            Box::new(audio.clone()),
+            Box::new(output),
+            Box::new(status),
+            mock_factory(Arc::clone(&created)),
+        )
+        .with_config_persistence(false);
+
+        for _ in 0..2 {
+            controller.start_recording().await;
+            controller.stop_recording();
+            wait_until_idle(&mut controller).await;
+        }
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        controller.set_model("small").unwrap();
+        assert_eq!(controller.config().transcription.model, "small");
+        controller.start_recording().await;
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        // Not while recording, and not to an unknown model
+        assert!(controller.set_model("tiny").is_err());
+        controller.stop_recording();
+        wait_until_idle(&mut controller).await;
+        assert!(controller.set_model("huge").is_err());
+        controller.set_model("AUTO").unwrap();
+        assert_eq!(controller.config().transcription.model, "auto");
+    }
+
+    #[tokio::test]
+    async fn test_control_commands() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+
+        let response = controller.handle_control(&ControlCommand::Toggle).await;
+        let ControlResponse::Ok { status: daemon } = response else {
+            panic!("unexpected response: {:?}", response);
+        };
+        assert!(daemon.recording);
+        assert_eq!(daemon.state, AppState::Recording);
+
+        // Starting again is a no-op
+        controller.handle_control(&ControlCommand::Start).await;
+        assert_eq!(controller.state(), AppState::Recording);
+
+        let response = controller
+            .handle_control(&ControlCommand::SetModel {
+                model: "tiny".to_string(),
+            })
+            .await;
+        assert!(matches!(response, ControlResponse::Error { .. }));
+
+        controller.handle_control(&ControlCommand::Stop).await;
+        assert_eq!(controller.state(), AppState::Processing);
+    }
+
+    #[tokio::test]
+    async fn test_push_to_talk() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+        controller.config.hotkeys.activation_mode = ActivationMode::PushToTalk;
+        let start = Instant::now();
+
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        assert_eq!(controller.state(), AppState::Recording);
+        // Key repeat does not stop recording
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        assert_eq!(controller.state(), AppState::Recording);
+
+        // Even a short press records until release
+        audio.speak(2);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + Duration::from_millis(10))
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+
+        wait_until_idle(&mut controller).await;
+        assert_eq!(*output.typed.lock().unwrap(), vec!["Said 2 seconds"]);
+    }
+
+    #[tokio::test]
+    async fn test_push_to_talk_while_processing() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+        controller.config.hotkeys.activation_mode = ActivationMode::PushToTalk;
+        let start = Instant::now();
+
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        audio.speak(1);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + Duration::from_secs(1))
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+
+        // Pressed and released again before the first recording is
+        // transcribed: the audio is captured meanwhile
+        controller
+            .handle_hotkey(HotkeyEvent::Pressed, start + Duration::from_secs(2))
+            .await;
+        assert!(audio.running.load(Ordering::SeqCst));
+        audio.speak(2);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + Duration::from_secs(4))
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+
+        wait_until_idle(&mut controller).await;
+        assert!(controller.poll().await);
+        assert_eq!(controller.state(), AppState::Processing);
+        wait_until_idle(&mut controller).await;
+        assert_eq!(
+            *output.typed.lock().unwrap(),
+            vec!["Said 1 seconds", "Said 2 seconds"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_activation() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+        controller.config.hotkeys.activation_mode = ActivationMode::Hybrid;
+        let hold = Duration::from_millis(controller.config.hotkeys.hold_threshold_ms);
+        let start = Instant::now();
+
+        // A tap keeps recording until the next press
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + hold / 2)
+            .await;
+        assert_eq!(controller.state(), AppState::Recording);
+        controller
+            .handle_hotkey(HotkeyEvent::Pressed, start + hold * 4)
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + hold * 5)
+            .await;
+        wait_until_idle(&mut controller).await;
+
+        // Holding records until release
+        let start = Instant::now();
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        assert_eq!(controller.state(), AppState::Recording);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + hold * 2)
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+
+        // Held down while transcribing: records until release
+        controller
+            .handle_hotkey(HotkeyEvent::Pressed, start + hold * 3)
+            .await;
+        wait_until_idle(&mut controller).await;
+        assert!(controller.poll().await);
+        assert_eq!(controller.state(), AppState::Recording);
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + hold * 6)
+            .await;
+        assert_eq!(controller.state(), AppState::Processing);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_ignores_release() {
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status);
+        let start = Instant::now();
+
+        controller.handle_hotkey(HotkeyEvent::Pressed, start).await;
+        controller
+            .handle_hotkey(HotkeyEvent::Released, start + Duration::from_secs(2))
+            .await;
+        assert_eq!(controller.state(), AppState::Recording);
+    }
+
+    #[tokio::test]
+    async fn test_history_records_output() {
+        let path = std::env::temp_dir().join("voxai_app_history.jsonl");
+        std::fs::remove_file(&path).ok();
+        let history = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+
+        let (audio, output, status) = Default::default();
+        let mut controller = controller(&audio, &output, &status).with_history(history);
+
+        controller.start_recording().await;
+        audio.speak(2);
+        controller.stop_recording();
+        wait_until_idle(&mut controller).await;
+
+        let history = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        let entries = history.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "Said 2 seconds");
+        assert_eq!(entries[0].output_method, OutputMethod::TypeText);
+        assert_eq!(entries[0].duration(), Duration::from_secs(2));
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_run_loop() {
+        let (audio, output): (FakeAudio, FakeOutput) = Default::default();
+        let controller = AppController::new(
+            Config::default(),
+            Box::new(audio.clone()),
+            Box::new(output.clone()),
+            Box::new(LogStatusDisplay),
+            mock_factory(Arc::new(AtomicUsize::new(0))),
+        )
+        .with_pipeline_config(PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        });
+        let (events, rx) = mpsc::unbounded_channel();
+
+        let driver = async {
+            events.send(AppEvent::Hotkey(HotkeyEvent::Pressed)).unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            audio.speak(1);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            let (reply, response) = oneshot::channel();
+            events
+                .send(AppEvent::Control(ControlRequest::new(
+                    ControlCommand::Toggle,
+                    reply,
+                )))
+                .unwrap();
+            let response = response.await.unwrap();
+            assert!(matches!(
+                response,
+                ControlResponse::Ok { ref status } if status.state == AppState::Processing
+            ));
+
+            for _ in 0..50 {
+                if !output.typed.lock().unwrap().is_empty() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            events.send(AppEvent::Quit).unwrap();
+        };
+
+        let (result, ()) = tokio::join!(controller.run(rx), driver);
+        result.unwrap();
+        assert_eq!(*output.typed.lock().unwrap(), vec!["Said 1 seconds"]);
+    }
+}
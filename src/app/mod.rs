@@ -0,0 +1,10 @@
+//! Application core
+//!
+//! The [`controller::AppController`] ties the components together: it starts
+//! and stops audio capture, feeds the transcription pipeline, outputs the
+//! text and keeps the tray in sync with the application state.
+
+pub mod adapters;
+pub mod components;
+pub mod controller;
+pub mod state;
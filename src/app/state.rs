@@ -0,0 +1,101 @@
+//! Application states and events
+
+use serde::{Deserialize, Serialize};
+
+use crate::hotkeys::HotkeyEvent;
+use crate::ipc::ControlRequest;
+
+/// Application state, shown by the tray icon and menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppState {
+    /// Waiting for the user to start recording
+    Idle,
+    /// Capturing audio and transcribing as it comes
+    Recording,
+    /// Recording stopped, transcribing the remaining audio
+    Processing,
+    /// A component failed; recording can be retried
+    Error,
+}
+
+impl AppState {
+    /// Check whether the app may move from this state to `next`
+    ///
+    /// Any state may fail into `Error`; from there the user can retry or
+    /// dismiss the error.
+    pub fn can_transition_to(self, next: AppState) -> bool {
+        use AppState::*;
+
+        matches!(
+            (self, next),
+            (Idle, Recording)
+                | (Recording, Processing)
+                | (Processing, Idle)
+                | (_, Error)
+                | (Error, Idle)
+                | (Error, Recording)
+        )
+    }
+}
+
+impl std::fmt::Display for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AppState::Idle => "idle",
+            AppState::Recording => "recording",
+            AppState::Processing => "processing",
+            AppState::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Something the app controller reacts to
+#[derive(Debug)]
+pub enum AppEvent {
+    /// Start recording
+    StartRecording,
+    /// Stop recording and transcribe what is left
+    StopRecording,
+    /// Start or stop recording depending on the current state
+    ToggleRecording,
+    /// Global hotkey pressed or released
+    Hotkey(HotkeyEvent),
+    /// Command received on the control socket
+    Control(ControlRequest),
+    /// Open the settings window
+    OpenSettings,
+    /// Shut down
+    Quit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_cycle() {
+        assert!(AppState::Idle.can_transition_to(AppState::Recording));
+        assert!(AppState::Recording.can_transition_to(AppState::Processing));
+        assert!(AppState::Processing.can_transition_to(AppState::Idle));
+    }
+
+    #[test]
+    fn test_invalid_transitions() {
+        assert!(!AppState::Idle.can_transition_to(AppState::Processing));
+        assert!(!AppState::Recording.can_transition_to(AppState::Idle));
+        assert!(!AppState::Processing.can_transition_to(AppState::Recording));
+        assert!(!AppState::Recording.can_transition_to(AppState::Recording));
+    }
+
+    #[test]
+    fn test_error_transitions() {
+        for state in [AppState::Idle, AppState::Recording, AppState::Processing] {
+            assert!(state.can_transition_to(AppState::Error));
+        }
+        assert!(AppState::Error.can_transition_to(AppState::Recording));
+        assert!(AppState::Error.can_transition_to(AppState::Idle));
+        assert!(!AppState::Error.can_transition_to(AppState::Processing));
+    }
+}
@@ -6,9 +6,10 @@
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::{Device, SampleFormat, Stream, StreamConfig};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use super::buffer::{AudioBuffer, WHISPER_SAMPLE_RATE};
+use super::resampler::StreamingResampler;
 
 /// Audio capture configuration
 #[derive(Debug, Clone)]
@@ -35,6 +36,8 @@ pub struct AudioCapture {
     stream: Option<Stream>,
     buffer: Arc<AudioBuffer>,
     capture_config: CaptureConfig,
+    /// Converts the device rate to the target rate, shared with the stream callback
+    resampler: Arc<Mutex<StreamingResampler>>,
 }
 
 impl AudioCapture {
@@ -64,7 +67,7 @@ impl AudioCapture {
         );
 
         // Create stream config with device's native sample rate
-        // We'll resample to target rate later if needed
+        // Audio is resampled to the target rate in the stream callback
         let config = StreamConfig {
             channels: supported_config.channels(),
             sample_rate: supported_config.sample_rate(),
@@ -72,6 +75,7 @@ impl AudioCapture {
         };
 
         let buffer = Arc::new(AudioBuffer::with_default_capacity());
+        let resampler = StreamingResampler::new(config.sample_rate.0, capture_config.sample_rate)?;
 
         Ok(Self {
             device,
@@ -79,6 +83,7 @@ impl AudioCapture {
             stream: None,
             buffer,
             capture_config,
+            resampler: Arc::new(Mutex::new(resampler)),
         })
     }
 
@@ -102,6 +107,11 @@ impl AudioCapture {
             _ => anyhow::bail!("Unsupported sample format"),
         };
 
+        // Start from a clean filter state
+        if let Ok(mut resampler) = self.resampler.lock() {
+            resampler.reset()?;
+        }
+
         stream.play().context("Failed to start audio stream")?;
         self.stream = Some(stream);
 
@@ -113,6 +123,13 @@ impl AudioCapture {
     pub fn stop(&mut self) -> Result<()> {
         if let Some(stream) = self.stream.take() {
             drop(stream);
+
+            // Push out the audio still held by the resampler
+            if let Ok(mut resampler) = self.resampler.lock() {
+                let tail = resampler.flush()?;
+                self.buffer.write(&tail);
+            }
+
             log::info!("Audio capture stopped");
         }
         Ok(())
@@ -123,6 +140,11 @@ impl AudioCapture {
         self.stream.is_some()
     }
 
+    /// Sample rate of the audio written to the buffer
+    pub fn target_sample_rate(&self) -> u32 {
+        self.capture_config.sample_rate
+    }
+
     /// Get reference to the audio buffer
     pub fn buffer(&self) -> Arc<AudioBuffer> {
         Arc::clone(&self.buffer)
@@ -136,9 +158,9 @@ impl AudioCapture {
     /// Build audio stream for f32 samples
     fn build_stream_f32(&self) -> Result<Stream> {
         let buffer = Arc::clone(&self.buffer);
+        let resampler = Arc::clone(&self.resampler);
         let config = self.config.clone();
-        let config_clone = config.clone();
-        let capture_config = self.capture_config.clone();
+        let channels = config.channels;
 
         let err_fn = |err| log::error!("Audio stream error: {}", err);
 
@@ -147,7 +169,7 @@ impl AudioCapture {
             .build_input_stream(
                 &config,
                 move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    Self::process_f32(data, &buffer, &config_clone, &capture_config);
+                    Self::process_f32(data, &buffer, channels, &resampler);
                 },
                 err_fn,
                 None,
@@ -160,9 +182,9 @@ impl AudioCapture {
     /// Build audio stream for i16 samples
     fn build_stream_i16(&self) -> Result<Stream> {
         let buffer = Arc::clone(&self.buffer);
+        let resampler = Arc::clone(&self.resampler);
         let config = self.config.clone();
-        let config_clone = config.clone();
-        let capture_config = self.capture_config.clone();
+        let channels = config.channels;
 
         let err_fn = |err| log::error!("Audio stream error: {}", err);
 
@@ -171,7 +193,7 @@ impl AudioCapture {
             .build_input_stream(
                 &config,
                 move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    Self::process_i16(data, &buffer, &config_clone, &capture_config);
+                    Self::process_i16(data, &buffer, channels, &resampler);
                 },
                 err_fn,
                 None,
@@ -184,9 +206,9 @@ impl AudioCapture {
     /// Build audio stream for u16 samples
     fn build_stream_u16(&self) -> Result<Stream> {
         let buffer = Arc::clone(&self.buffer);
+        let resampler = Arc::clone(&self.resampler);
         let config = self.config.clone();
-        let config_clone = config.clone();
-        let capture_config = self.capture_config.clone();
+        let channels = config.channels;
 
         let err_fn = |err| log::error!("Audio stream error: {}", err);
 
@@ -195,7 +217,7 @@ impl AudioCapture {
             .build_input_stream(
                 &config,
                 move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                    Self::process_u16(data, &buffer, &config_clone, &capture_config);
+                    Self::process_u16(data, &buffer, channels, &resampler);
                 },
                 err_fn,
                 None,
@@ -209,55 +231,62 @@ impl AudioCapture {
     fn process_f32(
         data: &[f32],
         buffer: &AudioBuffer,
-        config: &StreamConfig,
-        capture_config: &CaptureConfig,
+        channels: u16,
+        resampler: &Mutex<StreamingResampler>,
     ) {
         let mut samples = data.to_vec();
-        Self::process_common(&mut samples, buffer, config, capture_config);
+        Self::process_common(&mut samples, buffer, channels, resampler);
     }
 
     /// Process i16 samples
     fn process_i16(
         data: &[i16],
         buffer: &AudioBuffer,
-        config: &StreamConfig,
-        capture_config: &CaptureConfig,
+        channels: u16,
+        resampler: &Mutex<StreamingResampler>,
     ) {
         let mut samples: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-        Self::process_common(&mut samples, buffer, config, capture_config);
+        Self::process_common(&mut samples, buffer, channels, resampler);
     }
 
     /// Process u16 samples
     fn process_u16(
         data: &[u16],
         buffer: &AudioBuffer,
-        config: &StreamConfig,
-        capture_config: &CaptureConfig,
+        channels: u16,
+        resampler: &Mutex<StreamingResampler>,
     ) {
         let mut samples: Vec<f32> = data
             .iter()
             .map(|&s| (s as i32 - 32768) as f32 / 32768.0)
             .collect();
-        Self::process_common(&mut samples, buffer, config, capture_config);
+        Self::process_common(&mut samples, buffer, channels, resampler);
     }
 
     /// Common processing for all sample types
     fn process_common(
         samples: &mut Vec<f32>,
         buffer: &AudioBuffer,
-        config: &StreamConfig,
-        capture_config: &CaptureConfig,
+        channels: u16,
+        resampler: &Mutex<StreamingResampler>,
     ) {
         // Convert stereo to mono if needed
-        if config.channels > 1 {
-            *samples = Self::stereo_to_mono(samples, config.channels);
+        if channels > 1 {
+            *samples = Self::stereo_to_mono(samples, channels);
         }
 
-        // Resample if needed
-        if config.sample_rate.0 != capture_config.sample_rate {
-            *samples =
-                Self::simple_resample(samples, config.sample_rate.0, capture_config.sample_rate);
-        }
+        // Resample to the target rate, keeping filter state across callbacks
+        let resampled = match resampler.lock() {
+            Ok(mut resampler) => resampler.process(samples),
+            Err(_) => Err(anyhow::anyhow!("Resampler lock poisoned")),
+        };
+        *samples = match resampled {
+            Ok(resampled) => resampled,
+            Err(e) => {
+                log::error!("Failed to resample audio: {}", e);
+                return;
+            }
+        };
 
         // Write to buffer
         let written = buffer.write(samples);
@@ -286,34 +315,6 @@ impl AudioCapture {
 
         mono
     }
-
-    /// Simple linear resampling
-    /// For production, consider using `rubato` crate for better quality
-    fn simple_resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-        if from_rate == to_rate {
-            return samples.to_vec();
-        }
-
-        let ratio = to_rate as f64 / from_rate as f64;
-        let output_len = (samples.len() as f64 * ratio) as usize;
-        let mut output = Vec::with_capacity(output_len);
-
-        for i in 0..output_len {
-            let src_idx = i as f64 / ratio;
-            let idx = src_idx as usize;
-
-            if idx + 1 < samples.len() {
-                // Linear interpolation
-                let frac = src_idx - idx as f64;
-                let sample = samples[idx] * (1.0 - frac) as f32 + samples[idx + 1] * frac as f32;
-                output.push(sample);
-            } else if idx < samples.len() {
-                output.push(samples[idx]);
-            }
-        }
-
-        output
-    }
 }
 
 impl Drop for AudioCapture {
@@ -351,20 +352,23 @@ mod tests {
     }
 
     #[test]
-    fn test_simple_resample() {
-        let samples = vec![1.0, 2.0, 3.0, 4.0];
-
-        // Upsample 4 -> 8 samples
-        let upsampled = AudioCapture::simple_resample(&samples, 4, 8);
-        assert_eq!(upsampled.len(), 8);
+    fn test_process_common_resamples_across_callbacks() {
+        let buffer = AudioBuffer::with_default_capacity();
+        let resampler = Mutex::new(StreamingResampler::new(48000, 16000).unwrap());
+
+        // One second of stereo 48kHz in 10ms callbacks
+        for _ in 0..100 {
+            let mut samples = vec![0.25; 480 * 2];
+            AudioCapture::process_common(&mut samples, &buffer, 2, &resampler);
+        }
+        let tail = resampler.lock().unwrap().flush().unwrap();
+        buffer.write(&tail);
 
-        // Downsample 4 -> 2 samples
-        let downsampled = AudioCapture::simple_resample(&samples, 4, 2);
-        assert_eq!(downsampled.len(), 2);
+        let mut output = vec![0.0; 32000];
+        assert_eq!(buffer.read(&mut output), 16000);
 
-        // No resampling
-        let same = AudioCapture::simple_resample(&samples, 4, 4);
-        assert_eq!(same, samples);
+        // A constant signal stays constant, without clicks at callback boundaries
+        assert!(output[1000..15000].iter().all(|&s| (s - 0.25).abs() < 1e-3));
     }
 
     #[test]
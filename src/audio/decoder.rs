@@ -0,0 +1,260 @@
+//! Audio file decoding
+//!
+//! Decodes WAV, FLAC, OGG/Vorbis and MP3 files with `symphonia` and converts
+//! them to mono at the requested sample rate, block by block, so long
+//! recordings never need to be held in memory at their original rate.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::resampler::StreamingResampler;
+
+/// Streaming decoder producing mono audio at a fixed sample rate
+pub struct AudioFileDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    source_rate: u32,
+    /// Length of the track, when the container reports it
+    duration: Option<Duration>,
+    resampler: StreamingResampler,
+    finished: bool,
+}
+
+impl AudioFileDecoder {
+    /// Open a file for decoding to mono audio at `target_rate`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened, its format is not
+    /// recognized or it has no decodable audio track.
+    pub fn open(path: &Path, target_rate: u32) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .with_context(|| format!("Unsupported audio format: {}", path.display()))?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .context("No audio track found")?;
+        let params = &track.codec_params;
+
+        let source_rate = params.sample_rate.context("Unknown sample rate")?;
+        let duration = params
+            .n_frames
+            .map(|frames| Duration::from_secs_f64(frames as f64 / source_rate as f64));
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .context("Unsupported audio codec")?;
+
+        log::info!(
+            "Decoding {} ({} Hz{})",
+            path.display(),
+            source_rate,
+            duration
+                .map(|d| format!(", {:.1}s", d.as_secs_f32()))
+                .unwrap_or_default()
+        );
+
+        Ok(Self {
+            track_id: track.id,
+            format,
+            decoder,
+            source_rate,
+            duration,
+            resampler: StreamingResampler::new(source_rate, target_rate)?,
+            finished: false,
+        })
+    }
+
+    /// Decode the next block of audio
+    ///
+    /// Returns `None` once the whole file has been decoded.
+    pub fn next_block(&mut self) -> Result<Option<Vec<f32>>> {
+        while !self.finished {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    self.finished = true;
+                    let tail = self.resampler.flush()?;
+                    return Ok((!tail.is_empty()).then_some(tail));
+                }
+                Err(e) => return Err(e).context("Failed to read audio packet"),
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(e)) => {
+                    // Corrupt packets are skipped, like most players do
+                    log::warn!("Skipping undecodable packet: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(e).context("Failed to decode audio"),
+            };
+
+            let spec = *decoded.spec();
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+
+            let mono = downmix(buffer.samples(), spec.channels.count());
+            let resampled = self.resampler.process(&mono)?;
+            if !resampled.is_empty() {
+                return Ok(Some(resampled));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decode the rest of the file at once
+    pub fn decode_all(mut self) -> Result<Vec<f32>> {
+        let mut samples = Vec::new();
+        while let Some(block) = self.next_block()? {
+            samples.extend(block);
+        }
+        Ok(samples)
+    }
+
+    /// Sample rate of the file
+    pub fn source_rate(&self) -> u32 {
+        self.source_rate
+    }
+
+    /// Length of the file, if known
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+}
+
+/// Average interleaved channels into mono
+fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Write a 16-bit WAV file with a 440Hz tone on every channel
+    fn write_wav(name: &str, rate: u32, channels: u16, seconds: f32) -> PathBuf {
+        let dir = std::env::temp_dir().join("voxai_decoder_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..(rate as f32 * seconds) as usize {
+            let t = i as f32 / rate as f32;
+            let sample = (std::f32::consts::TAU * 440.0 * t).sin() * 0.5;
+            for _ in 0..channels {
+                writer
+                    .write_sample((sample * i16::MAX as f32) as i16)
+                    .unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_decode_wav_to_16k_mono() {
+        let path = write_wav("stereo_44k.wav", 44100, 2, 1.5);
+
+        let decoder = AudioFileDecoder::open(&path, 16000).unwrap();
+        assert_eq!(decoder.source_rate(), 44100);
+        assert_eq!(decoder.duration(), Some(Duration::from_secs_f32(1.5)));
+
+        let samples = decoder.decode_all().unwrap();
+        assert_eq!(samples.len(), 24000);
+
+        // Stereo channels are averaged, so the tone keeps its amplitude
+        let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.02, "peak {}", peak);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_decode_in_blocks() {
+        let path = write_wav("mono_16k.wav", 16000, 1, 2.0);
+
+        let mut decoder = AudioFileDecoder::open(&path, 16000).unwrap();
+        let mut blocks = 0;
+        let mut total = 0;
+        while let Some(block) = decoder.next_block().unwrap() {
+            blocks += 1;
+            total += block.len();
+        }
+        assert!(blocks > 1);
+        assert_eq!(total, 32000);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = std::env::temp_dir().join("voxai_decoder_test/missing.wav");
+        assert!(AudioFileDecoder::open(&path, 16000).is_err());
+    }
+
+    #[test]
+    fn test_not_audio() {
+        let dir = std::env::temp_dir().join("voxai_decoder_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "not audio").unwrap();
+
+        assert!(AudioFileDecoder::open(&path, 16000).is_err());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_downmix() {
+        assert_eq!(downmix(&[1.0, 3.0, -1.0, 1.0], 2), vec![2.0, 0.0]);
+        assert_eq!(downmix(&[0.5, 0.25], 1), vec![0.5, 0.25]);
+    }
+}
@@ -5,9 +5,14 @@
 //! - Noise reduction (RNNoise)
 //! - Voice Activity Detection (VAD)
 //! - Audio buffering and resampling
+//! - Audio file decoding (WAV, FLAC, OGG, MP3)
+//! - Preprocessing ahead of transcription (16kHz denoising)
 
 pub mod buffer;
 pub mod capture;
+pub mod decoder;
 pub mod device;
 pub mod noise_reduction;
+pub mod preprocess;
+pub mod resampler;
 pub mod vad;
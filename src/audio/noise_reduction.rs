@@ -12,6 +12,9 @@ const RNNOISE_SAMPLE_RATE: u32 = 48000;
 /// Frame size for RNNoise (480 samples @ 48kHz = 10ms)
 const RNNOISE_FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
 
+/// RNNoise works on 16-bit sample values stored as f32
+const I16_SCALE: f32 = 32768.0;
+
 /// Noise reduction processor
 pub struct NoiseReducer {
     denoiser: Box<DenoiseState<'static>>,
@@ -59,14 +62,18 @@ impl NoiseReducer {
 
     /// Process a single frame (480 samples @ 48kHz)
     fn process_frame(&mut self, frame: &[f32]) -> Vec<f32> {
-        // RNNoise expects input/output buffers
+        // RNNoise expects input/output buffers in 16-bit range
+        let input: Vec<f32> = frame.iter().map(|&s| s * I16_SCALE).collect();
         let mut output = vec![0.0f32; RNNOISE_FRAME_SIZE];
 
         // Process with RNNoise
         // Returns voice probability (not used for now)
-        let _voice_prob = self.denoiser.process_frame(&mut output, frame);
+        let _voice_prob = self.denoiser.process_frame(&mut output, &input);
 
         output
+            .iter()
+            .map(|&s| (s / I16_SCALE).clamp(-1.0, 1.0))
+            .collect()
     }
 
     /// Enable noise reduction
@@ -101,6 +108,11 @@ impl NoiseReducer {
         RNNOISE_SAMPLE_RATE
     }
 
+    /// Number of samples processed at a time (10ms @ 48kHz)
+    pub fn frame_size() -> usize {
+        RNNOISE_FRAME_SIZE
+    }
+
     /// Clear internal buffers
     pub fn reset(&mut self) {
         self.input_buffer.clear();
@@ -192,6 +204,19 @@ mod tests {
         assert_eq!(output2.len(), RNNOISE_FRAME_SIZE);
     }
 
+    #[test]
+    fn test_output_stays_in_range() {
+        let mut reducer = NoiseReducer::new();
+
+        let input: Vec<f32> = (0..RNNOISE_FRAME_SIZE * 10)
+            .map(|i| (i as f32 / 7.0).sin() * 0.9)
+            .collect();
+        let output = reducer.process(&input);
+
+        assert_eq!(output.len(), input.len());
+        assert!(output.iter().all(|s| (-1.0..=1.0).contains(s)));
+    }
+
     #[test]
     fn test_required_sample_rate() {
         assert_eq!(NoiseReducer::required_sample_rate(), 48000);
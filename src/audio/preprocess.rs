@@ -0,0 +1,371 @@
+//! Audio preprocessing ahead of transcription
+//!
+//! The pipeline works at 16kHz, but RNNoise only runs at 48kHz. The
+//! [`DenoiseStage`] upsamples, denoises frame by frame and downsamples back,
+//! trimming the delay of each step so the output stays sample-aligned with
+//! the input. Chunk positions computed downstream therefore still refer to
+//! the original stream.
+
+use anyhow::{Context, Result};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::noise_reduction::NoiseReducer;
+use super::resampler::StreamingResampler;
+
+/// Delay introduced by RNNoise's overlapping analysis window (one frame @ 48kHz)
+const RNNOISE_DELAY_SAMPLES: usize = 480;
+
+/// Message passed between the streaming stages of the pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioMessage {
+    /// Next block of samples
+    Samples(Vec<f32>),
+    /// End of an utterance: stages emit everything they hold, then pass the
+    /// flush on
+    Flush,
+}
+
+/// 16k → 48k → RNNoise → 16k noise reduction stage
+///
+/// The stage can be switched on and off while audio is flowing through the
+/// shared flag returned by [`DenoiseStage::enabled_flag`]. Switching drains
+/// or restarts the chain without losing or duplicating samples.
+pub struct DenoiseStage {
+    sample_rate: u32,
+    enabled: Arc<AtomicBool>,
+    /// Whether audio currently goes through the denoising chain
+    active: bool,
+    upsampler: StreamingResampler,
+    reducer: NoiseReducer,
+    downsampler: StreamingResampler,
+    /// RNNoise delay still to drop from its output
+    reducer_delay_to_skip: usize,
+    input_total: u64,
+    output_total: u64,
+}
+
+impl DenoiseStage {
+    /// Create a stage for audio at `sample_rate`, controlled by `enabled`
+    pub fn new(sample_rate: u32, enabled: Arc<AtomicBool>) -> Result<Self> {
+        let rnnoise_rate = NoiseReducer::required_sample_rate();
+
+        Ok(Self {
+            sample_rate,
+            active: enabled.load(Ordering::Relaxed),
+            enabled,
+            upsampler: StreamingResampler::new(sample_rate, rnnoise_rate)?,
+            reducer: NoiseReducer::new(),
+            downsampler: StreamingResampler::new(rnnoise_rate, sample_rate)?,
+            reducer_delay_to_skip: RNNOISE_DELAY_SAMPLES,
+            input_total: 0,
+            output_total: 0,
+        })
+    }
+
+    /// Flag toggling noise reduction at runtime
+    pub fn enabled_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.enabled)
+    }
+
+    /// Process a block of samples
+    ///
+    /// Output may be shorter than the input while denoising, the difference
+    /// being reported by [`DenoiseStage::latency`].
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>> {
+        let mut output = Vec::with_capacity(input.len());
+
+        let enabled = self.enabled.load(Ordering::Relaxed);
+        if enabled != self.active {
+            log::info!(
+                "Noise reduction {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+            if self.active {
+                // Let the audio already in the chain out before bypassing it
+                output.extend(self.drain()?);
+            } else {
+                self.restart()?;
+            }
+            self.active = enabled;
+        }
+
+        self.input_total += input.len() as u64;
+
+        if self.active {
+            let upsampled = self.upsampler.process(input)?;
+            let denoised = self.denoise(&upsampled);
+            output.extend(self.downsampler.process(&denoised)?);
+        } else {
+            output.extend_from_slice(input);
+        }
+
+        self.output_total += output.len() as u64;
+        Ok(output)
+    }
+
+    /// Emit the audio still held in the chain (end of stream)
+    ///
+    /// Afterwards the total output length equals the total input length.
+    pub fn finish(&mut self) -> Result<Vec<f32>> {
+        let output = self.drain()?;
+        self.output_total += output.len() as u64;
+        Ok(output)
+    }
+
+    /// How far the output lags behind the input
+    pub fn latency(&self) -> Duration {
+        let lag = self.input_total.saturating_sub(self.output_total);
+        Duration::from_secs_f64(lag as f64 / self.sample_rate as f64)
+    }
+
+    /// Run RNNoise and drop its initial delay
+    fn denoise(&mut self, samples_48k: &[f32]) -> Vec<f32> {
+        let mut denoised = self.reducer.process(samples_48k);
+        let skip = self.reducer_delay_to_skip.min(denoised.len());
+        self.reducer_delay_to_skip -= skip;
+        denoised.drain(..skip);
+        denoised
+    }
+
+    /// Flush every step of the chain and return exactly the samples owed
+    fn drain(&mut self) -> Result<Vec<f32>> {
+        let owed = self.input_total.saturating_sub(self.output_total) as usize;
+
+        let mut output = Vec::with_capacity(owed);
+        if self.active {
+            let mut upsampled = self.upsampler.flush()?;
+            // Pad so the partial frame and the RNNoise delay come out
+            upsampled.resize(
+                upsampled.len() + RNNOISE_DELAY_SAMPLES + NoiseReducer::frame_size(),
+                0.0,
+            );
+            let denoised = self.denoise(&upsampled);
+            output.extend(self.downsampler.process(&denoised)?);
+            output.extend(self.downsampler.flush()?);
+        }
+        output.resize(owed, 0.0);
+
+        self.restart()?;
+        Ok(output)
+    }
+
+    /// Reset the chain so it starts aligned with the next input sample
+    fn restart(&mut self) -> Result<()> {
+        self.upsampler.reset()?;
+        self.reducer.reset();
+        self.downsampler.reset()?;
+        self.reducer_delay_to_skip = RNNOISE_DELAY_SAMPLES;
+        Ok(())
+    }
+}
+
+/// Run a [`DenoiseStage`] on a dedicated thread
+///
+/// Returns the receiver for the processed audio. A [`AudioMessage::Flush`]
+/// drains the chain before being forwarded. The thread exits, flushing the
+/// chain, once `audio_rx` is closed.
+///
+/// # Errors
+///
+/// Returns an error if the resamplers or the thread cannot be created.
+pub fn spawn_denoise_worker(
+    mut audio_rx: mpsc::UnboundedReceiver<AudioMessage>,
+    sample_rate: u32,
+    enabled: Arc<AtomicBool>,
+) -> Result<(mpsc::UnboundedReceiver<AudioMessage>, JoinHandle<()>)> {
+    let mut stage = DenoiseStage::new(sample_rate, enabled)?;
+    let (output_tx, output_rx) = mpsc::unbounded_channel();
+
+    let handle = std::thread::Builder::new()
+        .name("voxai-denoise".to_string())
+        .spawn(move || {
+            log::info!("Noise reduction worker started");
+
+            while let Some(message) = audio_rx.blocking_recv() {
+                let (output, flush) = match message {
+                    AudioMessage::Samples(samples) => (stage.process(&samples), false),
+                    AudioMessage::Flush => (stage.finish(), true),
+                };
+                let output = match output {
+                    Ok(output) => output,
+                    Err(e) => {
+                        log::error!("Noise reduction failed: {}", e);
+                        return;
+                    }
+                };
+                log::trace!("Noise reduction latency: {:?}", stage.latency());
+
+                if !output.is_empty() && output_tx.send(AudioMessage::Samples(output)).is_err() {
+                    return;
+                }
+                if flush && output_tx.send(AudioMessage::Flush).is_err() {
+                    return;
+                }
+            }
+
+            match stage.finish() {
+                Ok(output) if !output.is_empty() => {
+                    let _ = output_tx.send(AudioMessage::Samples(output));
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Failed to flush noise reduction: {}", e),
+            }
+
+            log::info!("Noise reduction worker stopped");
+        })
+        .context("Failed to spawn noise reduction thread")?;
+
+    Ok((output_rx, handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Noise bursts with a speech-like envelope
+    fn bursts(len: usize) -> Vec<f32> {
+        let mut seed = 1u32;
+        (0..len)
+            .map(|i| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let noise = seed as f32 / u32::MAX as f32 - 0.5;
+                let envelope = (i as f32 / 16000.0 * std::f32::consts::TAU * 3.0)
+                    .sin()
+                    .max(0.0);
+                noise * envelope * 0.6
+            })
+            .collect()
+    }
+
+    fn stage(enabled: bool) -> DenoiseStage {
+        DenoiseStage::new(16000, Arc::new(AtomicBool::new(enabled))).unwrap()
+    }
+
+    fn run(stage: &mut DenoiseStage, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        for block in input.chunks(1600) {
+            output.extend(stage.process(block).unwrap());
+        }
+        output.extend(stage.finish().unwrap());
+        output
+    }
+
+    #[test]
+    fn test_disabled_is_passthrough() {
+        let mut stage = stage(false);
+        let input = bursts(16000);
+        assert_eq!(run(&mut stage, &input), input);
+    }
+
+    #[test]
+    fn test_output_length_matches_input() {
+        let mut stage = stage(true);
+        let input = bursts(16000 + 123);
+
+        let mut output = stage.process(&input[..1600]).unwrap();
+        assert!(stage.latency() > Duration::ZERO);
+
+        output.extend(run(&mut stage, &input[1600..]));
+        assert_eq!(output.len(), input.len());
+        assert_eq!(stage.latency(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_output_is_time_aligned() {
+        let mut stage = stage(true);
+        let input = bursts(16000);
+        let output = run(&mut stage, &input);
+        assert_eq!(output.len(), input.len());
+
+        // The correlation between input and output peaks at lag 0
+        let correlation = |lag: usize| -> f32 {
+            input[..input.len() - lag]
+                .iter()
+                .zip(&output[lag..])
+                .map(|(a, b)| a * b)
+                .sum()
+        };
+        let best = (0..200)
+            .max_by(|&a, &b| correlation(a).total_cmp(&correlation(b)))
+            .unwrap();
+        assert!(best <= 1, "output lags by {} samples", best);
+    }
+
+    #[test]
+    fn test_runtime_toggle_keeps_alignment() {
+        let mut stage = stage(true);
+        let flag = stage.enabled_flag();
+        let input = bursts(16000 * 2);
+
+        let mut output = Vec::new();
+        for (i, block) in input.chunks(1600).enumerate() {
+            if i == 5 {
+                flag.store(false, Ordering::Relaxed);
+            }
+            if i == 12 {
+                flag.store(true, Ordering::Relaxed);
+            }
+            output.extend(stage.process(block).unwrap());
+        }
+        output.extend(stage.finish().unwrap());
+        assert_eq!(output.len(), input.len());
+
+        // While bypassed, samples come through untouched at their position
+        assert_eq!(output[1600 * 5..1600 * 12], input[1600 * 5..1600 * 12]);
+    }
+
+    #[tokio::test]
+    async fn test_worker_flushes_on_close() {
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (mut output_rx, handle) =
+            spawn_denoise_worker(audio_rx, 16000, Arc::new(AtomicBool::new(true))).unwrap();
+
+        let input = bursts(16000);
+        for block in input.chunks(1000) {
+            audio_tx
+                .send(AudioMessage::Samples(block.to_vec()))
+                .unwrap();
+        }
+        drop(audio_tx);
+
+        let mut total = 0;
+        while let Some(AudioMessage::Samples(output)) = output_rx.recv().await {
+            total += output.len();
+        }
+        assert_eq!(total, input.len());
+        handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_worker_drains_on_flush() {
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (mut output_rx, handle) =
+            spawn_denoise_worker(audio_rx, 16000, Arc::new(AtomicBool::new(true))).unwrap();
+
+        let input = bursts(8000);
+        audio_tx.send(AudioMessage::Samples(input.clone())).unwrap();
+        audio_tx.send(AudioMessage::Flush).unwrap();
+
+        // Everything sent before the flush comes out ahead of it
+        let mut total = 0;
+        loop {
+            match output_rx.recv().await.unwrap() {
+                AudioMessage::Samples(output) => total += output.len(),
+                AudioMessage::Flush => break,
+            }
+        }
+        assert_eq!(total, input.len());
+
+        drop(audio_tx);
+        assert!(output_rx.recv().await.is_none());
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,302 @@
+//! Streaming sample rate conversion
+//!
+//! Wraps a `rubato` FFT resampler so audio can be pushed in blocks of any
+//! size. Input is buffered until a full resampler chunk is available, and
+//! the filter delay is trimmed from the output so that output sample `n`
+//! lines up with input time `n / output_rate`.
+
+use anyhow::{Context, Result};
+use rubato::{FftFixedIn, Resampler};
+use std::time::Duration;
+
+/// Sub-chunks used by the FFT resampler (more = lower latency, more CPU)
+const SUB_CHUNKS: usize = 2;
+
+/// Stateful mono resampler
+pub struct StreamingResampler {
+    from_rate: u32,
+    to_rate: u32,
+    chunk_size: usize,
+    /// `None` when both rates are equal
+    resampler: Option<FftFixedIn<f32>>,
+    /// Input waiting for a full chunk
+    pending: Vec<f32>,
+    /// Filter delay still to drop from the output
+    delay_to_skip: usize,
+    input_total: u64,
+    output_total: u64,
+}
+
+impl StreamingResampler {
+    /// Create a resampler processing roughly 10ms of input at a time
+    pub fn new(from_rate: u32, to_rate: u32) -> Result<Self> {
+        Self::with_chunk_size(from_rate, to_rate, (from_rate / 100).max(1) as usize)
+    }
+
+    /// Create a resampler with an explicit input chunk size (in samples)
+    ///
+    /// Larger chunks are cheaper but add latency.
+    pub fn with_chunk_size(from_rate: u32, to_rate: u32, chunk_size: usize) -> Result<Self> {
+        if from_rate == 0 || to_rate == 0 {
+            anyhow::bail!("Invalid resampling {} Hz -> {} Hz", from_rate, to_rate);
+        }
+
+        let mut resampler = Self {
+            from_rate,
+            to_rate,
+            chunk_size,
+            resampler: None,
+            pending: Vec::new(),
+            delay_to_skip: 0,
+            input_total: 0,
+            output_total: 0,
+        };
+        resampler.reset()?;
+        Ok(resampler)
+    }
+
+    /// Resample a block of samples
+    ///
+    /// Returns whatever output is ready; the rest is held until more input
+    /// arrives or [`StreamingResampler::flush`] is called.
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>> {
+        self.input_total += input.len() as u64;
+
+        let Some(resampler) = self.resampler.as_mut() else {
+            self.output_total += input.len() as u64;
+            return Ok(input.to_vec());
+        };
+
+        self.pending.extend_from_slice(input);
+
+        let mut output = Vec::new();
+        let mut consumed = 0;
+        while self.pending.len() - consumed >= resampler.input_frames_next() {
+            let needed = resampler.input_frames_next();
+            let block = &self.pending[consumed..consumed + needed];
+            let resampled = resampler
+                .process(&[block], None)
+                .context("Resampling failed")?;
+            consumed += needed;
+
+            let resampled = &resampled[0];
+            let skip = self.delay_to_skip.min(resampled.len());
+            self.delay_to_skip -= skip;
+            output.extend_from_slice(&resampled[skip..]);
+        }
+        self.pending.drain(..consumed);
+
+        self.output_total += output.len() as u64;
+        Ok(output)
+    }
+
+    /// Emit the remaining output and start a new stream
+    ///
+    /// After flushing, the total output length matches the total input
+    /// length converted to the output rate.
+    pub fn flush(&mut self) -> Result<Vec<f32>> {
+        let owed = self.expected_output().saturating_sub(self.output_total) as usize;
+        let mut output = Vec::with_capacity(owed);
+
+        if self.resampler.is_some() {
+            // Push silence through until the buffered audio comes out
+            let padding = vec![0.0; self.chunk_size];
+            while output.len() < owed {
+                let input_total = self.input_total;
+                output.extend(self.process(&padding)?);
+                self.input_total = input_total;
+            }
+            output.truncate(owed);
+        }
+
+        self.reset()?;
+        Ok(output)
+    }
+
+    /// Drop all buffered audio and start a new stream
+    pub fn reset(&mut self) -> Result<()> {
+        self.resampler = if self.from_rate == self.to_rate {
+            None
+        } else {
+            Some(
+                FftFixedIn::new(
+                    self.from_rate as usize,
+                    self.to_rate as usize,
+                    self.chunk_size,
+                    SUB_CHUNKS,
+                    1,
+                )
+                .context("Failed to create resampler")?,
+            )
+        };
+        self.delay_to_skip = self
+            .resampler
+            .as_ref()
+            .map_or(0, |resampler| resampler.output_delay());
+        self.pending.clear();
+        self.input_total = 0;
+        self.output_total = 0;
+        Ok(())
+    }
+
+    /// How far the output lags behind the input
+    pub fn latency(&self) -> Duration {
+        let lag = self.expected_output().saturating_sub(self.output_total);
+        Duration::from_secs_f64(lag as f64 / self.to_rate as f64)
+    }
+
+    /// Input sample rate
+    pub fn input_rate(&self) -> u32 {
+        self.from_rate
+    }
+
+    /// Output sample rate
+    pub fn output_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    /// Output length corresponding to all input received so far
+    fn expected_output(&self) -> u64 {
+        self.input_total * self.to_rate as u64 / self.from_rate as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    /// Push audio in irregular blocks, like a device callback would
+    fn run(resampler: &mut StreamingResampler, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        for (i, block) in input.chunks(317).enumerate() {
+            output.extend(resampler.process(block).unwrap());
+            if i == 0 {
+                assert!(resampler.latency() > Duration::ZERO);
+            }
+        }
+        output.extend(resampler.flush().unwrap());
+        output
+    }
+
+    #[test]
+    fn test_same_rate_passthrough() {
+        let mut resampler = StreamingResampler::new(16000, 16000).unwrap();
+        let input = sine(440.0, 16000, 1000);
+        assert_eq!(resampler.process(&input).unwrap(), input);
+        assert_eq!(resampler.latency(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_invalid_rate() {
+        assert!(StreamingResampler::new(0, 16000).is_err());
+    }
+
+    #[test]
+    fn test_output_length_matches_rate() {
+        for (from, to) in [(16000, 48000), (48000, 16000), (44100, 16000)] {
+            let mut resampler = StreamingResampler::new(from, to).unwrap();
+            let output = run(&mut resampler, &vec![0.1; from as usize / 2]);
+            assert_eq!(output.len(), to as usize / 2, "{} -> {}", from, to);
+            assert_eq!(resampler.latency(), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_output_is_time_aligned() {
+        for (from, to) in [(16000, 48000), (48000, 16000)] {
+            let mut resampler = StreamingResampler::new(from, to).unwrap();
+            let output = run(&mut resampler, &sine(440.0, from, from as usize));
+            let expected = sine(440.0, to, to as usize);
+
+            // Ignore the edges, where the filter sees silence
+            let edge = to as usize / 20;
+            let max_error = output[edge..output.len() - edge]
+                .iter()
+                .zip(&expected[edge..])
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0f32, f32::max);
+            assert!(max_error < 0.01, "{} -> {}: error {}", from, to, max_error);
+        }
+    }
+
+    /// Linear sine sweep from `start` to `end` Hz over `len` samples
+    fn sweep(start: f32, end: f32, rate: u32, len: usize) -> Vec<f32> {
+        let duration = len as f32 / rate as f32;
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / rate as f32;
+                let phase = start * t + (end - start) * t * t / (2.0 * duration);
+                (std::f32::consts::TAU * phase).sin() * 0.5
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn gain_db(input: &[f32], output: &[f32]) -> f32 {
+        20.0 * (rms(output) / rms(input)).log10()
+    }
+
+    #[test]
+    fn test_sweep_above_nyquist_is_rejected() {
+        for rate in [44100, 48000, 96000] {
+            let mut resampler = StreamingResampler::new(rate, 16000).unwrap();
+            // Everything between 9kHz and the device Nyquist would alias
+            let input = sweep(9000.0, rate as f32 / 2.0 - 500.0, rate, rate as usize);
+            let output = run(&mut resampler, &input);
+
+            let gain = gain_db(&input, &output);
+            assert!(gain < -40.0, "{} Hz: aliasing at {:.1} dB", rate, gain);
+        }
+    }
+
+    #[test]
+    fn test_sweep_in_passband_is_preserved() {
+        for rate in [44100, 48000, 96000] {
+            let mut resampler = StreamingResampler::new(rate, 16000).unwrap();
+            let input = sweep(100.0, 7000.0, rate, rate as usize);
+            let output = run(&mut resampler, &input);
+
+            let gain = gain_db(&input, &output);
+            assert!(
+                gain.abs() < 0.5,
+                "{} Hz: passband gain {:.2} dB",
+                rate,
+                gain
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_size_does_not_matter() {
+        // State is kept across calls, so callback boundaries leave no trace
+        let input = sweep(100.0, 7000.0, 44100, 44100);
+
+        let mut whole = StreamingResampler::new(44100, 16000).unwrap();
+        let mut expected = whole.process(&input).unwrap();
+        expected.extend(whole.flush().unwrap());
+
+        let mut blocks = StreamingResampler::new(44100, 16000).unwrap();
+        let mut output = Vec::new();
+        for block in input.chunks(441 + 7) {
+            output.extend(blocks.process(block).unwrap());
+        }
+        output.extend(blocks.flush().unwrap());
+
+        assert_eq!(output.len(), expected.len());
+        let max_diff = output
+            .iter()
+            .zip(&expected)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_diff < 1e-4, "max difference {}", max_diff);
+    }
+}
@@ -27,6 +27,19 @@ impl VoiceActivityDetector {
         Self::with_mode_and_rate(VadMode::Quality, 16000)
     }
 
+    /// Create a new VAD from the 0-3 aggressiveness level used in the config
+    pub fn with_aggressiveness(level: u8, sample_rate: u32) -> Result<Self> {
+        let mode = match level {
+            0 => VadMode::Quality,
+            1 => VadMode::LowBitrate,
+            2 => VadMode::Aggressive,
+            3 => VadMode::VeryAggressive,
+            _ => anyhow::bail!("Invalid VAD aggressiveness {}. Must be 0-3", level),
+        };
+
+        Self::with_mode_and_rate(mode, sample_rate)
+    }
+
     /// Create a new VAD with specific mode and sample rate
     pub fn with_mode_and_rate(mode: VadMode, sample_rate: u32) -> Result<Self> {
         // Validate sample rate
@@ -189,6 +202,14 @@ mod tests {
         vad.set_mode(VadMode::VeryAggressive);
     }
 
+    #[test]
+    fn test_vad_aggressiveness_levels() {
+        for level in 0..=3 {
+            assert!(VoiceActivityDetector::with_aggressiveness(level, 16000).is_ok());
+        }
+        assert!(VoiceActivityDetector::with_aggressiveness(4, 16000).is_err());
+    }
+
     #[test]
     fn test_invalid_sample_rate() {
         let vad = VoiceActivityDetector::with_mode_and_rate(VadMode::Quality, 44100);
@@ -0,0 +1,161 @@
+//! `voxai bench`: measure the Whisper models on this machine
+//!
+//! Each downloaded model transcribes the reference clip (or a recording
+//! given with `--clip`); the results are cached so that the automatic model
+//! selection picks the most accurate model meeting
+//! `transcription.latency_budget`.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::audio::decoder::AudioFileDecoder;
+use crate::config::{self, Language};
+use crate::transcription::bench::{self, BenchmarkCache, BenchmarkResult, Machine};
+use crate::transcription::downloader;
+use crate::transcription::engine::ENGINE_SAMPLE_RATE;
+use crate::transcription::models::{is_model_downloaded, ModelRegistry};
+use crate::transcription::whisper::{WhisperContext, WhisperOptions};
+
+/// Arguments of `voxai bench`
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// Models to benchmark; all downloaded models by default
+    pub models: Vec<String>,
+
+    /// Transcribe this recording instead of the bundled reference clip
+    #[arg(long)]
+    pub clip: Option<PathBuf>,
+
+    /// Configure the model selected for the latency budget
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Run `voxai bench`
+pub async fn run(args: BenchArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let models = if args.models.is_empty() {
+        downloader::list_downloaded_models(&registry).await?
+    } else {
+        let models = args
+            .models
+            .iter()
+            .map(|name| registry.resolve(name).cloned())
+            .collect::<Result<Vec<_>>>()?;
+        for model in &models {
+            anyhow::ensure!(
+                is_model_downloaded(model)?,
+                "The {} model is not downloaded; run `voxai models download {}`",
+                model,
+                model.name
+            );
+        }
+        models
+    };
+    anyhow::ensure!(
+        !models.is_empty(),
+        "No model downloaded; run `voxai models download <name>` first"
+    );
+
+    let (clip, language) = match &args.clip {
+        Some(path) => (
+            AudioFileDecoder::open(path, ENGINE_SAMPLE_RATE)?.decode_all()?,
+            Language::from_code(&config.transcription.language),
+        ),
+        None => (bench::reference_clip()?, Language::English),
+    };
+    let clip = Arc::new(clip);
+    let options = WhisperOptions::from_config(&config.transcription);
+    let machine = Machine::detect(options.use_gpu);
+    let mut cache = BenchmarkCache::load().unwrap_or_else(|e| {
+        log::warn!("Discarding benchmark results: {:#}", e);
+        BenchmarkCache::default()
+    });
+
+    let mut results = Vec::new();
+    for model in models {
+        if !args.json {
+            eprintln!("Benchmarking {} model...", model);
+        }
+        let clip = Arc::clone(&clip);
+        let options = options.clone();
+        let language = language.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            bench::benchmark(
+                &model,
+                |model| WhisperContext::with_options(model, options),
+                &clip,
+                &language,
+            )
+        })
+        .await
+        .context("Benchmark task panicked")??;
+
+        cache.insert(&machine, result.clone());
+        results.push(result);
+    }
+    cache.save()?;
+
+    let selected = bench::select_model(&config, &cache, &machine);
+    if args.json {
+        let output = serde_json::json!({
+            "machine": machine,
+            "results": results,
+            "latency_budget": config.transcription.latency_budget,
+            "selected": selected.as_ref().map(|model| &model.name),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_results(&machine, &results);
+        match &selected {
+            Some(model) => println!(
+                "\nMost accurate model within the latency budget ({:.2}x real time): {}",
+                config.transcription.latency_budget, model
+            ),
+            None => println!(
+                "\nNo downloaded model transcribes within the latency budget ({:.2}x real time)",
+                config.transcription.latency_budget
+            ),
+        }
+    }
+
+    if let (true, Some(model)) = (args.apply, selected) {
+        config.transcription.model = model.name.clone();
+        config::save_config(&config)?;
+        if !args.json {
+            println!("Configured the {} model", model);
+        }
+    }
+    Ok(())
+}
+
+fn print_results(machine: &Machine, results: &[BenchmarkResult]) {
+    println!(
+        "{} ({} CPUs, {} MB RAM{})\n",
+        machine.cpu,
+        machine.cpus,
+        machine.memory_mb,
+        if machine.gpu { ", GPU" } else { "" }
+    );
+    println!(
+        "{:<22} {:>8} {:>8} {:>11} {:>9}",
+        "MODEL", "LOAD", "RUN", "REAL TIME", "MEMORY"
+    );
+    for result in results {
+        println!(
+            "{:<22} {:>7.1}s {:>7.1}s {:>10.2}x {:>6} MB",
+            result.model,
+            result.load_secs,
+            result.transcribe_secs,
+            result.real_time_factor,
+            result.peak_memory_mb
+        );
+    }
+}
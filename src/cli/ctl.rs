@@ -0,0 +1,64 @@
+//! `voxai ctl`: control the running app
+//!
+//! Meant to be bound to desktop shortcuts where global hotkeys do not work,
+//! e.g. `voxai ctl toggle` in a Wayland compositor's key bindings.
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::ipc::protocol::{ControlCommand, ControlResponse};
+
+/// Arguments of `voxai ctl`
+#[derive(Debug, Args)]
+pub struct CtlArgs {
+    #[command(subcommand)]
+    pub action: CtlAction,
+}
+
+/// Commands accepted by the running app
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum CtlAction {
+    /// Start recording
+    Start,
+    /// Stop recording and transcribe
+    Stop,
+    /// Start or stop recording
+    Toggle,
+    /// Show whether VoxAI is recording, transcribing or failed
+    Status,
+    /// Switch the Whisper model (tiny, base, small, medium, large-v3...)
+    SetModel { model: String },
+}
+
+impl From<CtlAction> for ControlCommand {
+    fn from(action: CtlAction) -> Self {
+        match action {
+            CtlAction::Start => Self::Start,
+            CtlAction::Stop => Self::Stop,
+            CtlAction::Toggle => Self::Toggle,
+            CtlAction::Status => Self::Status,
+            CtlAction::SetModel { model } => Self::SetModel { model },
+        }
+    }
+}
+
+/// Run `voxai ctl`
+#[cfg(unix)]
+pub async fn run(args: CtlArgs) -> Result<()> {
+    let path = crate::ipc::socket_path()?;
+    let command = ControlCommand::from(args.action);
+
+    match crate::ipc::client::send_command(&path, &command).await? {
+        ControlResponse::Ok { status } => {
+            println!("{} (model: {})", status.state, status.model);
+            Ok(())
+        }
+        ControlResponse::Error { message } => anyhow::bail!(message),
+    }
+}
+
+/// Run `voxai ctl`
+#[cfg(not(unix))]
+pub async fn run(_args: CtlArgs) -> Result<()> {
+    anyhow::bail!("`voxai ctl` is only supported on Linux and macOS")
+}
@@ -0,0 +1,178 @@
+//! Command-line interface
+//!
+//! Without a subcommand VoxAI starts the desktop app. Subcommands run a
+//! single task and exit, so they also work on headless machines.
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+pub mod bench;
+pub mod ctl;
+pub mod models;
+pub mod transcribe;
+
+/// VoxAI - Real-time Audio Transcription
+#[derive(Debug, Parser)]
+#[command(name = "voxai", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Transcribe an audio file (WAV, FLAC, OGG or MP3)
+    Transcribe(transcribe::TranscribeArgs),
+    /// Control the running app (start/stop recording, switch model...)
+    Ctl(ctl::CtlArgs),
+    /// Manage Whisper models
+    Models(models::ModelsArgs),
+    /// Measure the speed and memory use of the models on this machine
+    Bench(bench::BenchArgs),
+    /// Open the settings window
+    Settings,
+}
+
+/// Run a subcommand to completion
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Transcribe(args) => transcribe::run(args).await,
+        Command::Ctl(args) => ctl::run(args).await,
+        Command::Models(args) => models::run(args).await,
+        Command::Bench(args) => bench::run(args).await,
+        Command::Settings => {
+            crate::ui::settings::SettingsWindow::run(crate::config::load_config()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_no_subcommand_starts_app() {
+        let cli = Cli::try_parse_from(["voxai"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_transcribe() {
+        let cli = Cli::try_parse_from([
+            "voxai",
+            "transcribe",
+            "meeting.wav",
+            "-o",
+            "meeting.txt",
+            "--model",
+            "small",
+            "--timestamps",
+        ])
+        .unwrap();
+
+        let Some(Command::Transcribe(args)) = cli.command else {
+            panic!("expected transcribe command");
+        };
+        assert_eq!(args.file.to_str(), Some("meeting.wav"));
+        assert_eq!(
+            args.output.as_deref().and_then(|p| p.to_str()),
+            Some("meeting.txt")
+        );
+        assert_eq!(args.model.as_deref(), Some("small"));
+        assert!(args.language.is_none());
+        assert!(args.timestamps);
+        assert!(!args.no_vad);
+    }
+
+    #[test]
+    fn test_parse_ctl() {
+        let cli = Cli::try_parse_from(["voxai", "ctl", "set-model", "small"]).unwrap();
+        let Some(Command::Ctl(args)) = cli.command else {
+            panic!("expected ctl command");
+        };
+        assert_eq!(
+            args.action,
+            ctl::CtlAction::SetModel {
+                model: "small".to_string()
+            }
+        );
+
+        assert!(Cli::try_parse_from(["voxai", "ctl", "toggle"]).is_ok());
+        assert!(Cli::try_parse_from(["voxai", "ctl", "explode"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        let cli = Cli::try_parse_from([
+            "voxai", "bench", "base", "small", "--clip", "me.wav", "--apply",
+        ])
+        .unwrap();
+        let Some(Command::Bench(args)) = cli.command else {
+            panic!("expected bench command");
+        };
+        assert_eq!(args.models, vec!["base".to_string(), "small".to_string()]);
+        assert_eq!(args.clip, Some(PathBuf::from("me.wav")));
+        assert!(args.apply);
+        assert!(!args.json);
+    }
+
+    #[test]
+    fn test_parse_models_import() {
+        let cli = Cli::try_parse_from([
+            "voxai",
+            "models",
+            "import",
+            "ggml-large-v3-q5_0.bin",
+            "--name",
+            "large",
+        ])
+        .unwrap();
+        let Some(Command::Models(args)) = cli.command else {
+            panic!("expected models command");
+        };
+        assert_eq!(
+            args.action,
+            models::ModelsAction::Import {
+                path: PathBuf::from("ggml-large-v3-q5_0.bin"),
+                name: Some("large".to_string()),
+            }
+        );
+
+        assert!(Cli::try_parse_from(["voxai", "models", "import"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_models() {
+        let cli = Cli::try_parse_from(["voxai", "models", "list", "--json"]).unwrap();
+        let Some(Command::Models(args)) = cli.command else {
+            panic!("expected models command");
+        };
+        assert!(args.json);
+        assert_eq!(
+            args.action,
+            models::ModelsAction::List { downloaded: false }
+        );
+
+        let cli = Cli::try_parse_from(["voxai", "models", "verify", "base", "small"]).unwrap();
+        let Some(Command::Models(args)) = cli.command else {
+            panic!("expected models command");
+        };
+        assert!(!args.json);
+        assert_eq!(
+            args.action,
+            models::ModelsAction::Verify {
+                names: vec!["base".to_string(), "small".to_string()]
+            }
+        );
+
+        assert!(Cli::try_parse_from(["voxai", "models", "prune", "--dry-run"]).is_ok());
+        assert!(Cli::try_parse_from(["voxai", "models", "download"]).is_err());
+    }
+
+    #[test]
+    fn test_transcribe_requires_file() {
+        assert!(Cli::try_parse_from(["voxai", "transcribe"]).is_err());
+    }
+}
@@ -0,0 +1,486 @@
+//! `voxai models`: manage the Whisper model files
+//!
+//! Lists the models of the registry with their disk usage and
+//! requirements, downloads, verifies and deletes them, and prunes the
+//! downloads the configuration does not use. Models already on disk
+//! (quantized or fine-tuned ggml files, downloads made on another machine)
+//! are imported into the models directory and added to the registry in the
+//! config file.
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::config;
+use crate::transcription::downloader::{self, DownloadMonitor, DownloadPhase, DownloadProgress};
+use crate::transcription::models::{configured_model, get_models_dir, ModelRegistry};
+
+/// Arguments of `voxai models`
+#[derive(Debug, Args)]
+pub struct ModelsArgs {
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub action: ModelsAction,
+}
+
+/// Model management commands
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum ModelsAction {
+    /// List the known models with their disk usage, RAM requirement and speed
+    List {
+        /// Only show downloaded models
+        #[arg(long)]
+        downloaded: bool,
+    },
+    /// Download a model
+    Download { name: String },
+    /// Check downloaded models against their size and checksum
+    Verify {
+        /// Models to check; all downloaded models by default
+        names: Vec<String>,
+    },
+    /// Delete a downloaded model
+    Delete { name: String },
+    /// Delete the downloaded models the configuration does not use
+    Prune {
+        /// Only show what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Also delete imported models, which cannot be downloaded again
+        #[arg(long)]
+        imported: bool,
+    },
+    /// Copy a ggml model file into the models directory
+    Import {
+        /// Model file (ggml-*.bin)
+        path: PathBuf,
+        /// Name of the model; defaults to the file name without its `ggml-`
+        /// prefix
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+/// A registry model and its file on disk
+#[derive(Debug, Clone, Serialize)]
+struct ModelStatus {
+    name: String,
+    file: String,
+    /// Size of the model file in bytes, as published
+    size: u64,
+    downloaded: bool,
+    /// Size of the downloaded file in bytes
+    disk_usage: u64,
+    /// Whether the file can be downloaded again (not an imported model)
+    downloadable: bool,
+    ram_mb: u64,
+    speed_multiplier: Option<f32>,
+    languages: Vec<String>,
+    quantization: Option<String>,
+    /// Model selected in the configuration
+    current: bool,
+}
+
+/// Run `voxai models`
+pub async fn run(args: ModelsArgs) -> Result<()> {
+    let json = args.json;
+    match args.action {
+        ModelsAction::List { downloaded } => list(downloaded, json),
+        ModelsAction::Download { name } => download(&name, json).await,
+        ModelsAction::Verify { names } => verify(&names, json).await,
+        ModelsAction::Delete { name } => delete(&name, json).await,
+        ModelsAction::Prune { dry_run, imported } => {
+            let config = config::load_config()?;
+            let registry = ModelRegistry::from_config(&config.models);
+            let pruned = prune(
+                &registry,
+                &configured_model(&config)?.name,
+                &get_models_dir()?,
+                imported,
+                dry_run,
+            )?;
+            print_pruned(&pruned, dry_run, json)
+        }
+        ModelsAction::Import { path, name } => import(path, name, json).await,
+    }
+}
+
+fn list(downloaded_only: bool, json: bool) -> Result<()> {
+    let config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let current = configured_model(&config)?;
+    let mut statuses = model_statuses(&registry, &current.name, &get_models_dir()?);
+    if downloaded_only {
+        statuses.retain(|status| status.downloaded);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+
+    println!(
+        "  {:<22} {:>9} {:>8} {:>6}  STATUS",
+        "MODEL", "SIZE", "RAM", "SPEED"
+    );
+    for status in &statuses {
+        let ram = match status.ram_mb {
+            0 => "-".to_string(),
+            ram_mb => format_bytes(ram_mb * 1_000_000),
+        };
+        let speed = status
+            .speed_multiplier
+            .map_or("-".to_string(), |speed| format!("{:.1}x", speed));
+        println!(
+            "{} {:<22} {:>9} {:>8} {:>6}  {}",
+            if status.current { "*" } else { " " },
+            status.name,
+            format_bytes(status.size),
+            ram,
+            speed,
+            if status.downloaded { "downloaded" } else { "" }
+        );
+    }
+
+    let downloaded: Vec<&ModelStatus> = statuses.iter().filter(|s| s.downloaded).collect();
+    println!(
+        "\n{} downloaded, {} on disk (* configured model)",
+        downloaded.len(),
+        format_bytes(downloaded.iter().map(|s| s.disk_usage).sum())
+    );
+    Ok(())
+}
+
+async fn download(name: &str, json: bool) -> Result<()> {
+    let config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let model = registry.resolve(name)?;
+
+    let (monitor, events) = DownloadMonitor::channel();
+    let bar = (!json).then(|| tokio::spawn(show_progress(model.to_string(), events)));
+    let cancel = monitor.cancel_token();
+    let ctrl_c = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+        }
+    });
+
+    let result = downloader::download_model(model, &config.models, &monitor).await;
+    ctrl_c.abort();
+    drop(monitor);
+    if let Some(bar) = bar {
+        bar.await?;
+    }
+    let path = result?;
+
+    if json {
+        let result = serde_json::json!({ "name": model.name, "path": path });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!("{} model ready at {}", model, path.display());
+    }
+    Ok(())
+}
+
+/// Draw the progress events of a download as a terminal progress bar
+async fn show_progress(label: String, mut events: UnboundedReceiver<DownloadProgress>) {
+    let mut bar: Option<ProgressBar> = None;
+    while let Some(progress) = events.recv().await {
+        let pb = bar.get_or_insert_with(|| {
+            let pb = match progress.total {
+                Some(total) => ProgressBar::new(total).with_style(
+                    ProgressStyle::default_bar()
+                        .template(
+                            "{msg}\n[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+                        )
+                        .expect("valid progress template")
+                        .progress_chars("█▓▒░ "),
+                ),
+                None => ProgressBar::new_spinner(),
+            };
+            pb.set_message(format!("Downloading {} model", label));
+            pb
+        });
+
+        match progress.phase {
+            DownloadPhase::Downloading => pb.set_position(progress.downloaded),
+            DownloadPhase::Verifying => {
+                pb.set_position(progress.downloaded);
+                pb.set_message(format!("Verifying {} model", label));
+            }
+            DownloadPhase::Done => pb.finish_with_message(format!("{} model downloaded", label)),
+            DownloadPhase::Failed(_) => pb.abandon_with_message("Download failed"),
+            DownloadPhase::Cancelled => {
+                pb.abandon_with_message("Download cancelled, run the command again to resume")
+            }
+        }
+    }
+}
+
+async fn verify(names: &[String], json: bool) -> Result<()> {
+    let config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let models = if names.is_empty() {
+        downloader::list_downloaded_models(&registry).await?
+    } else {
+        names
+            .iter()
+            .map(|name| registry.resolve(name).cloned())
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    let mut results = Vec::new();
+    let mut failed = 0;
+    for model in &models {
+        let valid = downloader::verify_model(model).await?;
+        if !valid {
+            failed += 1;
+        }
+        if !json {
+            println!("{}: {}", model, if valid { "ok" } else { "FAILED" });
+        }
+        results.push(serde_json::json!({ "name": model.name, "valid": valid }));
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    }
+
+    anyhow::ensure!(
+        failed == 0,
+        "{} model(s) missing or corrupt; delete and download them again",
+        failed
+    );
+    Ok(())
+}
+
+async fn delete(name: &str, json: bool) -> Result<()> {
+    let config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let model = registry.resolve(name)?;
+    if configured_model(&config).is_ok_and(|current| current.name == model.name) {
+        log::warn!(
+            "{} is the configured model; download it again before dictating",
+            model
+        );
+    }
+    downloader::delete_model(model).await?;
+
+    if json {
+        println!("{}", serde_json::json!({ "deleted": model.name }));
+    } else {
+        println!("Deleted {} model", model);
+    }
+    Ok(())
+}
+
+async fn import(path: PathBuf, name: Option<String>, json: bool) -> Result<()> {
+    let mut config = config::load_config()?;
+    let registry = ModelRegistry::from_config(&config.models);
+    let model = downloader::import_model(&path, name.as_deref(), &registry).await?;
+
+    // Registry models are already described
+    if registry.get(&model.name).is_none() {
+        config.models.custom.push(model.clone());
+        config::save_config(&config)?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&model)?);
+    } else {
+        println!("Imported {} as model '{}'", path.display(), model.name);
+        println!("Use it with: voxai ctl set-model {}", model.name);
+    }
+    Ok(())
+}
+
+/// Status of every model of `registry`, `current` being the configured one
+fn model_statuses(registry: &ModelRegistry, current: &str, models_dir: &Path) -> Vec<ModelStatus> {
+    registry
+        .all()
+        .iter()
+        .map(|model| {
+            let disk_usage = std::fs::metadata(models_dir.join(&model.file))
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len());
+            ModelStatus {
+                name: model.name.clone(),
+                file: model.file.clone(),
+                size: model.size,
+                downloaded: disk_usage.is_some(),
+                disk_usage: disk_usage.unwrap_or(0),
+                downloadable: model.is_downloadable(),
+                ram_mb: model.ram_mb,
+                speed_multiplier: model.speed_multiplier,
+                languages: model.languages.clone(),
+                quantization: model.quantization.clone(),
+                current: model.name.eq_ignore_ascii_case(current),
+            }
+        })
+        .collect()
+}
+
+/// Delete the downloaded models other than `current`, with their partial
+/// downloads. Imported models are kept unless `imported` is set: they could
+/// not be downloaded again.
+fn prune(
+    registry: &ModelRegistry,
+    current: &str,
+    models_dir: &Path,
+    imported: bool,
+    dry_run: bool,
+) -> Result<Vec<ModelStatus>> {
+    let pruned: Vec<ModelStatus> = model_statuses(registry, current, models_dir)
+        .into_iter()
+        .filter(|status| status.downloaded && !status.current)
+        .filter(|status| status.downloadable || imported)
+        .collect();
+
+    if !dry_run {
+        for status in &pruned {
+            let path = models_dir.join(&status.file);
+            std::fs::remove_file(&path)?;
+            let _ = std::fs::remove_file(path.with_extension("tmp"));
+            log::info!("Deleted model: {:?}", path);
+        }
+    }
+    Ok(pruned)
+}
+
+fn print_pruned(pruned: &[ModelStatus], dry_run: bool, json: bool) -> Result<()> {
+    let freed: u64 = pruned.iter().map(|status| status.disk_usage).sum();
+    if json {
+        let names: Vec<&str> = pruned.iter().map(|status| status.name.as_str()).collect();
+        let result = serde_json::json!({
+            "deleted": names,
+            "freed_bytes": freed,
+            "dry_run": dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if pruned.is_empty() {
+        println!("No unused model to delete");
+        return Ok(());
+    }
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    for status in pruned {
+        println!(
+            "{} {} ({})",
+            verb,
+            status.name,
+            format_bytes(status.disk_usage)
+        );
+    }
+    println!(
+        "{} {}",
+        if dry_run { "Would free" } else { "Freed" },
+        format_bytes(freed)
+    );
+    Ok(())
+}
+
+/// Human-readable size ("148 MB", "1.5 GB")
+fn format_bytes(bytes: u64) -> String {
+    const MB: u64 = 1_000_000;
+    const GB: u64 = 1_000_000_000;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{} MB", bytes / MB)
+    } else if bytes > 0 {
+        format!("{} kB", (bytes as f64 / 1000.0).ceil())
+    } else {
+        "-".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ModelInfo, ModelsConfig};
+
+    /// Models directory holding fake base and tiny models
+    fn models_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("voxai-models-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ggml-base.bin"), vec![0u8; 3000]).unwrap();
+        std::fs::write(dir.join("ggml-tiny.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.join("ggml-tiny.tmp"), vec![0u8; 10]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_model_statuses() {
+        let dir = models_dir("status");
+        let registry = ModelRegistry::builtin();
+        let statuses = model_statuses(&registry, "Base", &dir);
+        assert_eq!(statuses.len(), registry.all().len());
+
+        let base = statuses.iter().find(|s| s.name == "base").unwrap();
+        assert!(base.downloaded && base.current);
+        assert_eq!(base.disk_usage, 3000);
+        assert_eq!(base.ram_mb, 1024);
+        assert_eq!(base.speed_multiplier, Some(7.0));
+
+        let small = statuses.iter().find(|s| s.name == "small").unwrap();
+        assert!(!small.downloaded && !small.current);
+        assert_eq!(small.disk_usage, 0);
+    }
+
+    #[test]
+    fn test_prune_keeps_configured_model() {
+        let dir = models_dir("prune");
+        let registry = ModelRegistry::builtin();
+
+        let pruned = prune(&registry, "base", &dir, false, true).unwrap();
+        let names: Vec<&str> = pruned.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["tiny"]);
+        assert!(dir.join("ggml-tiny.bin").exists());
+
+        prune(&registry, "base", &dir, false, false).unwrap();
+        assert!(!dir.join("ggml-tiny.bin").exists());
+        assert!(!dir.join("ggml-tiny.tmp").exists());
+        assert!(dir.join("ggml-base.bin").exists());
+        assert!(prune(&registry, "base", &dir, false, false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prune_keeps_imported_models() {
+        let dir = models_dir("prune-imported");
+        std::fs::write(dir.join("ggml-mine.bin"), vec![0u8; 100]).unwrap();
+        let registry = ModelRegistry::from_config(&ModelsConfig {
+            custom: vec![ModelInfo::named("mine", "ggml-mine.bin")],
+            ..Default::default()
+        });
+
+        let pruned = prune(&registry, "base", &dir, false, false).unwrap();
+        let names: Vec<&str> = pruned.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["tiny"]);
+        assert!(dir.join("ggml-mine.bin").exists());
+
+        let pruned = prune(&registry, "base", &dir, true, false).unwrap();
+        let names: Vec<&str> = pruned.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["mine"]);
+        assert!(!dir.join("ggml-mine.bin").exists());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(147_951_465), "147 MB");
+        assert_eq!(format_bytes(1_533_763_059), "1.5 GB");
+        assert_eq!(format_bytes(1500), "2 kB");
+        assert_eq!(format_bytes(0), "-");
+    }
+}
@@ -0,0 +1,267 @@
+//! `voxai transcribe`: offline transcription of audio files
+//!
+//! The file is decoded to 16kHz mono and fed through the same
+//! [`TranscriptionPipeline`] and formatter as live dictation, using the
+//! model and formatting settings from the user's configuration.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::audio::decoder::AudioFileDecoder;
+use crate::config;
+use crate::output::export::{self, format_timestamp, CueOptions, ExportFormat};
+use crate::output::formatter::{format_text, FormattingOptions};
+use crate::transcription::engine::{Transcript, TranscriptionEngine, ENGINE_SAMPLE_RATE};
+use crate::transcription::pipeline::{PipelineConfig, TranscriptionPipeline, TranscriptionResult};
+use crate::transcription::whisper::{WhisperContext, WhisperOptions};
+
+/// Arguments of `voxai transcribe`
+#[derive(Debug, Args)]
+pub struct TranscribeArgs {
+    /// Audio file to transcribe
+    pub file: PathBuf,
+
+    /// Write the transcript to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Whisper model to use (tiny, base, small, medium, large-v3...)
+    #[arg(short, long)]
+    pub model: Option<String>,
+
+    /// Spoken language as an ISO 639-1 code, or "auto"
+    #[arg(short, long)]
+    pub language: Option<String>,
+
+    /// Output format (txt, srt, vtt, json); guessed from the output file
+    /// extension by default
+    #[arg(short, long)]
+    pub format: Option<ExportFormat>,
+
+    /// Prefix each line with its start and end time (text format only)
+    #[arg(long)]
+    pub timestamps: bool,
+
+    /// Cut the audio in fixed windows instead of at pauses in speech
+    #[arg(long)]
+    pub no_vad: bool,
+}
+
+/// Run `voxai transcribe`
+pub async fn run(args: TranscribeArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if let Some(model) = &args.model {
+        config.transcription.model = model.clone();
+    }
+    if let Some(language) = &args.language {
+        config.transcription.language = language.clone();
+    }
+
+    let mut pipeline_config = PipelineConfig::from_config(&config)?;
+    pipeline_config.enable_vad = !args.no_vad;
+    // Nobody watches a live preview of a file
+    pipeline_config.partial_interval = None;
+
+    let decoder = AudioFileDecoder::open(&args.file, ENGINE_SAMPLE_RATE)?;
+    let engine = WhisperContext::with_options(
+        &pipeline_config.model,
+        WhisperOptions::from_config(&config.transcription),
+    )?;
+    let formatting = FormattingOptions::from_config(&config);
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Text);
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| {
+            format!("Failed to create output file: {}", path.display())
+        })?)),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let lines = transcribe_file(
+        decoder,
+        pipeline_config,
+        Arc::new(engine),
+        &formatting,
+        format,
+        args.timestamps,
+        &mut output,
+    )
+    .await?;
+    output.flush().context("Failed to write transcript")?;
+
+    log::info!("Transcribed {} ({} results)", args.file.display(), lines);
+    if let Some(path) = &args.output {
+        eprintln!("Transcript written to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Transcribe a decoded file and write it in the given format
+///
+/// Plain text is written one line per result as transcription progresses;
+/// the other formats are rendered once the whole file is transcribed.
+/// Returns the number of non-empty results.
+pub async fn transcribe_file(
+    mut decoder: AudioFileDecoder,
+    config: PipelineConfig,
+    engine: Arc<dyn TranscriptionEngine>,
+    formatting: &FormattingOptions,
+    format: ExportFormat,
+    timestamps: bool,
+    output: &mut dyn Write,
+) -> Result<usize> {
+    let mut pipeline = TranscriptionPipeline::new(config, engine)?;
+    pipeline.start().await?;
+
+    // Decoding is much faster than transcription, so the whole file is
+    // queued up front and results are collected afterwards
+    while let Some(block) = decoder.next_block()? {
+        pipeline.send_audio(block)?;
+    }
+    pipeline.finish();
+
+    let mut lines = 0;
+    let mut transcript = Transcript::default();
+    while let Some(result) = pipeline.receive_text().await {
+        if !result.is_final() {
+            continue;
+        }
+        let text = format_text(&result.text, formatting)?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        lines += 1;
+
+        if format != ExportFormat::Text {
+            for mut segment in result.segments {
+                segment.text = format_text(&segment.text, formatting)?;
+                transcript.segments.push(segment);
+            }
+            if transcript.language.is_none() {
+                transcript.language = result.language;
+            }
+            continue;
+        }
+
+        if timestamps {
+            let (start, end) = speech_span(&result);
+            writeln!(
+                output,
+                "[{} --> {}] {}",
+                format_timestamp(start),
+                format_timestamp(end),
+                text
+            )
+        } else {
+            writeln!(output, "{}", text)
+        }
+        .context("Failed to write transcript")?;
+    }
+
+    if format != ExportFormat::Text {
+        let rendered = export::export(&transcript, format, &CueOptions::default())?;
+        output
+            .write_all(rendered.as_bytes())
+            .context("Failed to write transcript")?;
+    }
+
+    pipeline.stop();
+    Ok(lines)
+}
+
+/// Time span covered by the speech in a result
+fn speech_span(result: &TranscriptionResult) -> (Duration, Duration) {
+    match (result.segments.first(), result.segments.last()) {
+        (Some(first), Some(last)) => (first.start, last.end),
+        _ => (result.start, result.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::{samples_to_duration, MockEngine, Segment};
+
+    fn write_wav(name: &str, seconds: f32) -> PathBuf {
+        let dir = std::env::temp_dir().join("voxai_transcribe_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..(16000.0 * seconds) as usize {
+            writer.write_sample(8000i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    /// Transcribe 13 seconds of audio with a mock engine describing each chunk
+    /// (speech lasts at most 4 seconds per chunk)
+    async fn transcribe_sample(name: &str, format: ExportFormat, timestamps: bool) -> String {
+        let path = write_wav(name, 13.0);
+        let decoder = AudioFileDecoder::open(&path, ENGINE_SAMPLE_RATE).unwrap();
+
+        let engine = MockEngine::new().with_responder(|samples| {
+            let duration = samples_to_duration(samples.len());
+            let text = format!("heard {}s", duration.as_secs());
+            let speech_end = duration.min(Duration::from_secs(4));
+            vec![Segment::new(text, Duration::ZERO, speech_end)]
+        });
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let formatting = FormattingOptions::default();
+
+        let mut output = Vec::new();
+        let results = transcribe_file(
+            decoder,
+            config,
+            Arc::new(engine),
+            &formatting,
+            format,
+            timestamps,
+            &mut output,
+        )
+        .await
+        .unwrap();
+
+        // A full window, then the rest of the file with its overlap
+        assert_eq!(results, 2);
+        std::fs::remove_file(path).ok();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_file() {
+        assert_eq!(
+            transcribe_sample("speech.wav", ExportFormat::Text, true).await,
+            "[00:00:00.000 --> 00:00:04.000] Heard 10s\n\
+             [00:00:08.000 --> 00:00:12.000] Heard 5s\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_file_to_srt() {
+        assert_eq!(
+            transcribe_sample("speech_srt.wav", ExportFormat::Srt, false).await,
+            "1\n00:00:00,000 --> 00:00:04,000\nHeard 10s\n\n\
+             2\n00:00:08,000 --> 00:00:12,000\nHeard 5s\n\n"
+        );
+    }
+}
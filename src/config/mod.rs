@@ -81,14 +81,29 @@ pub fn save_config(config: &Config) -> Result<()> {
 
 /// Validate configuration values
 fn validate_config(config: &Config) -> Result<()> {
-    // Validate model name
-    let valid_models = ["tiny", "base", "small", "medium"];
-    anyhow::ensure!(
-        valid_models.contains(&config.transcription.model.as_str()),
-        "Invalid model '{}'. Valid models: {:?}",
-        config.transcription.model,
-        valid_models
-    );
+    // Validate custom models, then the model name against the registry
+    for model in &config.models.custom {
+        anyhow::ensure!(!model.name.trim().is_empty(), "Empty custom model name");
+        anyhow::ensure!(
+            !model.file.is_empty() && !model.file.contains(['/', '\\']),
+            "Invalid file name '{}' for model '{}'",
+            model.file,
+            model.name
+        );
+        if let Some(sha256) = &model.sha256 {
+            anyhow::ensure!(
+                sha256.len() == 64 && sha256.chars().all(|c| c.is_ascii_hexdigit()),
+                "Invalid SHA256 '{}' for model '{}'",
+                sha256,
+                model.name
+            );
+        }
+    }
+    if !crate::transcription::models::is_auto_model(&config.transcription.model) {
+        crate::transcription::models::ModelRegistry::from_config(&config.models)
+            .resolve(&config.transcription.model)
+            .context("Invalid model")?;
+    }
 
     // Validate sample rate (Whisper requires 16kHz)
     anyhow::ensure!(
@@ -104,12 +119,106 @@ fn validate_config(config: &Config) -> Result<()> {
         config.transcription.vad_aggressiveness
     );
 
+    // Validate decoding strategy
+    let valid_sampling = ["greedy", "beam_search"];
+    anyhow::ensure!(
+        valid_sampling.contains(&config.transcription.sampling.as_str()),
+        "Invalid sampling strategy '{}'. Valid strategies: {:?}",
+        config.transcription.sampling,
+        valid_sampling
+    );
+
+    anyhow::ensure!(
+        (1..=16).contains(&config.transcription.beam_size),
+        "Invalid beam size {}. Must be 1-16",
+        config.transcription.beam_size
+    );
+
+    let partial_interval = config.transcription.partial_interval_ms;
+    anyhow::ensure!(
+        partial_interval == 0 || PARTIAL_INTERVAL_RANGE_MS.contains(&partial_interval),
+        "Invalid live preview interval {} ms. Must be 0 (disabled) or {}-{}",
+        partial_interval,
+        PARTIAL_INTERVAL_RANGE_MS.start(),
+        PARTIAL_INTERVAL_RANGE_MS.end()
+    );
+
+    let latency_budget = config.transcription.latency_budget;
+    anyhow::ensure!(
+        (0.05..=2.0).contains(&latency_budget),
+        "Invalid latency budget {}. Must be 0.05-2.0 (real-time factor)",
+        latency_budget
+    );
+
     // Validate hotkey is not empty
     anyhow::ensure!(
         !config.hotkeys.toggle_recording.is_empty(),
         "Hotkey cannot be empty"
     );
 
+    anyhow::ensure!(
+        HOLD_THRESHOLD_RANGE_MS.contains(&config.hotkeys.hold_threshold_ms),
+        "Invalid hold threshold {} ms. Must be {}-{}",
+        config.hotkeys.hold_threshold_ms,
+        HOLD_THRESHOLD_RANGE_MS.start(),
+        HOLD_THRESHOLD_RANGE_MS.end()
+    );
+
+    // Validate voice commands
+    for (language, commands) in &config.commands.grammar {
+        for command in commands {
+            anyhow::ensure!(
+                !command.phrase.trim().is_empty(),
+                "Empty voice command phrase for language '{}'",
+                language
+            );
+            if let CommandAction::Insert { text } = &command.action {
+                anyhow::ensure!(
+                    !text.is_empty(),
+                    "Voice command '{}' inserts no text",
+                    command.phrase
+                );
+            }
+        }
+    }
+
+    // Validate text rules
+    for rule in &config.rules.replacements {
+        anyhow::ensure!(
+            !rule.pattern.is_empty(),
+            "Empty replacement pattern (replacement '{}')",
+            rule.replacement
+        );
+        if rule.regex {
+            regex::Regex::new(&rule.pattern)
+                .with_context(|| format!("Invalid replacement regex '{}'", rule.pattern))?;
+        }
+    }
+    for snippet in &config.rules.snippets {
+        anyhow::ensure!(
+            !snippet.trigger.trim().is_empty(),
+            "Empty snippet trigger (text '{}')",
+            snippet.text
+        );
+    }
+
+    // Validate model download sources
+    let model_urls = config
+        .models
+        .custom
+        .iter()
+        .filter_map(|model| model.url.as_ref());
+    for url in std::iter::once(&config.models.base_url)
+        .chain(&config.models.mirrors)
+        .chain(model_urls)
+    {
+        anyhow::ensure!(
+            url.starts_with("http://") || url.starts_with("https://"),
+            "Invalid model download URL '{}'. Must start with http:// or https://",
+            url
+        );
+    }
+
     Ok(())
 }
 
@@ -150,6 +259,30 @@ mod tests {
         let mut config = Config::default();
         config.transcription.model = "invalid".to_string();
         assert!(validate_config(&config).is_err());
+
+        config.transcription.model = "large-v3-q5_0".to_string();
+        assert!(validate_config(&config).is_ok());
+        config.transcription.model = "Auto".to_string();
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_custom_models() {
+        let mut config = Config::default();
+        config.transcription.model = "whisper-fr".to_string();
+        assert!(validate_config(&config).is_err());
+
+        config.models.custom.push(ModelInfo {
+            sha256: Some("abc".to_string()),
+            ..ModelInfo::named("whisper-fr", "ggml-whisper-fr.bin")
+        });
+        assert!(validate_config(&config).is_err());
+
+        config.models.custom[0].sha256 = None;
+        assert!(validate_config(&config).is_ok());
+
+        config.models.custom[0].file = "../ggml-whisper-fr.bin".to_string();
+        assert!(validate_config(&config).is_err());
     }
 
     #[test]
@@ -166,6 +299,114 @@ mod tests {
         assert!(validate_config(&config).is_err());
     }
 
+    #[test]
+    fn test_validate_config_partial_interval() {
+        let mut config = Config::default();
+        // Live preview is opt-in
+        assert_eq!(config.transcription.partial_interval_ms, 0);
+        config.transcription.partial_interval_ms = 5000;
+        assert!(validate_config(&config).is_ok());
+        config.transcription.partial_interval_ms = 0;
+        assert!(validate_config(&config).is_ok());
+        config.transcription.partial_interval_ms = 20;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_latency_budget() {
+        let mut config = Config::default();
+        config.transcription.latency_budget = 1.0;
+        assert!(validate_config(&config).is_ok());
+        config.transcription.latency_budget = 0.0;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_invalid_hold_threshold() {
+        let mut config = Config::default();
+        config.hotkeys.hold_threshold_ms = 10;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_invalid_voice_command() {
+        let mut config = Config::default();
+        let empty_phrase = VoiceCommand {
+            phrase: "  ".to_string(),
+            action: CommandAction::Undo,
+        };
+        config
+            .commands
+            .grammar
+            .insert("en".to_string(), vec![empty_phrase]);
+        assert!(validate_config(&config).is_err());
+
+        let empty_text = VoiceCommand {
+            phrase: "nothing".to_string(),
+            action: CommandAction::Insert {
+                text: String::new(),
+            },
+        };
+        config
+            .commands
+            .grammar
+            .insert("en".to_string(), vec![empty_text]);
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_invalid_rules() {
+        let mut config = Config::default();
+        config.rules.replacements.push(ReplacementRule {
+            pattern: "(unclosed".to_string(),
+            replacement: String::new(),
+            regex: true,
+            case_sensitive: false,
+            whole_word: false,
+        });
+        assert!(validate_config(&config).is_err());
+
+        // Only regex patterns are parsed
+        config.rules.replacements[0].regex = false;
+        assert!(validate_config(&config).is_ok());
+
+        config.rules.snippets.push(Snippet {
+            trigger: " ".to_string(),
+            text: "Best regards".to_string(),
+        });
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_invalid_model_urls() {
+        let mut config = Config::default();
+        config
+            .models
+            .mirrors
+            .push("http://models.internal:8080/whisper".to_string());
+        assert!(validate_config(&config).is_ok());
+
+        config
+            .models
+            .mirrors
+            .push("models.internal/whisper".to_string());
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_invalid_sampling() {
+        let mut config = Config::default();
+        config.transcription.sampling = "random".to_string();
+        assert!(validate_config(&config).is_err());
+
+        config.transcription.sampling = "beam_search".to_string();
+        config.transcription.beam_size = 0;
+        assert!(validate_config(&config).is_err());
+
+        config.transcription.beam_size = 5;
+        assert!(validate_config(&config).is_ok());
+    }
+
     #[test]
     fn test_save_and_load_config() {
         // This test creates a real config file in a temp directory
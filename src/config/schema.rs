@@ -3,6 +3,14 @@
 //! Defines all configuration structures with serde support for JSON serialization
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Valid values of `transcription.partial_interval_ms`, besides 0 (disabled)
+pub const PARTIAL_INTERVAL_RANGE_MS: RangeInclusive<u64> = 100..=5000;
+
+/// Valid values of `hotkeys.hold_threshold_ms`
+pub const HOLD_THRESHOLD_RANGE_MS: RangeInclusive<u64> = 50..=2000;
 
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +34,22 @@ pub struct Config {
     /// UI and visual settings
     #[serde(default)]
     pub ui: UiConfig,
+
+    /// Transcription history settings
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Spoken editing commands
+    #[serde(default)]
+    pub commands: CommandsConfig,
+
+    /// Text replacements and snippets
+    #[serde(default)]
+    pub rules: RulesConfig,
+
+    /// Model download sources
+    #[serde(default)]
+    pub models: ModelsConfig,
 }
 
 /// Audio configuration
@@ -47,7 +71,8 @@ pub struct AudioConfig {
 /// Transcription configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionConfig {
-    /// Whisper model size: "tiny", "base", "small", "medium"
+    /// Whisper model name: "tiny", "base", "small", "medium", "large-v3"... or
+    /// a model from `models.custom`
     #[serde(default = "default_model")]
     pub model: String,
 
@@ -62,6 +87,29 @@ pub struct TranscriptionConfig {
     /// Voice Activity Detection aggressiveness (0-3)
     #[serde(default = "default_vad_mode")]
     pub vad_aggressiveness: u8,
+
+    /// Decoding strategy: "greedy" or "beam_search"
+    #[serde(default = "default_sampling")]
+    pub sampling: String,
+
+    /// Number of beams when using beam search
+    #[serde(default = "default_beam_size")]
+    pub beam_size: u32,
+
+    /// Number of inference threads (0 = automatic)
+    #[serde(default)]
+    pub threads: u32,
+
+    /// Live preview: re-transcribe the utterance being spoken every this
+    /// many milliseconds (0 = disabled)
+    #[serde(default)]
+    pub partial_interval_ms: u64,
+
+    /// Longest time the automatically selected model may take to
+    /// transcribe, as a fraction of the audio duration (real-time factor
+    /// measured by `voxai bench`)
+    #[serde(default = "default_latency_budget")]
+    pub latency_budget: f32,
 }
 
 /// Hotkey configuration
@@ -70,6 +118,44 @@ pub struct HotkeyConfig {
     /// Hotkey to toggle recording
     #[serde(default = "default_hotkey")]
     pub toggle_recording: String,
+
+    /// How the hotkey starts and stops recording
+    #[serde(default)]
+    pub activation_mode: ActivationMode,
+
+    /// In hybrid mode, how long (ms) the hotkey must be held to act as
+    /// push-to-talk rather than a toggle
+    #[serde(default = "default_hold_threshold_ms")]
+    pub hold_threshold_ms: u64,
+}
+
+/// How the recording hotkey behaves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivationMode {
+    /// Press once to start recording, again to stop
+    #[default]
+    Toggle,
+    /// Record while the hotkey is held
+    PushToTalk,
+    /// A short tap toggles recording, holding the hotkey is push-to-talk
+    Hybrid,
+}
+
+impl ActivationMode {
+    /// Human-readable name
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Toggle => "Toggle",
+            Self::PushToTalk => "Push-to-talk",
+            Self::Hybrid => "Hybrid",
+        }
+    }
+
+    /// All activation modes
+    pub fn all() -> &'static [ActivationMode] {
+        &[Self::Toggle, Self::PushToTalk, Self::Hybrid]
+    }
 }
 
 /// UI configuration
@@ -86,6 +172,194 @@ pub struct UiConfig {
     /// Automatically capitalize first letter
     #[serde(default = "default_true")]
     pub auto_capitalization: bool,
+
+    /// Remove hesitations ("um", "euh"...) from dictated text
+    #[serde(default = "default_true")]
+    pub remove_fillers: bool,
+
+    /// Write spoken numbers, amounts, times and dates with digits
+    #[serde(default = "default_true")]
+    pub normalize_numbers: bool,
+}
+
+/// Transcription history configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Record dictated text in the local history
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Maximum number of entries kept (0 = unlimited)
+    #[serde(default = "default_history_max_entries")]
+    pub max_entries: usize,
+
+    /// Days after which entries are deleted (0 = never)
+    #[serde(default = "default_history_retention_days")]
+    pub retention_days: u32,
+}
+
+/// Voice command configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandsConfig {
+    /// Recognize spoken commands ("new line", "delete that"...) in dictated
+    /// text
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Commands per language code ("en", "fr"...)
+    #[serde(default = "default_command_grammar")]
+    pub grammar: BTreeMap<String, Vec<VoiceCommand>>,
+}
+
+/// Spoken phrase and the command it triggers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoiceCommand {
+    /// Words to say, matched ignoring case and punctuation
+    pub phrase: String,
+
+    /// What the phrase does
+    #[serde(flatten)]
+    pub action: CommandAction,
+}
+
+impl VoiceCommand {
+    fn new(phrase: &str, action: CommandAction) -> Self {
+        Self {
+            phrase: phrase.to_string(),
+            action,
+        }
+    }
+
+    fn insert(phrase: &str, text: &str) -> Self {
+        Self::new(
+            phrase,
+            CommandAction::Insert {
+                text: text.to_string(),
+            },
+        )
+    }
+}
+
+/// Editing operation triggered by a voice command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum CommandAction {
+    /// Start a new line
+    NewLine,
+    /// Start a new paragraph
+    NewParagraph,
+    /// Erase the text output last
+    DeleteThat,
+    /// Select everything in the focused field
+    SelectAll,
+    /// Undo the last edit in the focused application
+    Undo,
+    /// Insert text (typically punctuation) right after the previous word
+    Insert { text: String },
+}
+
+/// Text rules applied to transcribed text
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulesConfig {
+    /// Replacements, applied in order
+    #[serde(default)]
+    pub replacements: Vec<ReplacementRule>,
+
+    /// Spoken triggers expanded into text
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+}
+
+/// Where models are downloaded from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelsConfig {
+    /// URL the model files are downloaded from (`<base_url>/<file>`)
+    #[serde(default = "default_model_base_url")]
+    pub base_url: String,
+
+    /// Fallback URLs, tried in order when the base URL fails
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+
+    /// Models added to the built-in ones (imported or hand-written
+    /// entries); an entry named like a built-in model replaces it
+    #[serde(default)]
+    pub custom: Vec<ModelInfo>,
+}
+
+/// Description of a Whisper model file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Name used in `transcription.model` ("base", "large-v3-q5_0"...)
+    pub name: String,
+
+    /// File name in the models directory
+    pub file: String,
+
+    /// File size in bytes (0 if unknown)
+    #[serde(default)]
+    pub size: u64,
+
+    /// SHA256 of the file, checked after downloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    /// Languages the model transcribes, empty for multilingual models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+
+    /// Weight quantization ("q5_0", "q8_0"...), none for full precision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<String>,
+
+    /// Download URL, instead of `<base_url>/<file>` and the mirrors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Number of parameters in millions, which ranks the models by
+    /// accuracy (0 if unknown)
+    #[serde(default)]
+    pub parameters_m: u64,
+
+    /// RAM needed to run the model, in MB (0 if unknown)
+    #[serde(default)]
+    pub ram_mb: u64,
+
+    /// Speed in times real time on an average CPU, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_multiplier: Option<f32>,
+}
+
+/// Replacement of a literal text or regular expression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplacementRule {
+    /// Text to find, or a regular expression if `regex` is set
+    pub pattern: String,
+
+    /// Replacement text; `$1`, `$name`... refer to regex groups
+    pub replacement: String,
+
+    /// Treat `pattern` as a regular expression
+    #[serde(default)]
+    pub regex: bool,
+
+    /// Match case exactly
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    /// Only match whole words
+    #[serde(default = "default_true")]
+    pub whole_word: bool,
+}
+
+/// Text inserted when its trigger phrase is spoken
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Spoken phrase, e.g. "insert signature"
+    pub trigger: String,
+
+    /// Text inserted in place of the phrase
+    pub text: String,
 }
 
 // Default value functions
@@ -118,10 +392,71 @@ fn default_hotkey() -> String {
     return "Ctrl+Shift+Space".to_string();
 }
 
+fn default_latency_budget() -> f32 {
+    0.5
+}
+
+fn default_hold_threshold_ms() -> u64 {
+    300
+}
+
 fn default_vad_mode() -> u8 {
     1
 }
 
+fn default_sampling() -> String {
+    "greedy".to_string()
+}
+
+fn default_beam_size() -> u32 {
+    5
+}
+
+fn default_history_max_entries() -> usize {
+    1000
+}
+
+fn default_history_retention_days() -> u32 {
+    90
+}
+
+fn default_command_grammar() -> BTreeMap<String, Vec<VoiceCommand>> {
+    use CommandAction::*;
+
+    let english = vec![
+        VoiceCommand::new("new line", NewLine),
+        VoiceCommand::new("new paragraph", NewParagraph),
+        VoiceCommand::new("delete that", DeleteThat),
+        VoiceCommand::new("select all", SelectAll),
+        VoiceCommand::new("undo", Undo),
+        VoiceCommand::insert("period", "."),
+        VoiceCommand::insert("full stop", "."),
+        VoiceCommand::insert("comma", ","),
+        VoiceCommand::insert("question mark", "?"),
+        VoiceCommand::insert("exclamation mark", "!"),
+        VoiceCommand::insert("colon", ":"),
+    ];
+    let french = vec![
+        VoiceCommand::new("à la ligne", NewLine),
+        VoiceCommand::new("nouvelle ligne", NewLine),
+        VoiceCommand::new("nouveau paragraphe", NewParagraph),
+        VoiceCommand::new("efface ça", DeleteThat),
+        VoiceCommand::new("tout sélectionner", SelectAll),
+        VoiceCommand::new("annuler", Undo),
+        VoiceCommand::insert("point", "."),
+        VoiceCommand::insert("virgule", ","),
+        VoiceCommand::insert("point d'interrogation", "?"),
+        VoiceCommand::insert("point d'exclamation", "!"),
+        VoiceCommand::insert("deux points", ":"),
+    ];
+
+    BTreeMap::from([("en".to_string(), english), ("fr".to_string(), french)])
+}
+
+fn default_model_base_url() -> String {
+    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main".to_string()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -135,6 +470,10 @@ impl Default for Config {
             transcription: TranscriptionConfig::default(),
             hotkeys: HotkeyConfig::default(),
             ui: UiConfig::default(),
+            history: HistoryConfig::default(),
+            commands: CommandsConfig::default(),
+            rules: RulesConfig::default(),
+            models: ModelsConfig::default(),
         }
     }
 }
@@ -156,6 +495,11 @@ impl Default for TranscriptionConfig {
             language: default_language(),
             enable_gpu: default_true(),
             vad_aggressiveness: default_vad_mode(),
+            sampling: default_sampling(),
+            beam_size: default_beam_size(),
+            threads: 0,
+            partial_interval_ms: 0,
+            latency_budget: default_latency_budget(),
         }
     }
 }
@@ -164,6 +508,8 @@ impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             toggle_recording: default_hotkey(),
+            activation_mode: ActivationMode::default(),
+            hold_threshold_ms: default_hold_threshold_ms(),
         }
     }
 }
@@ -174,6 +520,37 @@ impl Default for UiConfig {
             show_overlay: default_true(),
             system_notifications: default_true(),
             auto_capitalization: default_true(),
+            remove_fillers: default_true(),
+            normalize_numbers: default_true(),
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            max_entries: default_history_max_entries(),
+            retention_days: default_history_retention_days(),
+        }
+    }
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            grammar: default_command_grammar(),
+        }
+    }
+}
+
+impl Default for ModelsConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_model_base_url(),
+            mirrors: Vec::new(),
+            custom: Vec::new(),
         }
     }
 }
@@ -188,6 +565,8 @@ mod tests {
         assert_eq!(config.version, "1.0.0");
         assert_eq!(config.audio.sample_rate, 16000);
         assert_eq!(config.transcription.model, "base");
+        assert_eq!(config.transcription.sampling, "greedy");
+        assert_eq!(config.transcription.threads, 0);
     }
 
     #[test]
@@ -204,5 +583,42 @@ mod tests {
         assert_eq!(config.version, "1.0.0");
         // Other fields should use defaults
         assert_eq!(config.audio.sample_rate, 16000);
+        assert!(config.history.enabled);
+        assert_eq!(config.history.max_entries, 1000);
+        assert_eq!(config.hotkeys.activation_mode, ActivationMode::Toggle);
+        assert_eq!(config.hotkeys.hold_threshold_ms, 300);
+    }
+
+    #[test]
+    fn test_activation_mode_names() {
+        let json = r#"{"hotkeys":{"activation_mode":"push_to_talk"}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.hotkeys.activation_mode, ActivationMode::PushToTalk);
+        assert_eq!(
+            serde_json::to_string(&ActivationMode::Hybrid).unwrap(),
+            r#""hybrid""#
+        );
+    }
+
+    #[test]
+    fn test_voice_command_format() {
+        let json = r#"{"commands":{"grammar":{"en":[
+            {"phrase":"scratch that","action":"delete_that"},
+            {"phrase":"semicolon","action":"insert","text":";"}
+        ]}}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(config.commands.enabled);
+        assert_eq!(
+            config.commands.grammar["en"],
+            vec![
+                VoiceCommand::new("scratch that", CommandAction::DeleteThat),
+                VoiceCommand::insert("semicolon", ";"),
+            ]
+        );
+        // Languages missing from a user grammar have no commands
+        assert!(!config.commands.grammar.contains_key("fr"));
+
+        let json = serde_json::to_string(&VoiceCommand::insert("comma", ",")).unwrap();
+        assert_eq!(json, r#"{"phrase":"comma","action":"insert","text":","}"#);
     }
 }
@@ -0,0 +1,7 @@
+//! Transcription history
+//!
+//! Keeps a local record of everything dictated so past text can be searched
+//! and copied again. Entries are appended to a JSON Lines file in the
+//! configuration directory and pruned according to the retention settings.
+
+pub mod store;
@@ -0,0 +1,503 @@
+//! On-disk history store
+//!
+//! The store is a JSON Lines file with one [`HistoryEntry`] per line. New
+//! entries are appended, so a crash can at most lose the line being written;
+//! the file is only rewritten when entries are pruned or deleted. Lines that
+//! cannot be parsed are skipped with a warning rather than failing the load.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{get_config_dir, HistoryConfig};
+use crate::output::clipboard::copy_to_clipboard;
+use crate::output::OutputMethod;
+use crate::transcription::pipeline::TranscriptionResult;
+
+/// History file name inside the configuration directory
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// A dictated utterance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Identifier, unique within the store (assigned on append)
+    pub id: u64,
+    /// When the text was dictated (milliseconds since the Unix epoch)
+    pub recorded_at: u64,
+    /// Final text, as it was output
+    pub text: String,
+    /// Whisper model that produced the text
+    pub model: String,
+    /// ISO 639-1 code of the spoken language, if known
+    pub language: Option<String>,
+    /// Where the text was sent
+    pub output_method: OutputMethod,
+    /// Length of the dictated audio (milliseconds)
+    pub duration_ms: u64,
+}
+
+impl HistoryEntry {
+    /// Create an entry recorded now
+    pub fn new(
+        text: impl Into<String>,
+        model: impl Into<String>,
+        language: Option<String>,
+        output_method: OutputMethod,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            id: 0,
+            recorded_at: unix_millis(SystemTime::now()),
+            text: text.into(),
+            model: model.into(),
+            language,
+            output_method,
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+
+    /// Create an entry for a finalized pipeline result
+    ///
+    /// `text` is the text actually output, after formatting.
+    pub fn from_result(
+        result: &TranscriptionResult,
+        text: impl Into<String>,
+        model: impl Into<String>,
+        output_method: OutputMethod,
+    ) -> Self {
+        Self::new(
+            text,
+            model,
+            result.language.clone(),
+            output_method,
+            result.end.saturating_sub(result.start),
+        )
+    }
+
+    /// When the text was dictated
+    pub fn recorded_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.recorded_at)
+    }
+
+    /// Length of the dictated audio
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    /// Check whether every word of `query` appears in the text
+    /// (case-insensitive)
+    pub fn matches(&self, query: &str) -> bool {
+        let text = self.text.to_lowercase();
+        query
+            .split_whitespace()
+            .all(|term| text.contains(&term.to_lowercase()))
+    }
+}
+
+/// Limits on what the history keeps
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    /// Maximum number of entries (`None` = unlimited)
+    pub max_entries: Option<usize>,
+    /// Maximum age of an entry (`None` = forever)
+    pub max_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Keep everything
+    pub fn unlimited() -> Self {
+        Self {
+            max_entries: None,
+            max_age: None,
+        }
+    }
+
+    /// Build a policy from the user's settings (0 meaning no limit)
+    pub fn from_config(config: &HistoryConfig) -> Self {
+        Self {
+            max_entries: (config.max_entries > 0).then_some(config.max_entries),
+            max_age: (config.retention_days > 0)
+                .then(|| Duration::from_secs(config.retention_days as u64 * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self::from_config(&HistoryConfig::default())
+    }
+}
+
+/// Persistent history of dictated text
+pub struct HistoryStore {
+    path: PathBuf,
+    policy: RetentionPolicy,
+    /// All entries, oldest first
+    entries: Vec<HistoryEntry>,
+    next_id: u64,
+}
+
+impl HistoryStore {
+    /// Open the history in the configuration directory
+    pub fn open_default(config: &HistoryConfig) -> Result<Self> {
+        let path = get_config_dir()?.join(HISTORY_FILE);
+        Self::open(path, RetentionPolicy::from_config(config))
+    }
+
+    /// Open (or create) the history stored at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read, or if pruning
+    /// expired entries fails.
+    pub fn open(path: impl Into<PathBuf>, policy: RetentionPolicy) -> Result<Self> {
+        let path = path.into();
+        let entries = if path.exists() {
+            read_entries(&path)?
+        } else {
+            Vec::new()
+        };
+
+        let mut store = Self {
+            next_id: entries.iter().map(|entry| entry.id + 1).max().unwrap_or(1),
+            path,
+            policy,
+            entries,
+        };
+        if store.prune(SystemTime::now()) {
+            store.rewrite()?;
+        }
+
+        log::debug!(
+            "Loaded {} history entries from {}",
+            store.entries.len(),
+            store.path.display()
+        );
+        Ok(store)
+    }
+
+    /// Record an entry and return its identifier
+    pub fn append(&mut self, mut entry: HistoryEntry) -> Result<u64> {
+        entry.id = self.next_id;
+        self.next_id += 1;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create history directory")?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open history: {}", self.path.display()))?;
+        let line = serde_json::to_string(&entry).context("Failed to serialize history entry")?;
+        writeln!(file, "{}", line).context("Failed to write history entry")?;
+
+        let id = entry.id;
+        self.entries.push(entry);
+        if self.prune(SystemTime::now()) {
+            self.rewrite()?;
+        }
+        Ok(id)
+    }
+
+    /// All entries, oldest first
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// The `count` most recent entries, newest first
+    pub fn recent(&self, count: usize) -> Vec<&HistoryEntry> {
+        self.entries.iter().rev().take(count).collect()
+    }
+
+    /// Look up an entry by identifier
+    pub fn get(&self, id: u64) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Entries containing every word of `query`, newest first
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.matches(query))
+            .collect()
+    }
+
+    /// Copy the text of a past entry to the clipboard
+    pub fn copy_to_clipboard(&self, id: u64) -> Result<()> {
+        let entry = self
+            .get(id)
+            .with_context(|| format!("No history entry {}", id))?;
+        copy_to_clipboard(&entry.text)
+    }
+
+    /// Delete an entry, returning whether it existed
+    pub fn delete(&mut self, id: u64) -> Result<bool> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        if self.entries.len() == before {
+            return Ok(false);
+        }
+        self.rewrite()?;
+        Ok(true)
+    }
+
+    /// Delete every entry
+    pub fn clear(&mut self) -> Result<()> {
+        self.entries.clear();
+        self.rewrite()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the history is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Drop entries outside the retention policy, returning whether any were
+    fn prune(&mut self, now: SystemTime) -> bool {
+        let before = self.entries.len();
+
+        if let Some(max_age) = self.policy.max_age {
+            let cutoff = unix_millis(now).saturating_sub(max_age.as_millis() as u64);
+            self.entries.retain(|entry| entry.recorded_at >= cutoff);
+        }
+        if let Some(max_entries) = self.policy.max_entries {
+            let excess = self.entries.len().saturating_sub(max_entries);
+            self.entries.drain(..excess);
+        }
+
+        self.entries.len() != before
+    }
+
+    /// Replace the file with the current entries
+    fn rewrite(&self) -> Result<()> {
+        let mut contents = String::new();
+        for entry in &self.entries {
+            contents.push_str(
+                &serde_json::to_string(entry).context("Failed to serialize history entry")?,
+            );
+            contents.push('\n');
+        }
+
+        // Write to a temporary file first so the history is never truncated
+        let temp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&temp, contents)
+            .with_context(|| format!("Failed to write history: {}", temp.display()))?;
+        std::fs::rename(&temp, &self.path)
+            .with_context(|| format!("Failed to replace history: {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Read every valid entry of a history file
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open history: {}", path.display()))?;
+
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.context("Failed to read history")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<HistoryEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!("Skipping invalid history line {}: {}", index + 1, e),
+        }
+    }
+
+    Ok(entries)
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("voxai_history_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::remove_file(&path).ok();
+        path
+    }
+
+    fn entry(text: &str) -> HistoryEntry {
+        HistoryEntry::new(
+            text,
+            "base",
+            Some("en".to_string()),
+            OutputMethod::TypeText,
+            Duration::from_millis(2500),
+        )
+    }
+
+    #[test]
+    fn test_append_and_reload() {
+        let path = temp_path("reload.jsonl");
+
+        let mut store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        assert!(store.is_empty());
+        let first = store.append(entry("Hello world")).unwrap();
+        let second = store.append(entry("Second note")).unwrap();
+        assert_ne!(first, second);
+
+        let store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        assert_eq!(store.len(), 2);
+        let loaded = store.get(first).unwrap();
+        assert_eq!(loaded.text, "Hello world");
+        assert_eq!(loaded.language.as_deref(), Some("en"));
+        assert_eq!(loaded.output_method, OutputMethod::TypeText);
+        assert_eq!(loaded.duration(), Duration::from_millis(2500));
+
+        // Identifiers keep increasing across sessions
+        let mut store = store;
+        assert!(store.append(entry("Third")).unwrap() > second);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_search() {
+        let path = temp_path("search.jsonl");
+        let mut store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        store.append(entry("Send the report to Alice")).unwrap();
+        store.append(entry("Call Bob tomorrow")).unwrap();
+        store.append(entry("Alice approved the REPORT")).unwrap();
+
+        let texts = |query: &str| -> Vec<String> {
+            store
+                .search(query)
+                .into_iter()
+                .map(|entry| entry.text.clone())
+                .collect()
+        };
+
+        // Newest first, every term must match, case-insensitive
+        assert_eq!(
+            texts("report alice"),
+            vec!["Alice approved the REPORT", "Send the report to Alice"]
+        );
+        assert_eq!(texts("bob"), vec!["Call Bob tomorrow"]);
+        assert!(texts("carol").is_empty());
+        assert_eq!(texts("").len(), 3);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let path = temp_path("max_entries.jsonl");
+        let policy = RetentionPolicy {
+            max_entries: Some(2),
+            max_age: None,
+        };
+
+        let mut store = HistoryStore::open(&path, policy.clone()).unwrap();
+        for text in ["one", "two", "three"] {
+            store.append(entry(text)).unwrap();
+        }
+        let texts: Vec<&str> = store.entries().iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["two", "three"]);
+
+        // The file was compacted too
+        let store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        assert_eq!(store.len(), 2);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_expired_entries_are_pruned_on_open() {
+        let path = temp_path("expired.jsonl");
+
+        let mut store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        let mut old = entry("old");
+        old.recorded_at -= 10 * 24 * 60 * 60 * 1000;
+        store.append(old).unwrap();
+        store.append(entry("new")).unwrap();
+
+        let policy = RetentionPolicy {
+            max_entries: None,
+            max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        };
+        let store = HistoryStore::open(&path, policy).unwrap();
+        let texts: Vec<&str> = store.entries().iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["new"]);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_invalid_lines_are_skipped() {
+        let path = temp_path("invalid.jsonl");
+        let valid = serde_json::to_string(&HistoryEntry {
+            id: 4,
+            ..entry("kept")
+        })
+        .unwrap();
+        std::fs::write(&path, format!("{}\nnot json\n\n", valid)).unwrap();
+
+        let mut store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.append(entry("next")).unwrap(), 5);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_delete_and_clear() {
+        let path = temp_path("delete.jsonl");
+        let mut store = HistoryStore::open(&path, RetentionPolicy::unlimited()).unwrap();
+        let id = store.append(entry("one")).unwrap();
+        store.append(entry("two")).unwrap();
+
+        assert!(store.delete(id).unwrap());
+        assert!(!store.delete(id).unwrap());
+        assert!(store.copy_to_clipboard(id).is_err());
+        assert_eq!(
+            HistoryStore::open(&path, RetentionPolicy::unlimited())
+                .unwrap()
+                .len(),
+            1
+        );
+
+        store.clear().unwrap();
+        assert!(HistoryStore::open(&path, RetentionPolicy::unlimited())
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_retention_from_config() {
+        let config = HistoryConfig {
+            enabled: true,
+            max_entries: 0,
+            retention_days: 1,
+        };
+        let policy = RetentionPolicy::from_config(&config);
+        assert_eq!(policy.max_entries, None);
+        assert_eq!(policy.max_age, Some(Duration::from_secs(86400)));
+    }
+}
@@ -53,7 +53,7 @@ impl HotkeyManager {
             log::warn!(
                 "Wayland detected: Global hotkeys have limited support. \
                  Consider using your desktop environment's keyboard settings \
-                 to manually configure the hotkey to call VoxAI."
+                 to bind the hotkey to `voxai ctl toggle`."
             );
         }
 
@@ -70,7 +70,7 @@ impl HotkeyManager {
                 if display_server == DisplayServer::Wayland {
                     log::error!(
                         "Failed to register hotkey on Wayland: {}. \
-                         Please configure hotkey manually in your desktop environment settings.",
+                         Bind `voxai ctl toggle` to a shortcut in your desktop environment settings.",
                         e
                     );
                     return Err(anyhow!(
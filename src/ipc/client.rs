@@ -0,0 +1,43 @@
+//! Control socket client
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use super::protocol::{ControlCommand, ControlResponse};
+
+/// How long to wait for the app to answer
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Send a command to the app listening on `path` and wait for its answer
+///
+/// # Errors
+///
+/// Returns an error if the app is not running or does not answer in time.
+/// A command the app rejected is returned as [`ControlResponse::Error`].
+pub async fn send_command(path: &Path, command: &ControlCommand) -> Result<ControlResponse> {
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "VoxAI does not seem to be running (no control socket at {})",
+            path.display()
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut request = serde_json::to_string(command).context("Failed to serialize command")?;
+    request.push('\n');
+    writer
+        .write_all(request.as_bytes())
+        .await
+        .context("Failed to send command")?;
+
+    let line = tokio::time::timeout(RESPONSE_TIMEOUT, BufReader::new(reader).lines().next_line())
+        .await
+        .context("Timed out waiting for VoxAI to answer")?
+        .context("Failed to read response")?
+        .context("VoxAI closed the connection without answering")?;
+
+    serde_json::from_str(&line).context("Invalid response from VoxAI")
+}
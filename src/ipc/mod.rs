@@ -0,0 +1,55 @@
+//! Local control interface
+//!
+//! The running app listens on a Unix domain socket for control commands, so
+//! recording can be driven from scripts and compositor shortcuts where global
+//! hotkeys are unavailable (Wayland). `voxai ctl` is the matching client.
+//!
+//! Messages are single lines of JSON, see [`protocol`].
+
+use anyhow::Result;
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+
+pub mod protocol;
+
+#[cfg(unix)]
+pub mod client;
+#[cfg(unix)]
+pub mod server;
+
+use protocol::{ControlCommand, ControlResponse};
+
+/// A command waiting for the app's answer
+#[derive(Debug)]
+pub struct ControlRequest {
+    /// Command received from a client
+    pub command: ControlCommand,
+    reply: oneshot::Sender<ControlResponse>,
+}
+
+impl ControlRequest {
+    /// Wrap a command with the channel its answer goes to
+    pub fn new(command: ControlCommand, reply: oneshot::Sender<ControlResponse>) -> Self {
+        Self { command, reply }
+    }
+
+    /// Send the answer back to the client
+    pub fn respond(self, response: ControlResponse) {
+        // The client may have disconnected meanwhile
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Socket file name
+const SOCKET_NAME: &str = "voxai.sock";
+
+/// Path of the control socket
+///
+/// Uses `$XDG_RUNTIME_DIR` when available (private to the user and cleared
+/// on logout), otherwise the configuration directory.
+pub fn socket_path() -> Result<PathBuf> {
+    match dirs::runtime_dir() {
+        Some(dir) => Ok(dir.join(SOCKET_NAME)),
+        None => Ok(crate::config::get_config_dir()?.join(SOCKET_NAME)),
+    }
+}
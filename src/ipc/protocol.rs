@@ -0,0 +1,100 @@
+//! Control protocol
+//!
+//! A client sends one [`ControlCommand`] per line and receives one
+//! [`ControlResponse`] line for each, e.g.:
+//!
+//! ```text
+//! > {"command":"set_model","model":"small"}
+//! < {"result":"ok","status":{"recording":false,"state":"idle","model":"small"}}
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::state::AppState;
+
+/// Command sent to the running app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    /// Start recording
+    Start,
+    /// Stop recording and transcribe
+    Stop,
+    /// Start or stop recording
+    Toggle,
+    /// Report the current state
+    Status,
+    /// Switch the Whisper model
+    SetModel { model: String },
+}
+
+/// State of the running app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Whether audio is being recorded
+    pub recording: bool,
+    /// Detailed application state
+    pub state: AppState,
+    /// Whisper model in use
+    pub model: String,
+}
+
+/// Reply to a [`ControlCommand`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ControlResponse {
+    /// The command succeeded; carries the state after it was applied
+    Ok { status: DaemonStatus },
+    /// The command failed
+    Error { message: String },
+}
+
+impl ControlResponse {
+    /// Create an error response
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::Error {
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_wire_format() {
+        assert_eq!(
+            serde_json::to_string(&ControlCommand::Toggle).unwrap(),
+            r#"{"command":"toggle"}"#
+        );
+        let command: ControlCommand =
+            serde_json::from_str(r#"{"command":"set_model","model":"small"}"#).unwrap();
+        assert_eq!(
+            command,
+            ControlCommand::SetModel {
+                model: "small".to_string()
+            }
+        );
+        assert!(serde_json::from_str::<ControlCommand>(r#"{"command":"reboot"}"#).is_err());
+    }
+
+    #[test]
+    fn test_response_wire_format() {
+        let response = ControlResponse::Ok {
+            status: DaemonStatus {
+                recording: true,
+                state: AppState::Recording,
+                model: "base".to_string(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"result":"ok","status":{"recording":true,"state":"recording","model":"base"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlResponse::error("nope")).unwrap(),
+            r#"{"result":"error","message":"nope"}"#
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Control socket server
+//!
+//! Accepts connections on the control socket and forwards each command to
+//! the app as a [`ControlRequest`]. The app answers through the request, so
+//! all state stays owned by its event loop.
+
+use anyhow::{Context, Result};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::protocol::{ControlCommand, ControlResponse};
+use super::ControlRequest;
+
+/// Listener on the control socket
+///
+/// The socket file is removed when the server is dropped.
+pub struct ControlServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Listen on `path`, forwarding commands to `requests`
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another instance is already listening on `path`,
+    /// or if the socket cannot be created.
+    pub fn start(path: impl Into<PathBuf>, requests: mpsc::Sender<ControlRequest>) -> Result<Self> {
+        let path = path.into();
+        remove_stale_socket(&path)?;
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind control socket: {}", path.display()))?;
+        // Only the current user may control the app
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .context("Failed to restrict control socket permissions")?;
+
+        log::info!("Control socket listening on {}", path.display());
+
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, requests.clone()));
+                    }
+                    Err(e) => {
+                        log::error!("Control socket accept failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { path, task })
+    }
+
+    /// Path of the socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to remove control socket: {}", e);
+        }
+    }
+}
+
+/// Remove a socket file left behind by a crashed instance
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        anyhow::bail!(
+            "VoxAI is already running (control socket {} is in use)",
+            path.display()
+        );
+    }
+
+    log::debug!("Removing stale control socket {}", path.display());
+    std::fs::remove_file(path)
+        .with_context(|| format!("Failed to remove stale socket: {}", path.display()))
+}
+
+/// Answer every command sent on a connection
+async fn handle_connection(stream: UnixStream, requests: mpsc::Sender<ControlRequest>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                log::warn!("Control connection error: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlCommand>(&line) {
+            Ok(command) => dispatch(command, &requests).await,
+            Err(e) => ControlResponse::error(format!("Invalid command: {}", e)),
+        };
+
+        let Ok(mut json) = serde_json::to_string(&response) else {
+            break;
+        };
+        json.push('\n');
+        if writer.write_all(json.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Hand a command to the app and wait for its answer
+async fn dispatch(
+    command: ControlCommand,
+    requests: &mpsc::Sender<ControlRequest>,
+) -> ControlResponse {
+    log::debug!("Control command: {:?}", command);
+
+    let (reply, response) = oneshot::channel();
+    if requests
+        .send(ControlRequest::new(command, reply))
+        .await
+        .is_err()
+    {
+        return ControlResponse::error("VoxAI is shutting down");
+    }
+
+    response
+        .await
+        .unwrap_or_else(|_| ControlResponse::error("Command was not answered"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::client::send_command;
+    use super::super::protocol::DaemonStatus;
+    use super::*;
+    use crate::app::state::AppState;
+
+    fn temp_socket(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("voxai_ipc_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    /// Answer requests like a minimal app that only tracks recording
+    fn spawn_fake_app(mut requests: mpsc::Receiver<ControlRequest>) {
+        tokio::spawn(async move {
+            let mut recording = false;
+            while let Some(request) = requests.recv().await {
+                let response = match &request.command {
+                    ControlCommand::SetModel { model } => {
+                        ControlResponse::error(format!("Unknown model size: {}", model))
+                    }
+                    command => {
+                        match command {
+                            ControlCommand::Start => recording = true,
+                            ControlCommand::Stop => recording = false,
+                            ControlCommand::Toggle => recording = !recording,
+                            _ => {}
+                        }
+                        ControlResponse::Ok {
+                            status: DaemonStatus {
+                                recording,
+                                state: if recording {
+                                    AppState::Recording
+                                } else {
+                                    AppState::Idle
+                                },
+                                model: "base".to_string(),
+                            },
+                        }
+                    }
+                };
+                request.respond(response);
+            }
+        });
+    }
+
+    fn recording(response: ControlResponse) -> bool {
+        match response {
+            ControlResponse::Ok { status } => status.recording,
+            ControlResponse::Error { message } => panic!("unexpected error: {}", message),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_round_trip() {
+        let path = temp_socket("round_trip.sock");
+        let (tx, rx) = mpsc::channel(8);
+        let server = ControlServer::start(&path, tx).unwrap();
+        spawn_fake_app(rx);
+
+        assert!(!recording(
+            send_command(&path, &ControlCommand::Status).await.unwrap()
+        ));
+        assert!(recording(
+            send_command(&path, &ControlCommand::Toggle).await.unwrap()
+        ));
+        assert!(!recording(
+            send_command(&path, &ControlCommand::Toggle).await.unwrap()
+        ));
+
+        let response = send_command(
+            &path,
+            &ControlCommand::SetModel {
+                model: "huge".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(response, ControlResponse::Error { .. }));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_command_gets_error() {
+        let path = temp_socket("invalid.sock");
+        let (tx, rx) = mpsc::channel(8);
+        let _server = ControlServer::start(&path, tx).unwrap();
+        spawn_fake_app(rx);
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"{\"command\":\"reboot\"}\n")
+            .await
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let reply: ControlResponse =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(reply, ControlResponse::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_second_instance_is_refused() {
+        let path = temp_socket("twice.sock");
+        let (tx, _rx) = mpsc::channel(8);
+        let _server = ControlServer::start(&path, tx.clone()).unwrap();
+        assert!(ControlServer::start(&path, tx).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stale_socket_is_replaced() {
+        let path = temp_socket("stale.sock");
+        // A socket file nobody listens on, as left by a crash
+        drop(std::os::unix::net::UnixListener::bind(&path).ok());
+        assert!(path.exists());
+
+        let (tx, rx) = mpsc::channel(8);
+        let _server = ControlServer::start(&path, tx).unwrap();
+        spawn_fake_app(rx);
+        assert!(send_command(&path, &ControlCommand::Status).await.is_ok());
+    }
+}
@@ -3,10 +3,13 @@
 //! This is the library crate for VoxAI, exposing all public APIs for testing and potential reuse.
 
 // Public modules
+pub mod app;
 pub mod audio;
 pub mod config;
 pub mod gpu;
+pub mod history;
 pub mod hotkeys;
+pub mod ipc;
 pub mod output;
 pub mod transcription;
 pub mod ui;
@@ -4,12 +4,24 @@
 //! Supports Windows, macOS, and Linux with hardware acceleration.
 
 use anyhow::Result;
+use clap::Parser;
 use log::info;
+use tokio::sync::mpsc;
 
+use app::adapters::{InjectorOutput, MicrophoneSource, TrayStatus};
+use app::components::{whisper_engine_factory, LogStatusDisplay, StatusDisplay};
+use app::controller::AppController;
+use app::state::AppEvent;
+use output::OutputMethod;
+
+mod app;
 mod audio;
+mod cli;
 mod config;
 mod gpu;
+mod history;
 mod hotkeys;
+mod ipc;
 mod output;
 mod transcription;
 mod ui;
@@ -19,6 +31,12 @@ async fn main() -> Result<()> {
     // Initialize logger
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    // Subcommands run a single task instead of the desktop app
+    let cli = cli::Cli::parse();
+    if let Some(command) = cli.command {
+        return cli::run(command).await;
+    }
+
     info!("VoxAI starting...");
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
 
@@ -32,12 +50,88 @@ async fn main() -> Result<()> {
     info!("Model: {}", config.transcription.model);
     info!("Sample rate: {} Hz", config.audio.sample_rate);
 
-    // TODO: Initialize components
-    // - Audio capture
-    // - Whisper transcription engine
-    // - System tray UI
-    // - Global hotkeys
-    // - Text output handler
+    // Every trigger (hotkey, tray menu, `voxai ctl`, Ctrl+C) becomes an event
+    // for the app controller
+    let (events, event_rx) = mpsc::unbounded_channel::<AppEvent>();
+
+    // Control socket for `voxai ctl`, the only trigger available on Wayland
+    let (control_tx, mut control_rx) = mpsc::channel::<ipc::ControlRequest>(16);
+    #[cfg(unix)]
+    let _control_server = match ipc::socket_path()
+        .and_then(|path| ipc::server::ControlServer::start(path, control_tx))
+    {
+        Ok(server) => Some(server),
+        Err(e) => {
+            log::warn!("Control socket unavailable: {:#}", e);
+            None
+        }
+    };
+    #[cfg(not(unix))]
+    drop(control_tx);
+
+    let control_events = events.clone();
+    tokio::spawn(async move {
+        while let Some(request) = control_rx.recv().await {
+            if control_events.send(AppEvent::Control(request)).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Global hotkey
+    let hotkey_events = events.clone();
+    let _hotkeys = match hotkeys::HotkeyManager::new(
+        &config.hotkeys.toggle_recording,
+        Box::new(move |event| {
+            let _ = hotkey_events.send(AppEvent::Hotkey(event));
+        }),
+    ) {
+        Ok(manager) => Some(manager),
+        Err(e) => {
+            log::warn!("Global hotkey unavailable: {:#}", e);
+            None
+        }
+    };
+
+    // Ctrl+C
+    let quit_events = events.clone();
+    tokio::spawn(async move {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => {
+                info!("Received Ctrl+C signal, shutting down...");
+                println!("\nShutting down VoxAI...");
+            }
+            Err(err) => {
+                eprintln!("Error waiting for Ctrl+C: {}", err);
+            }
+        }
+        let _ = quit_events.send(AppEvent::Quit);
+    });
+    drop(events);
+
+    // Components
+    let status: Box<dyn StatusDisplay> = match ui::Tray::new() {
+        Ok(tray) => Box::new(TrayStatus::new(tray, config.ui.system_notifications)),
+        Err(e) => {
+            log::warn!("System tray unavailable: {:#}", e);
+            Box::new(LogStatusDisplay)
+        }
+    };
+    let output = InjectorOutput::new(OutputMethod::default())?;
+
+    let mut controller = AppController::new(
+        config.clone(),
+        Box::<MicrophoneSource>::default(),
+        Box::new(output),
+        status,
+        whisper_engine_factory(),
+    );
+    if config.history.enabled {
+        match history::store::HistoryStore::open_default(&config.history) {
+            Ok(history) => controller = controller.with_history(history),
+            Err(e) => log::warn!("Transcription history unavailable: {:#}", e),
+        }
+    }
 
     info!("VoxAI initialized successfully");
     println!("\n===========================================");
@@ -45,16 +139,7 @@ async fn main() -> Result<()> {
     println!("  Press Ctrl+C to quit");
     println!("===========================================\n");
 
-    // Wait for Ctrl+C signal
-    match tokio::signal::ctrl_c().await {
-        Ok(()) => {
-            info!("Received Ctrl+C signal, shutting down...");
-            println!("\nShutting down VoxAI...");
-        }
-        Err(err) => {
-            eprintln!("Error waiting for Ctrl+C: {}", err);
-        }
-    }
+    controller.run(event_rx).await?;
 
     info!("VoxAI shutdown complete");
     Ok(())
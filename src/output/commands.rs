@@ -0,0 +1,318 @@
+//! Voice commands
+//!
+//! Recognizes spoken editing commands ("new line", "delete that",
+//! "comma"...) in transcribed text. The text is split into segments: plain
+//! text to type, and commands the output performs with key presses. Phrases
+//! come from the grammar configured for the spoken language.
+
+use crate::config::{CommandAction, CommandsConfig, Language, VoiceCommand};
+
+/// Part of a transcription after command recognition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Dictated text, output as is
+    Text(String),
+    /// Recognized command
+    Command(CommandAction),
+}
+
+/// Key press an output performs for a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKey {
+    /// Enter / Return
+    Enter,
+    /// Erase the character before the cursor
+    Backspace,
+    /// Ctrl+A (Cmd+A on macOS)
+    SelectAll,
+    /// Ctrl+Z (Cmd+Z on macOS)
+    Undo,
+}
+
+/// Phrases recognized for one language
+#[derive(Debug, Clone, Default)]
+pub struct CommandGrammar {
+    /// Normalized phrase words and their action, longest phrases first
+    phrases: Vec<(Vec<String>, CommandAction)>,
+}
+
+impl CommandGrammar {
+    /// Build a grammar from a list of commands
+    pub fn new(commands: &[VoiceCommand]) -> Self {
+        let mut phrases: Vec<(Vec<String>, CommandAction)> = commands
+            .iter()
+            .map(|command| {
+                let words = command
+                    .phrase
+                    .split_whitespace()
+                    .map(normalize)
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                (words, command.action.clone())
+            })
+            .filter(|(words, _): &(Vec<String>, _)| !words.is_empty())
+            .collect();
+
+        // "point d'interrogation" must win over "point"
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { phrases }
+    }
+
+    /// Grammar configured for `language`
+    ///
+    /// Empty when commands are disabled or the language has none.
+    pub fn for_language(config: &CommandsConfig, language: &Language) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        config
+            .grammar
+            .get(&language.to_code())
+            .map(|commands| Self::new(commands))
+            .unwrap_or_default()
+    }
+
+    /// Split `text` into dictated text and commands
+    ///
+    /// Commands are only recognized on word boundaries. Editing commands
+    /// ("select all", "undo", "new line"...) must also stand alone, as the
+    /// whole utterance or a sentence of their own, so that "select all the
+    /// files" is dictated rather than pressing Ctrl+A. Punctuation commands
+    /// are recognized anywhere. Punctuation the engine put around a command
+    /// ("New line.") is dropped with it.
+    pub fn parse(&self, text: &str) -> Vec<Segment> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let normalized: Vec<String> = words.iter().map(|word| normalize(word)).collect();
+
+        let mut segments = Vec::new();
+        let mut pending: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            let after_command = match segments.last() {
+                Some(Segment::Command(previous)) if pending.is_empty() => Some(previous),
+                _ => None,
+            };
+            let command = self.match_at(&normalized[i..]).filter(|(phrase, action)| {
+                is_insert(action)
+                    || self.stands_alone(&words, &normalized, i..i + phrase.len(), after_command)
+            });
+            let Some((phrase, action)) = command else {
+                pending.push(words[i]);
+                i += 1;
+                continue;
+            };
+
+            let mut text = pending.join(" ");
+            pending.clear();
+            if is_insert(action) {
+                // "Hello, comma" should not become "Hello,,"
+                let trimmed = text.trim_end_matches(is_trailing_punctuation).len();
+                text.truncate(trimmed);
+            }
+            if !text.is_empty() {
+                segments.push(Segment::Text(text));
+            }
+            segments.push(Segment::Command(action.clone()));
+            i += phrase.len();
+        }
+        if !pending.is_empty() {
+            segments.push(Segment::Text(pending.join(" ")));
+        }
+        segments
+    }
+
+    /// Whether the command phrase at `range` of `words` is a sentence of its
+    /// own: it starts the text, a sentence or follows a command ending one
+    /// (`after_command`), and it ends the same way
+    fn stands_alone(
+        &self,
+        words: &[&str],
+        normalized: &[String],
+        range: std::ops::Range<usize>,
+        after_command: Option<&CommandAction>,
+    ) -> bool {
+        let starts = range.start == 0
+            || words[range.start - 1].ends_with(is_sentence_end)
+            || after_command.is_some_and(ends_sentence);
+        let ends = range.end == words.len()
+            || words[range.end - 1].ends_with(is_sentence_end)
+            || self
+                .match_at(&normalized[range.end..])
+                .is_some_and(|(_, next)| ends_sentence(next));
+        starts && ends
+    }
+
+    /// Longest phrase starting at the first of `words`
+    fn match_at(&self, words: &[String]) -> Option<&(Vec<String>, CommandAction)> {
+        self.phrases
+            .iter()
+            .find(|(phrase, _)| words.len() >= phrase.len() && words[..phrase.len()] == phrase[..])
+    }
+}
+
+/// Lowercase a word and strip the punctuation around it
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .replace('’', "'")
+        .to_lowercase()
+}
+
+fn is_insert(action: &CommandAction) -> bool {
+    matches!(action, CommandAction::Insert { .. })
+}
+
+/// Whether a sentence may start after `action`: any editing command, or
+/// sentence-ending punctuation
+fn ends_sentence(action: &CommandAction) -> bool {
+    match action {
+        CommandAction::Insert { text } => text.ends_with(is_sentence_end),
+        _ => true,
+    }
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+fn is_trailing_punctuation(c: char) -> bool {
+    matches!(c, '.' | ',' | ';' | ':' | '!' | '?')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> CommandGrammar {
+        CommandGrammar::for_language(&CommandsConfig::default(), &Language::English)
+    }
+
+    fn text(text: &str) -> Segment {
+        Segment::Text(text.to_string())
+    }
+
+    fn insert(text: &str) -> Segment {
+        Segment::Command(CommandAction::Insert {
+            text: text.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_plain_text_is_kept() {
+        assert_eq!(
+            english().parse("  Hello   world, how are you?"),
+            vec![text("Hello world, how are you?")]
+        );
+    }
+
+    #[test]
+    fn test_commands_split_text() {
+        assert_eq!(
+            english().parse("Dear Anna. New line. Thanks for the update. Delete that."),
+            vec![
+                text("Dear Anna."),
+                Segment::Command(CommandAction::NewLine),
+                text("Thanks for the update."),
+                Segment::Command(CommandAction::DeleteThat),
+            ]
+        );
+        assert_eq!(
+            english().parse("select all"),
+            vec![Segment::Command(CommandAction::SelectAll)]
+        );
+    }
+
+    #[test]
+    fn test_edit_commands_must_stand_alone() {
+        let english = english();
+        for sentence in [
+            "select all the files",
+            "I want to undo my changes",
+            "Please delete that file.",
+            "Draw a new line here",
+            "Then select all. Undo the rest",
+            "Hello comma select all",
+        ] {
+            assert!(
+                english
+                    .parse(sentence)
+                    .iter()
+                    .all(|segment| !matches!(segment, Segment::Command(a) if !is_insert(a))),
+                "command recognized in {:?}",
+                sentence
+            );
+        }
+
+        let french = CommandGrammar::for_language(&CommandsConfig::default(), &Language::French);
+        assert_eq!(
+            french.parse("Il faut annuler la réunion"),
+            vec![text("Il faut annuler la réunion")]
+        );
+    }
+
+    #[test]
+    fn test_edit_commands_between_sentences() {
+        assert_eq!(
+            english().parse("That is wrong. Undo. Select all"),
+            vec![
+                text("That is wrong."),
+                Segment::Command(CommandAction::Undo),
+                Segment::Command(CommandAction::SelectAll),
+            ]
+        );
+        assert_eq!(
+            english().parse("new line new line"),
+            vec![
+                Segment::Command(CommandAction::NewLine),
+                Segment::Command(CommandAction::NewLine),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_punctuation_commands() {
+        assert_eq!(
+            english().parse("Hello, comma how are you question mark"),
+            vec![text("Hello"), insert(","), text("how are you"), insert("?")]
+        );
+    }
+
+    #[test]
+    fn test_longest_phrase_wins() {
+        let french = CommandGrammar::for_language(&CommandsConfig::default(), &Language::French);
+        assert_eq!(
+            french.parse("Vraiment point d’interrogation"),
+            vec![text("Vraiment"), insert("?")]
+        );
+        assert_eq!(
+            french.parse("C'est fini point"),
+            vec![text("C'est fini"), insert(".")]
+        );
+    }
+
+    #[test]
+    fn test_grammar_follows_language() {
+        let config = CommandsConfig::default();
+        let german = CommandGrammar::for_language(&config, &Language::German);
+        assert_eq!(german.parse("new line"), vec![text("new line")]);
+
+        let disabled = CommandsConfig {
+            enabled: false,
+            ..config
+        };
+        let english = CommandGrammar::for_language(&disabled, &Language::English);
+        assert_eq!(english.parse("new line"), vec![text("new line")]);
+    }
+
+    #[test]
+    fn test_words_must_match_whole() {
+        let grammar = CommandGrammar::new(&[VoiceCommand {
+            phrase: "undo".to_string(),
+            action: CommandAction::Undo,
+        }]);
+        assert_eq!(grammar.parse("undone"), vec![text("undone")]);
+        assert_eq!(
+            grammar.parse("UNDO!"),
+            vec![Segment::Command(CommandAction::Undo)]
+        );
+    }
+}
@@ -0,0 +1,502 @@
+//! Transcript export
+//!
+//! Renders timestamped transcripts as subtitles (SRT, WebVTT), plain text or
+//! structured JSON. Subtitle cues are cut from the transcript segments so
+//! that each one fits on screen: a cue holds at most `max_lines` lines of
+//! `max_line_chars` characters and lasts at most `max_duration`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::transcription::engine::{Segment, Transcript};
+
+/// Silence between segments that starts a new paragraph in plain text
+const PARAGRAPH_GAP: Duration = Duration::from_secs(2);
+
+/// Transcript export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Plain text, one paragraph per pause
+    Text,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+    /// Segments with timings and confidence
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+            Self::Json => "json",
+        }
+    }
+
+    /// Guess the format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    /// Get all formats
+    pub fn all() -> &'static [ExportFormat] {
+        &[Self::Text, Self::Srt, Self::Vtt, Self::Json]
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "txt" | "text" => Ok(Self::Text),
+            "srt" => Ok(Self::Srt),
+            "vtt" | "webvtt" => Ok(Self::Vtt),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// Limits applied when cutting segments into subtitle cues
+#[derive(Debug, Clone)]
+pub struct CueOptions {
+    /// Maximum characters per subtitle line
+    pub max_line_chars: usize,
+    /// Maximum lines per cue
+    pub max_lines: usize,
+    /// Maximum time a cue stays on screen
+    pub max_duration: Duration,
+}
+
+impl Default for CueOptions {
+    fn default() -> Self {
+        Self {
+            // Common broadcast guidelines
+            max_line_chars: 42,
+            max_lines: 2,
+            max_duration: Duration::from_secs(6),
+        }
+    }
+}
+
+/// A subtitle cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// Offset where the cue appears
+    pub start: Duration,
+    /// Offset where the cue disappears
+    pub end: Duration,
+    /// Text lines, each within `max_line_chars` unless a single word is longer
+    pub lines: Vec<String>,
+}
+
+/// Cut segments into subtitle cues
+///
+/// Segments only carry start and end times, so the time of each word is
+/// interpolated from its character position within the segment.
+pub fn build_cues(segments: &[Segment], options: &CueOptions) -> Vec<Cue> {
+    let mut cues = Vec::new();
+
+    for segment in segments {
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        // Character offset where each word ends, counting single spaces
+        let total_chars = words.iter().map(|w| w.chars().count()).sum::<usize>() + words.len() - 1;
+        let span = segment.end.saturating_sub(segment.start);
+        let time_at = |chars: usize| {
+            segment.start + span.mul_f64(chars.min(total_chars) as f64 / total_chars as f64)
+        };
+
+        let mut current: Vec<&str> = Vec::new();
+        let mut cue_start_chars = 0;
+        let mut chars = 0;
+
+        for word in words {
+            let word_end = chars + word.chars().count();
+
+            if !current.is_empty() {
+                let mut candidate = current.clone();
+                candidate.push(word);
+                let too_long =
+                    wrap_lines(&candidate, options.max_line_chars).len() > options.max_lines.max(1);
+                let too_slow = time_at(word_end) - time_at(cue_start_chars) > options.max_duration;
+
+                if too_long || too_slow {
+                    cues.push(Cue {
+                        start: time_at(cue_start_chars),
+                        end: time_at(chars.saturating_sub(1)),
+                        lines: wrap_lines(&current, options.max_line_chars),
+                    });
+                    current.clear();
+                    cue_start_chars = chars;
+                }
+            }
+
+            current.push(word);
+            chars = word_end + 1;
+        }
+
+        cues.push(Cue {
+            start: time_at(cue_start_chars),
+            end: segment.end,
+            lines: wrap_lines(&current, options.max_line_chars),
+        });
+    }
+
+    cues
+}
+
+/// Greedily wrap words into lines of at most `max_chars` characters
+fn wrap_lines(words: &[&str], max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in words {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Render a transcript in the given format
+pub fn export(
+    transcript: &Transcript,
+    format: ExportFormat,
+    options: &CueOptions,
+) -> Result<String> {
+    match format {
+        ExportFormat::Text => Ok(to_text(&transcript.segments)),
+        ExportFormat::Srt => Ok(to_srt(&build_cues(&transcript.segments, options))),
+        ExportFormat::Vtt => Ok(to_vtt(&build_cues(&transcript.segments, options))),
+        ExportFormat::Json => to_json(transcript),
+    }
+}
+
+/// Render cues as SubRip (`.srt`)
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut output = String::new();
+    for (index, cue) in cues.iter().enumerate() {
+        output.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            timestamp(cue.start, ','),
+            timestamp(cue.end, ','),
+            cue.lines.join("\n")
+        ));
+    }
+    output
+}
+
+/// Render cues as WebVTT (`.vtt`)
+pub fn to_vtt(cues: &[Cue]) -> String {
+    let mut output = String::from("WEBVTT\n\n");
+    for cue in cues {
+        output.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start),
+            format_timestamp(cue.end),
+            cue.lines.join("\n")
+        ));
+    }
+    output
+}
+
+/// Render segments as plain text, starting a paragraph after each pause
+pub fn to_text(segments: &[Segment]) -> String {
+    let mut output = String::new();
+    let mut previous_end: Option<Duration> = None;
+
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        match previous_end {
+            Some(end) if segment.start.saturating_sub(end) >= PARAGRAPH_GAP => {
+                output.push_str("\n\n")
+            }
+            Some(_) => output.push(' '),
+            None => {}
+        }
+        output.push_str(text);
+        previous_end = Some(segment.end);
+    }
+
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+/// JSON representation of a transcript
+#[derive(Serialize)]
+struct JsonTranscript<'a> {
+    language: Option<&'a str>,
+    /// End of the last segment, in seconds
+    duration: f64,
+    text: String,
+    segments: Vec<JsonSegment<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonSegment<'a> {
+    start: f64,
+    end: f64,
+    text: &'a str,
+    confidence: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tokens: Vec<JsonToken<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonToken<'a> {
+    text: &'a str,
+    probability: f32,
+}
+
+/// Render a transcript as pretty-printed JSON, with times in seconds
+pub fn to_json(transcript: &Transcript) -> Result<String> {
+    let segments = transcript
+        .segments
+        .iter()
+        .map(|segment| JsonSegment {
+            start: segment.start.as_secs_f64(),
+            end: segment.end.as_secs_f64(),
+            text: segment.text.trim(),
+            confidence: segment.confidence(),
+            tokens: segment
+                .tokens
+                .iter()
+                .map(|token| JsonToken {
+                    text: &token.text,
+                    probability: token.probability,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let json = JsonTranscript {
+        language: transcript.language.as_deref(),
+        duration: transcript
+            .segments
+            .last()
+            .map_or(0.0, |segment| segment.end.as_secs_f64()),
+        text: to_text(&transcript.segments).trim().replace("\n\n", " "),
+        segments,
+    };
+
+    serde_json::to_string_pretty(&json).context("Failed to serialize transcript")
+}
+
+/// Format an offset as `HH:MM:SS.mmm` (WebVTT style)
+pub fn format_timestamp(offset: Duration) -> String {
+    timestamp(offset, '.')
+}
+
+/// Format an offset as `HH:MM:SS<separator>mmm`
+fn timestamp(offset: Duration, separator: char) -> String {
+    let millis = offset.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::Token;
+
+    fn secs(s: f64) -> Duration {
+        Duration::from_secs_f64(s)
+    }
+
+    fn sample() -> Transcript {
+        Transcript::new(vec![
+            Segment::new(" Hello everyone.", secs(0.5), secs(2.0)).with_tokens(vec![
+                Token::new(" Hello", 0.9),
+                Token::new(" everyone.", 0.7),
+            ]),
+            Segment::new(" Let's get started.", secs(2.5), secs(4.0)),
+            Segment::new(" Next topic.", secs(10.0), secs(11.25)),
+        ])
+        .with_language("en")
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("SRT".parse::<ExportFormat>(), Ok(ExportFormat::Srt));
+        assert_eq!("webvtt".parse::<ExportFormat>(), Ok(ExportFormat::Vtt));
+        assert!("docx".parse::<ExportFormat>().is_err());
+        assert_eq!(
+            ExportFormat::from_path(Path::new("talk.vtt")),
+            Some(ExportFormat::Vtt)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("talk")), None);
+        for format in ExportFormat::all() {
+            assert_eq!(format.extension().parse::<ExportFormat>(), Ok(*format));
+        }
+    }
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(format_timestamp(Duration::ZERO), "00:00:00.000");
+        assert_eq!(
+            timestamp(Duration::from_millis(3_723_456), ','),
+            "01:02:03,456"
+        );
+    }
+
+    #[test]
+    fn test_srt() {
+        let cues = build_cues(&sample().segments, &CueOptions::default());
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:00,500 --> 00:00:02,000\nHello everyone.\n\n\
+             2\n00:00:02,500 --> 00:00:04,000\nLet's get started.\n\n\
+             3\n00:00:10,000 --> 00:00:11,250\nNext topic.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt() {
+        let output = export(&sample(), ExportFormat::Vtt, &CueOptions::default()).unwrap();
+        assert!(output.starts_with("WEBVTT\n\n00:00:00.500 --> 00:00:02.000\nHello everyone.\n\n"));
+        assert!(output.ends_with("00:00:10.000 --> 00:00:11.250\nNext topic.\n\n"));
+    }
+
+    #[test]
+    fn test_text_paragraphs() {
+        assert_eq!(
+            to_text(&sample().segments),
+            "Hello everyone. Let's get started.\n\nNext topic.\n"
+        );
+        assert_eq!(to_text(&[]), "");
+    }
+
+    #[test]
+    fn test_json() {
+        let output = to_json(&sample()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(value["language"], "en");
+        assert_eq!(value["duration"], 11.25);
+        assert_eq!(
+            value["text"],
+            "Hello everyone. Let's get started. Next topic."
+        );
+        assert_eq!(value["segments"].as_array().unwrap().len(), 3);
+        assert_eq!(value["segments"][0]["start"], 0.5);
+        assert_eq!(value["segments"][0]["tokens"][1]["text"], " everyone.");
+        assert!((value["segments"][0]["confidence"].as_f64().unwrap() - 0.8).abs() < 1e-6);
+        assert!(value["segments"][1]["confidence"].is_null());
+        assert!(value["segments"][1].get("tokens").is_none());
+    }
+
+    #[test]
+    fn test_long_segment_is_split_by_length() {
+        let text = "one two three four five six seven eight nine ten eleven twelve thirteen \
+                    fourteen fifteen sixteen seventeen eighteen nineteen twenty";
+        let segment = Segment::new(text, secs(0.0), secs(5.0));
+        let options = CueOptions {
+            max_line_chars: 20,
+            max_lines: 2,
+            max_duration: secs(60.0),
+        };
+
+        let cues = build_cues(&[segment], &options);
+        assert!(cues.len() > 1);
+
+        // Every word is kept, in order, within the line limits
+        let words: Vec<String> = cues.iter().flat_map(|cue| cue.lines.clone()).collect();
+        assert_eq!(
+            words.join(" "),
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        );
+        for cue in &cues {
+            assert!(cue.lines.len() <= 2, "{:?}", cue);
+            assert!(
+                cue.lines.iter().all(|line| line.chars().count() <= 20),
+                "{:?}",
+                cue
+            );
+        }
+
+        // Cues follow each other and cover the segment
+        assert_eq!(cues[0].start, Duration::ZERO);
+        assert_eq!(cues.last().unwrap().end, secs(5.0));
+        for pair in cues.windows(2) {
+            assert!(pair[0].start < pair[0].end);
+            assert!(pair[0].end <= pair[1].start);
+        }
+    }
+
+    #[test]
+    fn test_long_segment_is_split_by_duration() {
+        let segment = Segment::new("a b c d e f g h", secs(0.0), secs(16.0));
+        let options = CueOptions {
+            max_duration: secs(5.0),
+            ..Default::default()
+        };
+
+        let cues = build_cues(&[segment], &options);
+        assert!(cues.len() >= 4, "{:?}", cues);
+        for cue in &cues {
+            assert!(cue.end - cue.start <= secs(5.0), "{:?}", cue);
+        }
+    }
+
+    #[test]
+    fn test_overlong_word_gets_its_own_line() {
+        let segment = Segment::new(
+            "see https://example.com/a/very/long/path now",
+            secs(0.0),
+            secs(3.0),
+        );
+        let options = CueOptions {
+            max_line_chars: 10,
+            max_lines: 1,
+            ..Default::default()
+        };
+
+        let lines: Vec<Vec<String>> = build_cues(&[segment], &options)
+            .into_iter()
+            .map(|cue| cue.lines)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                vec!["see".to_string()],
+                vec!["https://example.com/a/very/long/path".to_string()],
+                vec!["now".to_string()],
+            ]
+        );
+    }
+}
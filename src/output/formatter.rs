@@ -2,6 +2,9 @@
 //!
 //! Post-processing for transcribed text
 
+use super::itn;
+use super::rules::TextRules;
+use crate::config::{Config, Language};
 use anyhow::Result;
 
 /// Formatting options
@@ -15,6 +18,15 @@ pub struct FormattingOptions {
     pub trim_whitespace: bool,
     /// Add final period if missing
     pub add_final_period: bool,
+    /// Remove hesitations ("um", "euh"...) of the text's language
+    pub remove_fillers: bool,
+    /// Write spoken numbers, amounts, times and dates with digits
+    pub normalize_numbers: bool,
+    /// Language of the text, for fillers and punctuation conventions
+    /// (none are applied for `Language::Auto`)
+    pub language: Language,
+    /// User-defined replacements and snippets, applied last
+    pub rules: TextRules,
 }
 
 impl Default for FormattingOptions {
@@ -24,6 +36,30 @@ impl Default for FormattingOptions {
             capitalize_sentences: true,
             trim_whitespace: true,
             add_final_period: false,
+            remove_fillers: true,
+            normalize_numbers: true,
+            language: Language::Auto,
+            rules: TextRules::default(),
+        }
+    }
+}
+
+impl FormattingOptions {
+    /// Build formatting options from the user's settings
+    pub fn from_config(config: &Config) -> Self {
+        // The configuration was validated when loaded
+        let rules = TextRules::from_config(&config.rules).unwrap_or_else(|e| {
+            log::warn!("Ignoring text rules: {:#}", e);
+            TextRules::default()
+        });
+
+        Self {
+            capitalize_sentences: config.ui.auto_capitalization,
+            remove_fillers: config.ui.remove_fillers,
+            normalize_numbers: config.ui.normalize_numbers,
+            language: Language::from_code(&config.transcription.language),
+            rules,
+            ..Default::default()
         }
     }
 }
@@ -41,10 +77,19 @@ pub fn format_text(text: &str, options: &FormattingOptions) -> Result<String> {
         }
     }
 
-    // Capitalize first letter
+    if options.remove_fillers {
+        formatted = transforms::remove_fillers(&formatted, &options.language);
+    }
+    if options.normalize_numbers {
+        formatted = itn::normalize(&formatted, &options.language);
+    }
+    formatted = transforms::punctuate(&formatted, &options.language);
+
+    // Capitalize first letter (after an opening "¿" or quote; text starting
+    // with a digit is left alone)
     if options.capitalize_sentences && !formatted.is_empty() {
         let mut chars: Vec<char> = formatted.chars().collect();
-        if let Some(first) = chars.first_mut() {
+        if let Some(first) = chars.iter_mut().find(|c| c.is_alphanumeric()) {
             *first = first.to_uppercase().next().unwrap_or(*first);
         }
         formatted = chars.into_iter().collect();
@@ -58,25 +103,195 @@ pub fn format_text(text: &str, options: &FormattingOptions) -> Result<String> {
         }
     }
 
+    if !options.rules.is_empty() {
+        formatted = options.rules.apply(&formatted);
+    }
+
     log::trace!("Formatted text: '{}' -> '{}'", text, formatted);
     Ok(formatted)
 }
 
 /// Common text transformations
 pub mod transforms {
+    use crate::config::Language;
+
+    /// Hesitations removed by [`remove_fillers`] for `language`
+    ///
+    /// Only sounds that are never real words are listed: "like" or "you
+    /// know" carry meaning too often to be dropped.
+    pub fn fillers(language: &Language) -> &'static [&'static str] {
+        match language {
+            Language::English => &["um", "umm", "uh", "uhh", "uhm", "er", "erm", "hmm"],
+            Language::French => &["euh", "euhm", "heu", "hum", "hmm"],
+            Language::Spanish => &["eh", "ehm", "em", "mmm"],
+            Language::German => &["äh", "ähm", "öh", "öhm", "hm"],
+            _ => &[],
+        }
+    }
+
     /// Remove filler words
-    pub fn remove_fillers(text: &str) -> String {
-        let fillers = ["um", "uh", "like", "you know", "kind of", "sort of"];
-        let mut result = text.to_string();
-
-        for filler in &fillers {
-            // Remove with surrounding spaces
-            result = result.replace(&format!(" {} ", filler), " ");
-            result = result.replace(&format!("{} ", filler), "");
-            result = result.replace(&format!(" {}", filler), "");
+    ///
+    /// Fillers are matched as whole words, whatever their case and the
+    /// punctuation around them ("Um," or "uh."). A filler ending a sentence
+    /// passes its period on to the previous word, and the word after a
+    /// capitalized filler starting a sentence is capitalized.
+    pub fn remove_fillers(text: &str, language: &Language) -> String {
+        let fillers = fillers(language);
+        let mut kept: Vec<String> = Vec::new();
+        let mut capitalize_next = false;
+
+        for token in text.split_whitespace() {
+            let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+            if word.is_empty() || !fillers.contains(&word.to_lowercase().as_str()) {
+                kept.push(if capitalize_next {
+                    capitalize_first(token)
+                } else {
+                    token.to_string()
+                });
+                capitalize_next = false;
+                continue;
+            }
+
+            let sentence_start = match kept.last() {
+                Some(last) => ends_sentence(last),
+                None => true,
+            };
+            if sentence_start && word.starts_with(char::is_uppercase) {
+                capitalize_next = true;
+            }
+
+            let trailing = &token[token.trim_end_matches(|c: char| !c.is_alphanumeric()).len()..];
+            let marks: String = trailing
+                .chars()
+                .filter(|c| matches!(c, '.' | '!' | '?'))
+                .collect();
+            if let Some(last) = kept.last_mut() {
+                if !marks.is_empty() && !ends_sentence(last) {
+                    let len = last.trim_end_matches([',', ';', ':']).len();
+                    last.truncate(len);
+                    last.push_str(&marks);
+                }
+            }
+        }
+
+        kept.join(" ")
+    }
+
+    /// Apply the punctuation conventions of `language`
+    ///
+    /// - no space before `,` and `.`, nor before `?`, `!`, `:` and `;`
+    ///   except in French, which puts a no-break space there and inside « »
+    /// - Spanish questions and exclamations get their opening `¿` / `¡`
+    pub fn punctuate(text: &str, language: &Language) -> String {
+        match language {
+            Language::Auto | Language::Custom(_) => text.to_string(),
+            Language::French => french_spacing(text),
+            Language::Spanish => spanish_opening_marks(&tight_spacing(text)),
+            _ => tight_spacing(text),
+        }
+    }
+
+    /// Remove spaces before punctuation ("Hello , world" -> "Hello, world")
+    fn tight_spacing(text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        for (i, &c) in chars.iter().enumerate() {
+            if c.is_whitespace() && closes_clause(&chars[i + 1..]).is_some() {
+                continue;
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn french_spacing(text: &str) -> String {
+        const NBSP: char = '\u{a0}';
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        for (i, &c) in chars.iter().enumerate() {
+            if c.is_whitespace() {
+                // Dropped here, put back as a no-break space if needed
+                if closes_clause(&chars[i + 1..]).is_some() || out.ends_with(['«', NBSP]) {
+                    continue;
+                }
+                out.push(c);
+                continue;
+            }
+
+            let spaced = match c {
+                '?' | '!' | ';' | '»' => true,
+                // Not in times (10:30) or URLs (https://)
+                ':' => {
+                    !matches!(
+                        (out.chars().last(), chars.get(i + 1)),
+                        (Some(prev), Some(next)) if prev.is_ascii_digit() && next.is_ascii_digit()
+                    ) && chars.get(i + 1) != Some(&'/')
+                }
+                _ => false,
+            };
+            if spaced
+                && !out.is_empty()
+                && !out.ends_with(|prev: char| prev.is_whitespace() || "?!:;".contains(prev))
+            {
+                out.push(NBSP);
+            }
+            out.push(c);
+            if c == '«' {
+                out.push(NBSP);
+            }
+        }
+        out
+    }
+
+    /// Punctuation mark starting `rest` if it ends a word or clause
+    /// (followed by a space or the end of the text, unlike ".NET")
+    fn closes_clause(rest: &[char]) -> Option<char> {
+        let (&c, after) = rest.split_first()?;
+        let ends_word = match after.first() {
+            Some(next) => next.is_whitespace() || ",.?!:;»".contains(*next),
+            None => true,
+        };
+        (ends_word && matches!(c, ',' | '.' | '?' | '!' | ':' | ';' | '»')).then_some(c)
+    }
+
+    fn spanish_opening_marks(text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + 2);
+        let mut sentence_start = 0;
+        let mut at_boundary = true;
+        for c in text.chars() {
+            if at_boundary && !c.is_whitespace() && !matches!(c, '.' | '?' | '!') {
+                sentence_start = out.len();
+                at_boundary = false;
+            }
+            let opening = match c {
+                '?' => Some('¿'),
+                '!' => Some('¡'),
+                _ => None,
+            };
+            if let Some(opening) = opening {
+                if !at_boundary && !out[sentence_start..].contains(opening) {
+                    out.insert(sentence_start, opening);
+                }
+            }
+            out.push(c);
+            if matches!(c, '.' | '?' | '!') {
+                at_boundary = true;
+            }
         }
+        out
+    }
 
-        result.trim().to_string()
+    fn ends_sentence(word: &str) -> bool {
+        word.ends_with(['.', '!', '?', '…'])
+    }
+
+    fn capitalize_first(word: &str) -> String {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
     }
 
     /// Convert to title case
@@ -123,6 +338,53 @@ mod tests {
         assert_eq!(result, "Hello world.");
     }
 
+    #[test]
+    fn test_options_from_config() {
+        let mut config = Config::default();
+        assert!(FormattingOptions::from_config(&config).capitalize_sentences);
+
+        config.ui.auto_capitalization = false;
+        let options = FormattingOptions::from_config(&config);
+        assert_eq!(format_text("hello", &options).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_rules_apply_after_options() {
+        let mut config = Config::default();
+        config
+            .rules
+            .replacements
+            .push(crate::config::ReplacementRule {
+                pattern: "Teh".to_string(),
+                replacement: "the".to_string(),
+                regex: false,
+                case_sensitive: true,
+                whole_word: true,
+            });
+        let options = FormattingOptions::from_config(&config);
+
+        // Capitalization runs first and produces "Teh"
+        assert_eq!(format_text("teh end", &options).unwrap(), "the end");
+
+        // Numbers are already written with digits
+        config.transcription.language = "en".to_string();
+        config
+            .rules
+            .replacements
+            .push(crate::config::ReplacementRule {
+                pattern: r"(\d+) degrees".to_string(),
+                replacement: "$1°".to_string(),
+                regex: true,
+                case_sensitive: false,
+                whole_word: true,
+            });
+        let options = FormattingOptions::from_config(&config);
+        assert_eq!(
+            format_text("it is twenty degrees", &options).unwrap(),
+            "It is 20°"
+        );
+    }
+
     #[test]
     fn test_format_trim() {
         let options = FormattingOptions::default();
@@ -133,8 +395,120 @@ mod tests {
     #[test]
     fn test_remove_fillers() {
         let text = "um hello uh world like you know";
-        let result = transforms::remove_fillers(text);
-        assert_eq!(result, "hello world");
+        let result = transforms::remove_fillers(text, &Language::English);
+        assert_eq!(result, "hello world like you know");
+    }
+
+    #[test]
+    fn test_remove_fillers_keeps_words_and_punctuation() {
+        let english = Language::English;
+        assert_eq!(
+            transforms::remove_fillers("Um, I think, uh, the summer was hummable", &english),
+            "I think, the summer was hummable"
+        );
+        assert_eq!(
+            transforms::remove_fillers("It works, uh. Erm, shall we go? Hmm!", &english),
+            "It works. Shall we go?"
+        );
+        assert_eq!(transforms::remove_fillers("Uh...", &english), "");
+    }
+
+    #[test]
+    fn test_fillers_per_language() {
+        assert_eq!(
+            transforms::remove_fillers("Euh, on y va, heu, demain", &Language::French),
+            "On y va, demain"
+        );
+        assert_eq!(
+            transforms::remove_fillers("Ähm, ich komme äh morgen", &Language::German),
+            "Ich komme morgen"
+        );
+        // "Um" is not a French filler, and nothing is removed without a
+        // known language
+        assert_eq!(
+            transforms::remove_fillers("um euh", &Language::French),
+            "um"
+        );
+        assert_eq!(transforms::remove_fillers("um", &Language::Auto), "um");
+    }
+
+    #[test]
+    fn test_french_spacing() {
+        let french = Language::French;
+        assert_eq!(
+            transforms::punctuate(
+                "Vraiment? Oui : à 10:30 ; voir https://voxai.dev !",
+                &french
+            ),
+            "Vraiment\u{a0}? Oui\u{a0}: à 10:30\u{a0}; voir https://voxai.dev\u{a0}!"
+        );
+        assert_eq!(
+            transforms::punctuate("Il a dit « bonjour » , puis ?!", &french),
+            "Il a dit «\u{a0}bonjour\u{a0}», puis\u{a0}?!"
+        );
+    }
+
+    #[test]
+    fn test_punctuation_spacing_removed() {
+        assert_eq!(
+            transforms::punctuate("Wie geht's ? Gut , danke . Mit .NET", &Language::German),
+            "Wie geht's? Gut, danke. Mit .NET"
+        );
+        assert_eq!(
+            transforms::punctuate("Hello , world", &Language::Auto),
+            "Hello , world"
+        );
+    }
+
+    #[test]
+    fn test_spanish_opening_marks() {
+        let spanish = Language::Spanish;
+        assert_eq!(
+            transforms::punctuate("Hola. Qué tal? Muy bien!", &spanish),
+            "Hola. ¿Qué tal? ¡Muy bien!"
+        );
+        assert_eq!(
+            transforms::punctuate("¿Vienes? Sí", &spanish),
+            "¿Vienes? Sí"
+        );
+    }
+
+    #[test]
+    fn test_format_language_rules() {
+        let options = FormattingOptions {
+            language: Language::Spanish,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_text("eh, qué hora es ?", &options).unwrap(),
+            "¿Qué hora es?"
+        );
+    }
+
+    #[test]
+    fn test_format_starting_with_digit() {
+        let options = FormattingOptions::default();
+        assert_eq!(format_text("3 apples", &options).unwrap(), "3 apples");
+        assert_eq!(format_text("« 3 apples", &options).unwrap(), "« 3 apples");
+        assert_eq!(format_text("« apples", &options).unwrap(), "« Apples");
+    }
+
+    #[test]
+    fn test_format_numbers() {
+        let mut options = FormattingOptions {
+            language: Language::English,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_text("uh twenty five percent on march third", &options).unwrap(),
+            "25% on March 3"
+        );
+
+        options.normalize_numbers = false;
+        assert_eq!(
+            format_text("twenty five percent", &options).unwrap(),
+            "Twenty five percent"
+        );
     }
 
     #[test]
@@ -0,0 +1,943 @@
+//! Inverse text normalization
+//!
+//! The [`format_text`](super::formatter::format_text) stage writing spoken
+//! numbers the way they are read: "twenty five dollars on march third"
+//! becomes "$25 on March 3". Cardinals, ordinals, currency, percentages,
+//! times and dates are handled in English and French; other languages are
+//! left untouched.
+//!
+//! Numbers below ten stay in words ("one idea") unless they are part of an
+//! amount, a time or a date ("$5", "7:00", "March 3").
+
+use crate::config::Language;
+
+/// Smallest number written with digits outside amounts, times and dates
+const MIN_STANDALONE: u64 = 10;
+
+/// Write the spoken numbers of `text` with digits
+pub fn normalize(text: &str, language: &Language) -> String {
+    let rewrite: fn(&[&str], usize) -> Rewrite = match language {
+        Language::English => english::rewrite,
+        Language::French => french::rewrite,
+        _ => return text.to_string(),
+    };
+
+    let (prefix, tokens) = tokenize(text);
+    let words: Vec<String> = tokens
+        .iter()
+        .map(|token| token.word.replace('’', "'").to_lowercase())
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    let mut out = String::with_capacity(text.len());
+    out.push_str(prefix);
+    let mut i = 0;
+    while i < tokens.len() {
+        // Numbers only span words separated by spaces or hyphens, and the
+        // letters of an abbreviation ("a.m.")
+        let mut joined = 1;
+        while i + joined < tokens.len()
+            && (is_joiner(tokens[i + joined - 1].after)
+                || is_abbreviation(&tokens[i + joined - 1], &tokens[i + joined]))
+        {
+            joined += 1;
+        }
+
+        match rewrite(&words[i..], joined) {
+            Rewrite::Write(written, consumed) if (1..=joined).contains(&consumed) => {
+                out.push_str(&written);
+                out.push_str(tokens[i + consumed - 1].after);
+                i += consumed;
+            }
+            Rewrite::Write(..) => unreachable!("rewrite beyond the joined words"),
+            Rewrite::Keep(kept) => {
+                for token in &tokens[i..i + kept.clamp(1, joined)] {
+                    out.push_str(token.word);
+                    out.push_str(token.after);
+                }
+                i += kept.clamp(1, joined);
+            }
+        }
+    }
+    out
+}
+
+/// What to do with the words at the current position
+enum Rewrite {
+    /// Replace that many words with the text
+    Write(String, usize),
+    /// Leave that many words as they are
+    Keep(usize),
+}
+
+/// Word of the text and the separator following it
+struct Token<'a> {
+    word: &'a str,
+    after: &'a str,
+}
+
+/// Split `text` into words (letters, digits and inner apostrophes) and
+/// return the text before the first one
+fn tokenize(text: &str) -> (&str, Vec<Token<'_>>) {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let is_word_char = |k: usize| {
+        let c = chars[k].1;
+        c.is_alphanumeric()
+            || (matches!(c, '\'' | '’')
+                && k > 0
+                && chars[k - 1].1.is_alphanumeric()
+                && chars
+                    .get(k + 1)
+                    .is_some_and(|next| next.1.is_alphanumeric()))
+    };
+
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut k = 0;
+    while k < chars.len() {
+        if !is_word_char(k) {
+            k += 1;
+            continue;
+        }
+        let start = chars[k].0;
+        while k < chars.len() && is_word_char(k) {
+            k += 1;
+        }
+        let end = chars.get(k).map_or(text.len(), |c| c.0);
+        spans.push((start, end));
+    }
+
+    let prefix = &text[..spans.first().map_or(text.len(), |span| span.0)];
+    let tokens = spans
+        .iter()
+        .enumerate()
+        .map(|(n, &(start, end))| Token {
+            word: &text[start..end],
+            after: &text[end..spans.get(n + 1).map_or(text.len(), |next| next.0)],
+        })
+        .collect();
+    (prefix, tokens)
+}
+
+fn is_joiner(separator: &str) -> bool {
+    !separator.is_empty() && separator.chars().all(|c| c.is_whitespace() || c == '-')
+}
+
+/// Whether `token` and `next` are single letters separated by a dot
+fn is_abbreviation(token: &Token, next: &Token) -> bool {
+    let is_letter = |word: &str| word.chars().count() == 1;
+    token.after == "." && is_letter(token.word) && is_letter(next.word)
+}
+
+/// Number written with a digit group separator from ten thousand up
+fn format_number(value: u64, separator: char) -> String {
+    let digits = value.to_string();
+    if value < 10_000 {
+        return digits;
+    }
+    let groups: Vec<&str> = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect();
+    groups.join(&separator.to_string())
+}
+
+/// Number already written with digits
+fn digits(word: &str) -> Option<u64> {
+    if word.is_empty() || word.len() > 9 || !word.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    word.parse().ok()
+}
+
+mod english {
+    use super::{digits, format_number, Rewrite, MIN_STANDALONE};
+
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Word {
+        Zero,
+        Unit(u64),
+        Teen(u64),
+        Tens(u64),
+        Hundred,
+        Scale(u64),
+        And,
+    }
+
+    fn word(w: &str) -> Option<Word> {
+        let unit = |u| Some(Word::Unit(u));
+        match w {
+            "zero" => Some(Word::Zero),
+            "one" => unit(1),
+            "two" => unit(2),
+            "three" => unit(3),
+            "four" => unit(4),
+            "five" => unit(5),
+            "six" => unit(6),
+            "seven" => unit(7),
+            "eight" => unit(8),
+            "nine" => unit(9),
+            "ten" => Some(Word::Teen(10)),
+            "eleven" => Some(Word::Teen(11)),
+            "twelve" => Some(Word::Teen(12)),
+            "thirteen" => Some(Word::Teen(13)),
+            "fourteen" => Some(Word::Teen(14)),
+            "fifteen" => Some(Word::Teen(15)),
+            "sixteen" => Some(Word::Teen(16)),
+            "seventeen" => Some(Word::Teen(17)),
+            "eighteen" => Some(Word::Teen(18)),
+            "nineteen" => Some(Word::Teen(19)),
+            "twenty" => Some(Word::Tens(20)),
+            "thirty" => Some(Word::Tens(30)),
+            "forty" => Some(Word::Tens(40)),
+            "fifty" => Some(Word::Tens(50)),
+            "sixty" => Some(Word::Tens(60)),
+            "seventy" => Some(Word::Tens(70)),
+            "eighty" => Some(Word::Tens(80)),
+            "ninety" => Some(Word::Tens(90)),
+            "hundred" => Some(Word::Hundred),
+            "thousand" => Some(Word::Scale(1_000)),
+            "million" => Some(Word::Scale(1_000_000)),
+            "billion" => Some(Word::Scale(1_000_000_000)),
+            "and" => Some(Word::And),
+            _ => None,
+        }
+    }
+
+    /// Longest number at the start of `words`, and the words it spans
+    fn cardinal(words: &[&str]) -> Option<(u64, usize)> {
+        if let Some(value) = words.first().and_then(|w| digits(w)) {
+            return Some((value, 1));
+        }
+
+        let mut total = 0;
+        let mut small = 0;
+        let mut last_scale = u64::MAX;
+        let mut last: Option<Word> = None;
+        let mut consumed = 0;
+        for (k, w) in words.iter().enumerate() {
+            let Some(kind) = word(w) else {
+                break;
+            };
+            let after_group = matches!(
+                last,
+                None | Some(Word::Hundred | Word::Scale(_) | Word::And)
+            );
+            let accepted = match kind {
+                Word::Zero => {
+                    if last.is_none() {
+                        return Some((0, 1));
+                    }
+                    false
+                }
+                Word::Unit(u) if after_group || matches!(last, Some(Word::Tens(_))) => {
+                    small += u;
+                    true
+                }
+                Word::Teen(t) | Word::Tens(t) if after_group => {
+                    small += t;
+                    true
+                }
+                Word::Hundred
+                    if matches!(last, Some(Word::Unit(_) | Word::Teen(_))) && small < 100 =>
+                {
+                    small *= 100;
+                    true
+                }
+                Word::Scale(scale)
+                    if small > 0 && scale < last_scale && !matches!(last, Some(Word::And)) =>
+                {
+                    total += small * scale;
+                    small = 0;
+                    last_scale = scale;
+                    true
+                }
+                // Only part of the number if another number word follows
+                Word::And => matches!(last, Some(Word::Hundred | Word::Scale(_))),
+                _ => false,
+            };
+            if !accepted {
+                break;
+            }
+            last = Some(kind);
+            if kind != Word::And {
+                consumed = k + 1;
+            }
+        }
+
+        (consumed > 0).then_some((total + small, consumed))
+    }
+
+    /// Number word an ordinal is built on ("third" -> "three")
+    fn ordinal_base(w: &str) -> Option<String> {
+        let base = match w {
+            "first" => "one",
+            "second" => "two",
+            "third" => "three",
+            "fifth" => "five",
+            "eighth" => "eight",
+            "ninth" => "nine",
+            "twelfth" => "twelve",
+            _ => {
+                let base = match w.strip_suffix("ieth") {
+                    Some(stem) => format!("{}y", stem),
+                    None => w.strip_suffix("th")?.to_string(),
+                };
+                return word(&base).map(|_| base);
+            }
+        };
+        Some(base.to_string())
+    }
+
+    /// Ordinal at the start of `words` ("twenty first" -> 21)
+    fn ordinal(words: &[&str]) -> Option<(u64, usize)> {
+        for (k, w) in words.iter().enumerate() {
+            if let Some(base) = ordinal_base(w) {
+                let mut spoken: Vec<&str> = words[..k].to_vec();
+                spoken.push(&base);
+                let (value, consumed) = cardinal(&spoken)?;
+                return (consumed == k + 1).then_some((value, k + 1));
+            }
+            word(w)?;
+        }
+        None
+    }
+
+    fn ordinal_suffix(value: u64) -> &'static str {
+        match (value % 10, value % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        }
+    }
+
+    fn month(w: &str) -> Option<&'static str> {
+        MONTHS
+            .iter()
+            .copied()
+            .find(|month| month.eq_ignore_ascii_case(w))
+    }
+
+    /// Day of the month: "third", "twenty first", or a number for months
+    /// that are not also common words ("may one")
+    fn day(words: &[&str], month: &str) -> Option<(u64, usize)> {
+        let (day, consumed) = ordinal(words).or_else(|| {
+            if month == "May" || month == "March" {
+                return None;
+            }
+            cardinal(words)
+        })?;
+        (1..=31).contains(&day).then_some((day, consumed))
+    }
+
+    /// Year: "two thousand twenty four" or "twenty twenty four"
+    fn year(words: &[&str]) -> Option<(u64, usize)> {
+        if let Some((year, consumed)) = cardinal(words) {
+            if (1000..=2999).contains(&year) {
+                return Some((year, consumed));
+            }
+        }
+
+        let (century, first) = cardinal(&words[..words.len().min(2)])?;
+        if !(10..=99).contains(&century) {
+            return None;
+        }
+        let rest = &words[first..];
+        let (decade, second) = match rest.first() {
+            Some(&"oh") => {
+                let (unit, _) = cardinal(&rest[1..rest.len().min(2)])?;
+                (unit, 2)
+            }
+            _ => cardinal(&rest[..rest.len().min(2)])?,
+        };
+        (decade <= 99 && (decade >= 10 || rest[0] == "oh"))
+            .then_some((century * 100 + decade, first + second))
+    }
+
+    /// Minutes after the hour: "oh five", "fifteen", "forty five"
+    fn minutes(words: &[&str]) -> Option<(u64, usize)> {
+        if words.first() == Some(&"oh") {
+            let (minutes, _) = cardinal(&words[1..words.len().min(2)])?;
+            return (1..=9).contains(&minutes).then_some((minutes, 2));
+        }
+        let (minutes, consumed) = cardinal(&words[..words.len().min(2)])?;
+        (10..=59).contains(&minutes).then_some((minutes, consumed))
+    }
+
+    /// "am", "pm", "a.m.", "p m"...
+    fn is_meridiem(words: &[&str]) -> bool {
+        matches!(words, ["am" | "pm", ..] | ["a" | "p", "m", ..])
+    }
+
+    fn currency(w: &str) -> Option<&'static str> {
+        match w {
+            "dollar" | "dollars" => Some("$"),
+            "euro" | "euros" => Some("€"),
+            "pound" | "pounds" => Some("£"),
+            _ => None,
+        }
+    }
+
+    fn is_number(w: &str) -> bool {
+        (word(w).is_some() && w != "and") || digits(w).is_some()
+    }
+
+    /// Rewrite the words starting `words`; only the first `joined` may be
+    /// replaced
+    pub(super) fn rewrite(words: &[&str], joined: usize) -> Rewrite {
+        written(words, joined).unwrap_or(Rewrite::Keep(1))
+    }
+
+    fn written(words: &[&str], joined: usize) -> Option<Rewrite> {
+        let span = &words[..joined];
+
+        // Dates: "march third", "march third twenty twenty four"
+        if let Some(month) = month(span[0]) {
+            if let Some((day, consumed)) = day(&span[1..], month) {
+                let end = 1 + consumed;
+                return Some(match year(&span[end..]) {
+                    Some((year, years)) => {
+                        Rewrite::Write(format!("{} {}, {}", month, day, year), end + years)
+                    }
+                    None => Rewrite::Write(format!("{} {}", month, day), end),
+                });
+            }
+        }
+        // "the third of march"
+        if span[0] == "the" {
+            if let Some((day, consumed)) = ordinal(&span[1..]) {
+                let rest = &span[1 + consumed..];
+                if let (["of", name, ..], 1..=31) = (rest, day) {
+                    if let Some(month) = month(name) {
+                        return Some(Rewrite::Write(format!("{} {}", month, day), consumed + 3));
+                    }
+                }
+            }
+        }
+
+        if let Some((ordinal, consumed)) = ordinal(span) {
+            if ordinal < MIN_STANDALONE {
+                return Some(Rewrite::Keep(consumed));
+            }
+            return Some(Rewrite::Write(
+                format!("{}{}", format_number(ordinal, ','), ordinal_suffix(ordinal)),
+                consumed,
+            ));
+        }
+
+        let (value, consumed) = cardinal(span)?;
+        let number = format_number(value, ',');
+        let rest = &span[consumed..];
+
+        // Amounts: "twenty five dollars and fifty cents"
+        if let Some(symbol) = rest.first().and_then(|w| currency(w)) {
+            if let ["and", cents_words @ ..] = &rest[1..] {
+                if let Some((cents, spoken)) = cardinal(cents_words) {
+                    if matches!(cents_words.get(spoken), Some(&("cent" | "cents"))) && cents < 100 {
+                        return Some(Rewrite::Write(
+                            format!("{}{}.{:02}", symbol, number, cents),
+                            consumed + 3 + spoken,
+                        ));
+                    }
+                }
+            }
+            return Some(Rewrite::Write(
+                format!("{}{}", symbol, number),
+                consumed + 1,
+            ));
+        }
+        match rest {
+            ["percent", ..] => return Some(Rewrite::Write(format!("{}%", number), consumed + 1)),
+            ["per", "cent", ..] => {
+                return Some(Rewrite::Write(format!("{}%", number), consumed + 2))
+            }
+            _ => {}
+        }
+
+        // Times: "seven o'clock", "three thirty pm", "seven pm"
+        if consumed == 1 && (1..=12).contains(&value) {
+            if rest.first() == Some(&"o'clock") {
+                return Some(Rewrite::Write(format!("{}:00", value), 2));
+            }
+            if let Some((minutes, spoken)) = minutes(rest) {
+                if is_meridiem(&span[1 + spoken..]) {
+                    return Some(Rewrite::Write(
+                        format!("{}:{:02}", value, minutes),
+                        1 + spoken,
+                    ));
+                }
+            }
+            if is_meridiem(&span[1..]) {
+                return Some(Rewrite::Write(value.to_string(), 1));
+            }
+        }
+
+        // "three thirty" could be a time, a score...
+        let following = rest.iter().take_while(|w| is_number(w)).count();
+        if value < MIN_STANDALONE || following > 0 || digits(span[0]).is_some() {
+            return Some(Rewrite::Keep(consumed + following));
+        }
+        Some(Rewrite::Write(number, consumed))
+    }
+}
+
+mod french {
+    use super::{digits, format_number, Rewrite, MIN_STANDALONE};
+
+    const MONTHS: [&str; 12] = [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ];
+
+    /// No-break space between a number and its unit ("25 €", "15 h")
+    const NBSP: char = '\u{a0}';
+    /// Digit group separator ("12 000")
+    const GROUP: char = '\u{202f}';
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Word {
+        Zero,
+        Unit(u64),
+        Ten,
+        Teen(u64),
+        Tens(u64),
+        Vingt,
+        Hundred,
+        Scale(u64),
+        Et,
+    }
+
+    fn word(w: &str) -> Option<Word> {
+        let unit = |u| Some(Word::Unit(u));
+        match w {
+            "zéro" => Some(Word::Zero),
+            "un" | "une" => unit(1),
+            "deux" => unit(2),
+            "trois" => unit(3),
+            "quatre" => unit(4),
+            "cinq" => unit(5),
+            "six" => unit(6),
+            "sept" => unit(7),
+            "huit" => unit(8),
+            "neuf" => unit(9),
+            "dix" => Some(Word::Ten),
+            "onze" => Some(Word::Teen(11)),
+            "douze" => Some(Word::Teen(12)),
+            "treize" => Some(Word::Teen(13)),
+            "quatorze" => Some(Word::Teen(14)),
+            "quinze" => Some(Word::Teen(15)),
+            "seize" => Some(Word::Teen(16)),
+            "vingt" | "vingts" => Some(Word::Vingt),
+            "trente" => Some(Word::Tens(30)),
+            "quarante" => Some(Word::Tens(40)),
+            "cinquante" => Some(Word::Tens(50)),
+            "soixante" => Some(Word::Tens(60)),
+            "cent" | "cents" => Some(Word::Hundred),
+            "mille" | "mil" => Some(Word::Scale(1_000)),
+            "million" | "millions" => Some(Word::Scale(1_000_000)),
+            "milliard" | "milliards" => Some(Word::Scale(1_000_000_000)),
+            "et" => Some(Word::Et),
+            _ => None,
+        }
+    }
+
+    /// Longest number at the start of `words`, and the words it spans
+    ///
+    /// Handles the vigesimal forms: "soixante-dix-sept", "quatre-vingt-onze".
+    fn cardinal(words: &[&str]) -> Option<(u64, usize)> {
+        if let Some(value) = words.first().and_then(|w| digits(w)) {
+            return Some((value, 1));
+        }
+
+        let mut total = 0;
+        let mut hundreds = 0;
+        // Part of the current group below one hundred
+        let mut rest = 0;
+        let mut last_scale = u64::MAX;
+        let mut last: Option<Word> = None;
+        let mut consumed = 0;
+        for (k, w) in words.iter().enumerate() {
+            let Some(kind) = word(w) else {
+                break;
+            };
+            let group_start = matches!(last, None | Some(Word::Hundred | Word::Scale(_)));
+            let accepted = match kind {
+                Word::Zero => {
+                    if last.is_none() {
+                        return Some((0, 1));
+                    }
+                    false
+                }
+                Word::Unit(u) => {
+                    let fits = group_start
+                        // vingt-deux, quatre-vingt-trois
+                        || (matches!(last, Some(Word::Tens(_) | Word::Vingt)) && rest % 10 == 0)
+                        // vingt et un
+                        || (last == Some(Word::Et) && u == 1)
+                        // dix-sept, soixante-dix-huit
+                        || (last == Some(Word::Ten) && u >= 7);
+                    if fits {
+                        rest += u;
+                    }
+                    fits
+                }
+                Word::Ten | Word::Teen(_) => {
+                    let value = match kind {
+                        Word::Teen(t) => t,
+                        _ => 10,
+                    };
+                    // soixante-dix, quatre-vingt-douze, soixante et onze
+                    let fits = group_start
+                        || (matches!(last, Some(Word::Tens(60) | Word::Vingt))
+                            && matches!(rest, 60 | 80))
+                        || (last == Some(Word::Et) && rest == 60 && value == 11);
+                    if fits {
+                        rest += value;
+                    }
+                    fits
+                }
+                Word::Tens(t) if group_start => {
+                    rest = t;
+                    true
+                }
+                Word::Vingt if group_start => {
+                    rest = 20;
+                    true
+                }
+                // quatre-vingt
+                Word::Vingt if last == Some(Word::Unit(4)) && rest == 4 => {
+                    rest = 80;
+                    true
+                }
+                // cent, deux cents, mille cent
+                Word::Hundred
+                    if hundreds == 0
+                        && ((group_start && rest == 0)
+                            || (matches!(last, Some(Word::Unit(_))) && rest >= 2)) =>
+                {
+                    hundreds = rest.max(1) * 100;
+                    rest = 0;
+                    true
+                }
+                Word::Scale(scale) if scale < last_scale => {
+                    let multiplier = hundreds + rest;
+                    // "mille" alone, "un million"
+                    if multiplier == 0 && (scale != 1_000 || last.is_some()) {
+                        false
+                    } else {
+                        total += multiplier.max(1) * scale;
+                        hundreds = 0;
+                        rest = 0;
+                        last_scale = scale;
+                        true
+                    }
+                }
+                Word::Et => {
+                    matches!(last, Some(Word::Tens(_) | Word::Vingt))
+                        && matches!(words.get(k + 1), Some(&("un" | "une" | "onze")))
+                }
+                _ => false,
+            };
+            if !accepted {
+                break;
+            }
+            last = Some(kind);
+            if kind != Word::Et {
+                consumed = k + 1;
+            }
+        }
+
+        (consumed > 0).then_some((total + hundreds + rest, consumed))
+    }
+
+    /// Number word an ordinal is built on ("neuvième" -> "neuf")
+    fn ordinal_base(w: &str) -> Option<String> {
+        let stem = w.strip_suffix("ième").or_else(|| w.strip_suffix("ièmes"))?;
+        let candidates = match stem {
+            "cinqu" => vec!["cinq".to_string()],
+            "neuv" => vec!["neuf".to_string()],
+            _ => vec![stem.to_string(), format!("{}e", stem)],
+        };
+        candidates.into_iter().find(|base| word(base).is_some())
+    }
+
+    /// Ordinal at the start of `words` and its suffix ("premier" -> 1er,
+    /// "vingt et unième" -> 21e)
+    fn ordinal(words: &[&str]) -> Option<(u64, &'static str, usize)> {
+        match words.first() {
+            Some(&("premier" | "premiers")) => return Some((1, "er", 1)),
+            Some(&("première" | "premières")) => return Some((1, "re", 1)),
+            _ => {}
+        }
+        for (k, w) in words.iter().enumerate() {
+            if let Some(base) = ordinal_base(w) {
+                let mut spoken: Vec<&str> = words[..k].to_vec();
+                spoken.push(&base);
+                let (value, consumed) = cardinal(&spoken)?;
+                return (consumed == k + 1).then_some((value, "e", k + 1));
+            }
+            word(w)?;
+        }
+        None
+    }
+
+    fn month(w: &str) -> Option<&'static str> {
+        let w = if w == "aout" { "août" } else { w };
+        MONTHS.iter().copied().find(|month| *month == w)
+    }
+
+    fn currency(w: &str) -> Option<&'static str> {
+        match w {
+            "euro" | "euros" => Some("€"),
+            "dollar" | "dollars" => Some("$"),
+            _ => None,
+        }
+    }
+
+    fn is_number(w: &str) -> bool {
+        (word(w).is_some() && w != "et") || digits(w).is_some()
+    }
+
+    /// Rewrite the words starting `words`; only the first `joined` may be
+    /// replaced
+    pub(super) fn rewrite(words: &[&str], joined: usize) -> Rewrite {
+        written(words, joined).unwrap_or(Rewrite::Keep(1))
+    }
+
+    fn written(words: &[&str], joined: usize) -> Option<Rewrite> {
+        let span = &words[..joined];
+
+        // Dates: "premier mai", "trois mars deux mille vingt-quatre"
+        let day = match ordinal(span) {
+            Some((1, suffix, consumed)) => Some((format!("1{}", suffix), consumed)),
+            Some(_) => None,
+            None => cardinal(span)
+                .filter(|(day, _)| (1..=31).contains(day))
+                .map(|(day, consumed)| (day.to_string(), consumed)),
+        };
+        if let Some((day, consumed)) = day {
+            if let Some(month) = span.get(consumed).and_then(|w| month(w)) {
+                let end = consumed + 1;
+                let year = cardinal(&span[end..]).filter(|(year, _)| (1000..=2999).contains(year));
+                return Some(match year {
+                    Some((year, years)) => {
+                        Rewrite::Write(format!("{} {} {}", day, month, year), end + years)
+                    }
+                    None => Rewrite::Write(format!("{} {}", day, month), end),
+                });
+            }
+        }
+
+        if let Some((ordinal, suffix, consumed)) = ordinal(span) {
+            if ordinal < MIN_STANDALONE {
+                return Some(Rewrite::Keep(consumed));
+            }
+            return Some(Rewrite::Write(
+                format!("{}{}", format_number(ordinal, GROUP), suffix),
+                consumed,
+            ));
+        }
+
+        let (value, consumed) = cardinal(span)?;
+        let number = format_number(value, GROUP);
+        let rest = &span[consumed..];
+
+        // Amounts: "vingt-cinq euros cinquante"
+        if let Some(symbol) = rest.first().and_then(|w| currency(w)) {
+            if let Some((cents, spoken)) = cardinal(&rest[1..]).filter(|(cents, _)| *cents < 100) {
+                let unit = matches!(rest.get(1 + spoken), Some(&("centime" | "centimes")));
+                return Some(Rewrite::Write(
+                    format!("{},{:02}{}{}", number, cents, NBSP, symbol),
+                    consumed + 1 + spoken + usize::from(unit),
+                ));
+            }
+            return Some(Rewrite::Write(
+                format!("{}{}{}", number, NBSP, symbol),
+                consumed + 1,
+            ));
+        }
+        match rest {
+            ["pour", "cent", ..] => {
+                return Some(Rewrite::Write(format!("{}{}%", number, NBSP), consumed + 2))
+            }
+            ["pourcent", ..] => {
+                return Some(Rewrite::Write(format!("{}{}%", number, NBSP), consumed + 1))
+            }
+            _ => {}
+        }
+
+        // Times: "quinze heures trente", "trois heures et quart"
+        if matches!(rest.first(), Some(&("heure" | "heures"))) && value <= 23 {
+            let hour = format!("{}{}h", value, NBSP);
+            let after = &rest[1..];
+            let minutes = match after {
+                ["et", "quart", ..] => Some((15, 2)),
+                ["et", "demie", ..] => Some((30, 2)),
+                _ => cardinal(after).filter(|(minutes, _)| (1..=59).contains(minutes)),
+            };
+            return Some(match minutes {
+                Some((minutes, spoken)) => Rewrite::Write(
+                    format!("{}{}{:02}", hour, NBSP, minutes),
+                    consumed + 1 + spoken,
+                ),
+                None => Rewrite::Write(hour, consumed + 1),
+            });
+        }
+
+        let following = rest.iter().take_while(|w| is_number(w)).count();
+        if value < MIN_STANDALONE || following > 0 || digits(span[0]).is_some() {
+            return Some(Rewrite::Keep(consumed + following));
+        }
+        Some(Rewrite::Write(number, consumed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(language: Language, cases: &[(&str, &str)]) {
+        for (spoken, written) in cases {
+            assert_eq!(
+                normalize(spoken, &language),
+                *written,
+                "normalizing {:?}",
+                spoken
+            );
+        }
+    }
+
+    #[test]
+    fn test_english() {
+        check(
+            Language::English,
+            &[
+                // Cardinals
+                ("I have one idea", "I have one idea"),
+                ("twenty five people", "25 people"),
+                ("twenty-five people", "25 people"),
+                ("one hundred and five", "105"),
+                ("three thousand four hundred twelve", "3412"),
+                ("twelve thousand", "12,000"),
+                ("two million five hundred thousand", "2,500,000"),
+                ("five six seven", "five six seven"),
+                ("ten, eleven", "10, 11"),
+                ("rock and roll", "rock and roll"),
+                // Ordinals
+                ("the first time", "the first time"),
+                ("the twenty first century", "the 21st century"),
+                ("her twelfth birthday", "her 12th birthday"),
+                ("the one hundred and third day", "the 103rd day"),
+                // Currency and percentages
+                ("twenty five dollars", "$25"),
+                ("five dollars and fifty cents", "$5.50"),
+                ("one pound", "£1"),
+                ("it costs 30 euros.", "it costs €30."),
+                ("fifty percent", "50%"),
+                ("five per cent off", "5% off"),
+                // Times
+                ("at seven o'clock", "at 7:00"),
+                ("at three thirty pm", "at 3:30 pm"),
+                ("at ten oh five a.m.", "at 10:05 a.m."),
+                ("at six p.m.", "at 6 p.m."),
+                ("three thirty", "three thirty"),
+                ("I have seven. Am I right", "I have seven. Am I right"),
+                ("I have twelve. Am I right", "I have 12. Am I right"),
+                ("at three thirty, pm", "at three thirty, pm"),
+                // Dates
+                ("twenty five dollars on march third", "$25 on March 3"),
+                ("June twenty first, 2024", "June 21, 2024"),
+                ("july fourth twenty twenty four", "July 4, 2024"),
+                ("may fifth nineteen oh five", "May 5, 1905"),
+                ("on the third of march", "on March 3"),
+                ("you may one day", "you may one day"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_french() {
+        check(
+            Language::French,
+            &[
+                // Cardinals
+                ("j'ai une idée", "j'ai une idée"),
+                ("vingt-cinq personnes", "25 personnes"),
+                ("soixante-dix-sept", "77"),
+                ("soixante et onze", "71"),
+                ("quatre-vingt-dix-neuf", "99"),
+                ("quatre-vingts ans", "80 ans"),
+                ("trente et un", "31"),
+                ("deux cent trois", "203"),
+                ("mille neuf cent quatre-vingt-quatre", "1984"),
+                ("douze mille", "12\u{202f}000"),
+                ("deux trois", "deux trois"),
+                // Ordinals
+                ("le deuxième jour", "le deuxième jour"),
+                ("le vingt et unième siècle", "le 21e siècle"),
+                ("la onzième fois", "la 11e fois"),
+                // Currency and percentages
+                ("vingt-cinq euros", "25\u{a0}€"),
+                ("trois euros cinquante", "3,50\u{a0}€"),
+                ("quinze pour cent", "15\u{a0}%"),
+                // Times
+                ("à quinze heures trente", "à 15\u{a0}h\u{a0}30"),
+                ("vers trois heures et quart", "vers 3\u{a0}h\u{a0}15"),
+                ("à midi", "à midi"),
+                // Dates
+                ("le premier mai", "le 1er mai"),
+                ("le trois mars deux mille vingt-quatre", "le 3 mars 2024"),
+                ("le vingt et un juin", "le 21 juin"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_other_languages_unchanged() {
+        check(
+            Language::German,
+            &[("twenty five dollars", "twenty five dollars")],
+        );
+        check(Language::Auto, &[("twenty five", "twenty five")]);
+    }
+
+    #[test]
+    fn test_punctuation_is_kept() {
+        check(
+            Language::English,
+            &[
+                ("  Twenty five, twenty six!", "  25, 26!"),
+                ("(fifteen)", "(15)"),
+                ("twenty. Five", "20. Five"),
+            ],
+        );
+    }
+}
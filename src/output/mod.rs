@@ -1,11 +1,17 @@
 //! Text output module
 //!
-//! Handles text injection, clipboard operations, and formatting
+//! Handles text injection, clipboard operations, formatting and transcript
+//! export
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 pub mod clipboard;
+pub mod commands;
+pub mod export;
 pub mod formatter;
+pub mod itn;
+pub mod rules;
 
 #[cfg(target_os = "windows")]
 pub mod text_injector_windows;
@@ -23,7 +29,8 @@ pub mod text_injector_linux;
 pub use text_injector_linux::TextInjector;
 
 /// Output method
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OutputMethod {
     /// Type the text using keyboard simulation
     TypeText,
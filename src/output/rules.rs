@@ -0,0 +1,346 @@
+//! User-defined text rules
+//!
+//! Replacements (literal or regex) and snippets configured in the `rules`
+//! section of the config file. Snippet triggers are expanded first; the
+//! replacements then run in configuration order, each on the output of the
+//! previous one, on the text around the snippets. Snippet text is inserted
+//! as written and never rewritten by a replacement.
+
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex, RegexBuilder};
+
+use crate::config::{ReplacementRule, RulesConfig};
+
+/// Compiled text rules
+#[derive(Debug, Clone, Default)]
+pub struct TextRules {
+    replacements: Vec<Replacement>,
+    /// One group per snippet trigger, longest triggers first
+    triggers: Option<Regex>,
+    /// Snippet texts, in the order of the trigger groups
+    snippets: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Replacement {
+    regex: Regex,
+    replacement: String,
+    /// Expand `$1`, `$name`... in the replacement
+    expand: bool,
+    /// Only replace whole words; checked on each match of a regex, whose
+    /// ends are only known once it matched
+    whole_word: bool,
+}
+
+impl TextRules {
+    /// Compile the configured rules
+    pub fn from_config(config: &RulesConfig) -> Result<Self> {
+        let replacements = config
+            .replacements
+            .iter()
+            .map(Replacement::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        // A longer trigger wins over one it starts with ("insert signature"
+        // over "insert")
+        let mut snippets: Vec<_> = config
+            .snippets
+            .iter()
+            .filter(|snippet| !snippet.trigger.trim().is_empty())
+            .collect();
+        snippets.sort_by_key(|snippet| std::cmp::Reverse(snippet.trigger.len()));
+
+        let triggers = if snippets.is_empty() {
+            None
+        } else {
+            let alternatives: Vec<String> = snippets
+                .iter()
+                .map(|snippet| format!("({})", trigger_pattern(&snippet.trigger)))
+                .collect();
+            let regex = RegexBuilder::new(&alternatives.join("|"))
+                .case_insensitive(true)
+                .build()
+                .context("Invalid snippet trigger")?;
+            Some(regex)
+        };
+
+        Ok(Self {
+            replacements,
+            triggers,
+            snippets: snippets
+                .into_iter()
+                .map(|snippet| snippet.text.clone())
+                .collect(),
+        })
+    }
+
+    /// Whether there is no rule to apply
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty() && self.triggers.is_none()
+    }
+
+    /// Expand snippets and apply the replacements to `text`
+    pub fn apply(&self, text: &str) -> String {
+        let Some(triggers) = &self.triggers else {
+            return self.replace(text);
+        };
+
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for captures in triggers.captures_iter(text) {
+            let Some(whole) = captures.get(0) else {
+                continue;
+            };
+            let Some(index) = (1..captures.len()).find(|&i| captures.get(i).is_some()) else {
+                continue;
+            };
+            result.push_str(&self.replace(&text[last..whole.start()]));
+            result.push_str(&self.snippets[index - 1]);
+            last = whole.end();
+        }
+        result.push_str(&self.replace(&text[last..]));
+        result
+    }
+
+    fn replace(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for replacement in &self.replacements {
+            text = replacement.apply(&text);
+        }
+        text
+    }
+}
+
+impl Replacement {
+    fn new(rule: &ReplacementRule) -> Result<Self> {
+        let pattern = match (rule.regex, rule.whole_word) {
+            (true, _) => rule.pattern.clone(),
+            (false, true) => word_bounded(&regex::escape(&rule.pattern), &rule.pattern),
+            (false, false) => regex::escape(&rule.pattern),
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!rule.case_sensitive)
+            .build()
+            .with_context(|| format!("Invalid replacement pattern '{}'", rule.pattern))?;
+        Ok(Self {
+            regex,
+            replacement: rule.replacement.clone(),
+            expand: rule.regex,
+            whole_word: rule.regex && rule.whole_word,
+        })
+    }
+
+    fn apply(&self, text: &str) -> String {
+        if !self.whole_word {
+            let replaced = if self.expand {
+                self.regex.replace_all(text, self.replacement.as_str())
+            } else {
+                self.regex.replace_all(text, NoExpand(&self.replacement))
+            };
+            return replaced.into_owned();
+        }
+
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for captures in self.regex.captures_iter(text) {
+            let whole = captures.get(0).expect("group 0 always matches");
+            if !is_whole_word(text, whole.start(), whole.end()) {
+                continue;
+            }
+            result.push_str(&text[last..whole.start()]);
+            captures.expand(&self.replacement, &mut result);
+            last = whole.end();
+        }
+        result.push_str(&text[last..]);
+        result
+    }
+}
+
+/// Pattern matching a spoken trigger, whatever the spacing and the
+/// punctuation the engine put after it ("Insert signature.")
+fn trigger_pattern(trigger: &str) -> String {
+    let words: Vec<String> = trigger.split_whitespace().map(regex::escape).collect();
+    let pattern = word_bounded(&words.join(r"[\s,]+"), trigger.trim());
+    format!("{}[.,!?;:]*", pattern)
+}
+
+/// Wrap `pattern` in word boundaries where `text` starts or ends with a word
+/// character (`\b` never matches next to punctuation)
+fn word_bounded(pattern: &str, text: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(is_word_char);
+    let start = if is_word(text.chars().next()) {
+        r"\b"
+    } else {
+        ""
+    };
+    let end = if is_word(text.chars().last()) {
+        r"\b"
+    } else {
+        ""
+    };
+    if end.is_empty() {
+        format!("{}{}", start, pattern)
+    } else {
+        format!("{}(?:{}){}", start, pattern, end)
+    }
+}
+
+/// Whether `text[start..end]` does not cut a word: each end that is a word
+/// character must not touch another one, the same rule as `word_bounded`
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let matched = &text[start..end];
+    let touches = |inner: Option<char>, outer: Option<char>| {
+        inner.is_some_and(is_word_char) && outer.is_some_and(is_word_char)
+    };
+    !touches(matched.chars().next(), text[..start].chars().next_back())
+        && !touches(matched.chars().next_back(), text[end..].chars().next())
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Snippet;
+
+    fn literal(pattern: &str, replacement: &str) -> ReplacementRule {
+        ReplacementRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            regex: false,
+            case_sensitive: false,
+            whole_word: true,
+        }
+    }
+
+    fn regex(pattern: &str, replacement: &str) -> ReplacementRule {
+        ReplacementRule {
+            regex: true,
+            ..literal(pattern, replacement)
+        }
+    }
+
+    fn snippet(trigger: &str, text: &str) -> Snippet {
+        Snippet {
+            trigger: trigger.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn rules(replacements: Vec<ReplacementRule>, snippets: Vec<Snippet>) -> TextRules {
+        TextRules::from_config(&RulesConfig {
+            replacements,
+            snippets,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_no_rules() {
+        let rules = TextRules::default();
+        assert!(rules.is_empty());
+        assert_eq!(rules.apply("Hello world"), "Hello world");
+    }
+
+    #[test]
+    fn test_literal_replacement() {
+        let rules = rules(vec![literal("voxai", "VoxAI")], vec![]);
+        assert_eq!(
+            rules.apply("Voxai and VOXAI, not voxaiz"),
+            "VoxAI and VoxAI, not voxaiz"
+        );
+
+        // `$` is not special in literal replacements
+        let rules = self::rules(vec![literal("dollars", "$1")], vec![]);
+        assert_eq!(rules.apply("ten dollars"), "ten $1");
+    }
+
+    #[test]
+    fn test_case_sensitive_and_partial_words() {
+        let mut rule = literal("co", "Company");
+        rule.case_sensitive = true;
+        rule.whole_word = false;
+        let rules = rules(vec![rule], vec![]);
+        assert_eq!(rules.apply("co Co coop"), "Company Co Companyop");
+    }
+
+    #[test]
+    fn test_regex_replacement() {
+        let rules = rules(vec![regex(r"(\d+) percent", "$1%")], vec![]);
+        assert_eq!(
+            rules.apply("Up 12 percent, then 3 Percent"),
+            "Up 12%, then 3%"
+        );
+        assert_eq!(rules.apply("12 percentage"), "12 percentage");
+    }
+
+    #[test]
+    fn test_regex_starting_with_punctuation() {
+        // A pattern starting with "$" matches after a space, where "\b" would
+        // not; its other end still has to end the word
+        let rules = rules(vec![regex(r"\$(\d+)", "$1 dollars")], vec![]);
+        assert_eq!(
+            rules.apply("Costs $5, not $50k"),
+            "Costs 5 dollars, not $50k"
+        );
+    }
+
+    #[test]
+    fn test_replacements_apply_in_order() {
+        // The second rule sees the output of the first one
+        let rules = self::rules(
+            vec![literal("e mail", "email"), literal("email", "e-mail")],
+            vec![],
+        );
+        assert_eq!(rules.apply("Send an e mail"), "Send an e-mail");
+
+        // The first matching rule wins over a later one for the same words
+        let rules = self::rules(
+            vec![literal("new york", "NYC"), literal("new york", "New York")],
+            vec![],
+        );
+        assert_eq!(rules.apply("new york"), "NYC");
+    }
+
+    #[test]
+    fn test_snippet_expansion() {
+        let rules = rules(
+            vec![],
+            vec![snippet("insert signature", "Best regards,\nAnna")],
+        );
+        assert_eq!(
+            rules.apply("Thanks. Insert signature."),
+            "Thanks. Best regards,\nAnna"
+        );
+        assert_eq!(rules.apply("insert, signature"), "Best regards,\nAnna");
+        assert_eq!(rules.apply("reinsert signatures"), "reinsert signatures");
+    }
+
+    #[test]
+    fn test_longest_snippet_trigger_wins() {
+        let rules = rules(
+            vec![],
+            vec![
+                snippet("insert address", "1 Main Street"),
+                snippet("insert address work", "5 Office Park"),
+            ],
+        );
+        assert_eq!(rules.apply("insert address work"), "5 Office Park");
+        assert_eq!(rules.apply("insert address"), "1 Main Street");
+    }
+
+    #[test]
+    fn test_snippet_text_is_not_replaced() {
+        let rules = rules(
+            vec![literal("regards", "cheers")],
+            vec![snippet("sign off", "Kind regards")],
+        );
+        assert_eq!(
+            rules.apply("regards to all, sign off"),
+            "cheers to all, Kind regards"
+        );
+    }
+}
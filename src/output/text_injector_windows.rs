@@ -49,7 +49,7 @@ impl TextInjector {
     }
 
     /// Type text character by character
-    fn type_text(&mut self, text: &str) -> Result<()> {
+    pub fn type_text(&mut self, text: &str) -> Result<()> {
         // Small delay to allow user to switch windows if needed
         thread::sleep(Duration::from_millis(100));
 
@@ -67,7 +67,7 @@ impl TextInjector {
     }
 
     /// Copy text to clipboard
-    fn copy_to_clipboard(&self, text: &str) -> Result<()> {
+    pub fn copy_to_clipboard(&self, text: &str) -> Result<()> {
         super::clipboard::copy_to_clipboard(text).context("Failed to copy text to clipboard")
     }
 
@@ -0,0 +1,452 @@
+//! Benchmark of the Whisper models on this machine
+//!
+//! Each model transcribes a reference clip while the memory of the process
+//! is sampled. The real-time factor (transcription time over audio
+//! duration) and the peak memory are cached in `benchmarks.json` in the
+//! config directory, together with the machine they were measured on, so
+//! that the `auto` model ([`configured_model`](super::models::configured_model))
+//! is the most accurate model meeting the latency budget.
+
+use super::engine::{samples_to_duration, TranscriptionEngine, ENGINE_SAMPLE_RATE};
+use super::models::{detect_gpu_available, is_model_downloaded, ModelRegistry};
+use crate::audio::resampler::StreamingResampler;
+use crate::config::{self, Config, Language, ModelInfo};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::System;
+
+/// Reference clip: a short speech recording (see `assets/bench/README.md`)
+const REFERENCE_WAV: &[u8] = include_bytes!("../../assets/bench/speech.wav");
+
+/// How often the memory of the process is sampled during a benchmark
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Benchmark cache file in the config directory
+const CACHE_FILE: &str = "benchmarks.json";
+
+/// Hardware a benchmark ran on
+///
+/// Results measured on another machine, or with the GPU used or not, do not
+/// apply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Machine {
+    pub cpu: String,
+    pub cpus: usize,
+    pub memory_mb: u64,
+    pub gpu: bool,
+}
+
+impl Machine {
+    /// Describe this machine; models run on the GPU when `use_gpu` is set
+    /// and whisper.cpp has one to use
+    pub fn detect(use_gpu: bool) -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu();
+        sys.refresh_memory();
+
+        Self {
+            cpu: sys
+                .cpus()
+                .first()
+                .map(|cpu| cpu.brand().trim().to_string())
+                .unwrap_or_default(),
+            cpus: sys.cpus().len(),
+            memory_mb: sys.total_memory() / (1024 * 1024),
+            gpu: use_gpu && detect_gpu_available(),
+        }
+    }
+}
+
+/// Measures of one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub model: String,
+    /// Duration of the transcribed audio in seconds
+    pub audio_secs: f64,
+    /// Time to load the model in seconds
+    pub load_secs: f64,
+    /// Time to transcribe the audio in seconds
+    pub transcribe_secs: f64,
+    /// Transcription time over audio duration: below 1.0 is faster than
+    /// real time
+    pub real_time_factor: f64,
+    /// Memory the process grew by while loading and running the model
+    pub peak_memory_mb: u64,
+    /// Unix time of the benchmark
+    pub measured_at: u64,
+}
+
+/// Benchmark results of the models on one machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkCache {
+    #[serde(default)]
+    pub machine: Option<Machine>,
+    #[serde(default)]
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl BenchmarkCache {
+    /// Path of the cache file in the config directory
+    pub fn path() -> Result<PathBuf> {
+        Ok(config::get_config_dir()?.join(CACHE_FILE))
+    }
+
+    /// Load the cached results, if any
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Save the results in the config directory
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Results that apply to `machine`
+    pub fn results_for(&self, machine: &Machine) -> &[BenchmarkResult] {
+        if self.machine.as_ref() == Some(machine) {
+            &self.results
+        } else {
+            &[]
+        }
+    }
+
+    /// Record the result of a model measured on `machine`, replacing its
+    /// previous one; results measured on another machine are dropped
+    pub fn insert(&mut self, machine: &Machine, result: BenchmarkResult) {
+        if self.machine.as_ref() != Some(machine) {
+            self.machine = Some(machine.clone());
+            self.results.clear();
+        }
+        self.results
+            .retain(|previous| !previous.model.eq_ignore_ascii_case(&result.model));
+        self.results.push(result);
+    }
+}
+
+/// Reference clip: the bundled speech recording, mono at 16kHz
+pub fn reference_clip() -> Result<Vec<f32>> {
+    decode_wav(REFERENCE_WAV).context("Invalid reference clip")
+}
+
+/// Decode a WAV file to mono audio at the engine sample rate
+fn decode_wav(wav: &[u8]) -> Result<Vec<f32>> {
+    let mut reader = hound::WavReader::new(Cursor::new(wav))?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    let mut resampler = StreamingResampler::new(spec.sample_rate, ENGINE_SAMPLE_RATE)?;
+    let mut clip = resampler.process(&mono)?;
+    clip.extend(resampler.flush()?);
+    Ok(clip)
+}
+
+/// Load `model` with `load` and transcribe `clip` with it, measuring the
+/// time and memory it takes
+pub fn benchmark<E, F>(
+    model: &ModelInfo,
+    load: F,
+    clip: &[f32],
+    language: &Language,
+) -> Result<BenchmarkResult>
+where
+    E: TranscriptionEngine,
+    F: FnOnce(&ModelInfo) -> Result<E>,
+{
+    let (timings, peak_memory) = with_peak_memory(|| -> Result<(Duration, Duration)> {
+        let started = Instant::now();
+        let engine = load(model).with_context(|| format!("Failed to load {} model", model))?;
+        let load_time = started.elapsed();
+
+        let started = Instant::now();
+        engine.transcribe(clip, language, None)?;
+        Ok((load_time, started.elapsed()))
+    });
+    let (load_time, transcribe_time) = timings?;
+
+    let audio_secs = samples_to_duration(clip.len()).as_secs_f64();
+    anyhow::ensure!(audio_secs > 0.0, "Empty benchmark clip");
+
+    Ok(BenchmarkResult {
+        model: model.name.clone(),
+        audio_secs,
+        load_secs: load_time.as_secs_f64(),
+        transcribe_secs: transcribe_time.as_secs_f64(),
+        real_time_factor: transcribe_time.as_secs_f64() / audio_secs,
+        peak_memory_mb: peak_memory / (1024 * 1024),
+        measured_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs()),
+    })
+}
+
+/// Run `f`, returning how many bytes the memory of the process grew by at
+/// most meanwhile
+fn with_peak_memory<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        return (f(), 0);
+    };
+    let mut sys = System::new();
+    let mut memory = move || {
+        sys.refresh_process(pid);
+        sys.process(pid).map_or(0, |process| process.memory())
+    };
+
+    let baseline = memory();
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let sampler = scope.spawn(|| {
+            let mut peak = baseline;
+            while !done.load(Ordering::Relaxed) {
+                peak = peak.max(memory());
+                std::thread::sleep(MEMORY_SAMPLE_INTERVAL);
+            }
+            peak.max(memory())
+        });
+        let output = f();
+        done.store(true, Ordering::Relaxed);
+        let peak = sampler.join().unwrap_or(baseline);
+        (output, peak.saturating_sub(baseline))
+    })
+}
+
+/// Most accurate of `models` that transcribes within `latency_budget`
+/// according to `results`
+///
+/// Models with more parameters are more accurate; between variants of one
+/// model, quantization costs a little accuracy.
+pub fn most_accurate<'a>(
+    models: impl IntoIterator<Item = &'a ModelInfo>,
+    results: &[BenchmarkResult],
+    latency_budget: f64,
+) -> Option<&'a ModelInfo> {
+    models
+        .into_iter()
+        .filter(|model| {
+            results.iter().any(|result| {
+                result.model.eq_ignore_ascii_case(&model.name)
+                    && result.real_time_factor <= latency_budget
+            })
+        })
+        .max_by_key(|model| (model.parameters_m, model.quantization.is_none(), model.size))
+}
+
+/// Most accurate downloaded model of the registry that transcribes the
+/// configured language within the latency budget, according to the results
+/// of `cache` for `machine`
+pub fn select_model(
+    config: &Config,
+    cache: &BenchmarkCache,
+    machine: &Machine,
+) -> Option<ModelInfo> {
+    let registry = ModelRegistry::from_config(&config.models);
+    let candidates = registry.all().iter().filter(|model| {
+        model.transcribes(&config.transcription.language)
+            && is_model_downloaded(model).unwrap_or(false)
+    });
+    most_accurate(
+        candidates,
+        cache.results_for(machine),
+        config.transcription.latency_budget as f64,
+    )
+    .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::MockEngine;
+
+    fn machine() -> Machine {
+        Machine {
+            cpu: "Test CPU".to_string(),
+            cpus: 8,
+            memory_mb: 16_384,
+            gpu: false,
+        }
+    }
+
+    fn result(model: &str, real_time_factor: f64) -> BenchmarkResult {
+        BenchmarkResult {
+            model: model.to_string(),
+            audio_secs: 10.0,
+            load_secs: 0.5,
+            transcribe_secs: 10.0 * real_time_factor,
+            real_time_factor,
+            peak_memory_mb: 300,
+            measured_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_reference_clip() {
+        let clip = reference_clip().unwrap();
+        assert_eq!(clip.len(), 86_400);
+        let peak = clip.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.1 && peak <= 1.0);
+    }
+
+    #[test]
+    fn test_decode_wav_to_mono_16khz() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut wav = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut wav, spec).unwrap();
+        for _ in 0..48_000 {
+            writer.write_sample(i16::MAX / 2).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let clip = decode_wav(wav.get_ref()).unwrap();
+        assert_eq!(clip.len(), ENGINE_SAMPLE_RATE as usize);
+        // Channels are averaged
+        assert!((clip[ENGINE_SAMPLE_RATE as usize / 2] - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_benchmark_measures_real_time_factor() {
+        let registry = ModelRegistry::builtin();
+        let model = registry.get("tiny").unwrap();
+        let clip = vec![0.0; ENGINE_SAMPLE_RATE as usize * 2];
+
+        let result = benchmark(
+            model,
+            |_| Ok(MockEngine::new().with_delay(Duration::from_millis(200))),
+            &clip,
+            &Language::English,
+        )
+        .unwrap();
+
+        assert_eq!(result.model, "tiny");
+        assert_eq!(result.audio_secs, 2.0);
+        assert!(result.transcribe_secs >= 0.2);
+        assert!((0.1..1.0).contains(&result.real_time_factor));
+        assert!(result.measured_at > 0);
+    }
+
+    #[test]
+    fn test_benchmark_load_error() {
+        let registry = ModelRegistry::builtin();
+        let result = benchmark(
+            registry.get("tiny").unwrap(),
+            |_| -> Result<MockEngine> { anyhow::bail!("no model file") },
+            &reference_clip().unwrap(),
+            &Language::English,
+        );
+        assert!(result.unwrap_err().to_string().contains("tiny"));
+    }
+
+    #[test]
+    fn test_cache_is_per_machine() {
+        let mut cache = BenchmarkCache::default();
+        cache.insert(&machine(), result("base", 0.2));
+        cache.insert(&machine(), result("small", 0.6));
+        cache.insert(&machine(), result("base", 0.3));
+        assert_eq!(cache.results_for(&machine()).len(), 2);
+        assert_eq!(cache.results_for(&machine())[1].real_time_factor, 0.3);
+
+        let gpu = Machine {
+            gpu: true,
+            ..machine()
+        };
+        assert!(cache.results_for(&gpu).is_empty());
+        cache.insert(&gpu, result("medium", 0.1));
+        assert!(cache.results_for(&machine()).is_empty());
+        assert_eq!(cache.results_for(&gpu).len(), 1);
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("voxai-benchmarks-{}.json", std::process::id()));
+        assert_eq!(
+            BenchmarkCache::load_from(&path).unwrap(),
+            BenchmarkCache::default()
+        );
+
+        let mut cache = BenchmarkCache::default();
+        cache.insert(&machine(), result("small", 0.4));
+        cache.save_to(&path).unwrap();
+        assert_eq!(BenchmarkCache::load_from(&path).unwrap(), cache);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_most_accurate_within_budget() {
+        let registry = ModelRegistry::builtin();
+        let results = vec![
+            result("tiny", 0.05),
+            result("base", 0.1),
+            result("small", 0.4),
+            result("medium", 1.2),
+        ];
+
+        let selected = |budget| most_accurate(registry.all(), &results, budget).map(|m| &m.name);
+        assert_eq!(selected(0.5).unwrap(), "small");
+        assert_eq!(selected(2.0).unwrap(), "medium");
+        assert_eq!(selected(0.08).unwrap(), "tiny");
+        assert!(selected(0.01).is_none());
+        // Models without results are never picked
+        assert!(most_accurate(registry.all(), &[], 2.0).is_none());
+    }
+
+    #[test]
+    fn test_most_accurate_ranks_parameters() {
+        let registry = ModelRegistry::builtin();
+        let results = vec![
+            result("tiny", 0.05),
+            result("medium", 0.4),
+            result("large-v3-q5_0", 0.45),
+            result("large-v3", 0.9),
+            // Not in the manifest: its file size is unknown
+            result("distil-large-v3", 0.3),
+        ];
+        assert_eq!(registry.get("distil-large-v3").unwrap().size, 0);
+
+        let selected = |budget| most_accurate(registry.all(), &results, budget).map(|m| &m.name);
+        // A quantized large model beats a smaller full precision one...
+        assert_eq!(selected(0.5).unwrap(), "large-v3-q5_0");
+        // ...but not its full precision variant
+        assert_eq!(selected(1.0).unwrap(), "large-v3");
+        // An unsized model is ranked on its parameters
+        assert_eq!(selected(0.35).unwrap(), "distil-large-v3");
+    }
+}
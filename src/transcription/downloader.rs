@@ -1,127 +1,489 @@
-//! Model downloader with progress indication
+//! Model downloader with progress reporting
 //!
-//! Downloads Whisper models from HuggingFace
+//! Downloads Whisper models from HuggingFace or the configured mirrors. An
+//! interrupted download is kept in a `.tmp` file and resumed with an HTTP
+//! range request; the complete file is checked against the model manifest
+//! before it is moved in place.
+//!
+//! Progress is published as [`DownloadProgress`] events through a
+//! [`DownloadMonitor`], which also cancels the download: the terminal
+//! progress bar of `voxai models download` and the settings window are both
+//! consumers of these events.
 
-use super::models::{get_model_path, ModelSize};
+use super::models::{get_model_path, get_models_dir, ModelRegistry};
+use crate::config::{ModelInfo, ModelsConfig};
 use anyhow::{Context, Result};
-use indicatif::{ProgressBar, ProgressStyle};
 use log::{info, warn};
-use reqwest::Client;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
 use sha2::{Digest, Sha256};
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Shortest time between two progress events while downloading
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stage of a model download
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadPhase {
+    /// Receiving the file
+    Downloading,
+    /// Checking the SHA256 of the complete file
+    Verifying,
+    /// The model is in place
+    Done,
+    /// The download stopped with this error
+    Failed(String),
+    /// Cancelled; the partial file is kept to resume later
+    Cancelled,
+}
+
+/// Progress event of a model download
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadProgress {
+    pub phase: DownloadPhase,
+    /// Bytes of the file downloaded so far, resumed ones included
+    pub downloaded: u64,
+    /// Size of the file, when the server tells it
+    pub total: Option<u64>,
+    /// Transfer rate in bytes per second
+    pub rate: f64,
+    /// Estimated time left
+    pub eta: Option<Duration>,
+}
+
+impl DownloadProgress {
+    fn new(phase: DownloadPhase, downloaded: u64, total: Option<u64>) -> Self {
+        Self {
+            phase,
+            downloaded,
+            total,
+            rate: 0.0,
+            eta: None,
+        }
+    }
+
+    /// Downloaded part of the file (0.0 to 1.0), when its size is known
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total {
+            Some(total) if total > 0 => {
+                Some((self.downloaded as f64 / total as f64).min(1.0) as f32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is the last event of the download
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.phase,
+            DownloadPhase::Done | DownloadPhase::Failed(_) | DownloadPhase::Cancelled
+        )
+    }
+}
+
+/// Progress reporting and cancellation of a download
+///
+/// The default monitor reports nothing and is never cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadMonitor {
+    events: Option<mpsc::UnboundedSender<DownloadProgress>>,
+    cancel: CancellationToken,
+}
+
+impl DownloadMonitor {
+    /// Monitor publishing progress events to the returned receiver
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<DownloadProgress>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        let monitor = Self {
+            events: Some(events),
+            cancel: CancellationToken::new(),
+        };
+        (monitor, receiver)
+    }
+
+    /// Token cancelling the download, for a task that does not need the
+    /// events
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Stop the download, keeping the partial file
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    fn publish(&self, progress: DownloadProgress) {
+        if let Some(events) = &self.events {
+            // Nobody may be listening anymore
+            let _ = events.send(progress);
+        }
+    }
+}
 
 /// Download a Whisper model
-#[allow(dead_code)]
-pub async fn download_model(model: ModelSize) -> Result<std::path::PathBuf> {
+pub async fn download_model(
+    model: &ModelInfo,
+    config: &ModelsConfig,
+    monitor: &DownloadMonitor,
+) -> Result<PathBuf> {
     let model_path = get_model_path(model)?;
 
     // Skip if already exists
     if model_path.exists() {
         info!("Model {} already exists at {:?}", model, model_path);
+        let size = tokio::fs::metadata(&model_path).await?.len();
+        monitor.publish(DownloadProgress::new(DownloadPhase::Done, size, Some(size)));
         return Ok(model_path);
     }
 
     info!("Downloading {} model...", model);
-    info!("URL: {}", model.download_url());
+    download_file(
+        &model_urls(model, config),
+        &model_path,
+        model.sha256.as_deref(),
+        &format!("{} model", model),
+        monitor,
+    )
+    .await?;
+
+    info!("Model saved to: {:?}", model_path);
+    Ok(model_path)
+}
+
+/// URLs `model` can be downloaded from: its own URL, or the base URL then
+/// the mirrors
+pub fn model_urls(model: &ModelInfo, config: &ModelsConfig) -> Vec<String> {
+    if let Some(url) = &model.url {
+        return vec![url.clone()];
+    }
+    std::iter::once(&config.base_url)
+        .chain(&config.mirrors)
+        .map(|base_url| model.download_url(base_url))
+        .collect()
+}
+
+/// Download a file from the first of `urls` that answers, publishing the
+/// final phase of the download to `monitor`
+async fn download_file(
+    urls: &[String],
+    path: &Path,
+    sha256: Option<&str>,
+    label: &str,
+    monitor: &DownloadMonitor,
+) -> Result<()> {
+    let result = download_and_verify(urls, path, sha256, label, monitor).await;
+
+    let size = |path: &Path| std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+    let progress = match &result {
+        Ok(()) => {
+            let size = size(path);
+            DownloadProgress::new(DownloadPhase::Done, size, Some(size))
+        }
+        Err(_) if monitor.is_cancelled() => DownloadProgress::new(
+            DownloadPhase::Cancelled,
+            size(&path.with_extension("tmp")),
+            None,
+        ),
+        Err(e) => DownloadProgress::new(
+            DownloadPhase::Failed(format!("{:#}", e)),
+            size(&path.with_extension("tmp")),
+            None,
+        ),
+    };
+    monitor.publish(progress);
+    result
+}
+
+/// Download a file from the first of `urls` that answers
+///
+/// A previous partial download of `path` is resumed, from whichever URL
+/// serves it. When `sha256` is known, a file that does not match it is
+/// deleted and an error returned.
+async fn download_and_verify(
+    urls: &[String],
+    path: &Path,
+    sha256: Option<&str>,
+    label: &str,
+    monitor: &DownloadMonitor,
+) -> Result<()> {
+    anyhow::ensure!(!urls.is_empty(), "No download URL for {}", label);
 
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(600))
         .build()?;
 
-    let response = client
-        .get(model.download_url())
-        .send()
-        .await?
-        .error_for_status()?;
-
-    let total_size = response
-        .content_length()
-        .ok_or_else(|| anyhow::anyhow!("Failed to get content length"))?;
-
-    // Create progress bar
-    let pb = ProgressBar::new(total_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{msg}\n[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-            )?
-            .progress_chars("█▓▒░ "),
-    );
-    pb.set_message(format!("Downloading {} model", model));
+    let temp_path = path.with_extension("tmp");
+    let mut last_error = None;
+    for url in urls {
+        info!("URL: {}", url);
+        match fetch(&client, url, &temp_path, label, monitor).await {
+            Ok(()) => {
+                last_error = None;
+                break;
+            }
+            Err(e) if monitor.is_cancelled() => return Err(e),
+            Err(e) => {
+                warn!("Download from {} failed: {:#}", url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if let Some(e) = last_error {
+        return Err(e.context(format!("Failed to download {}", label)));
+    }
+
+    // Calculate checksum
+    let size = tokio::fs::metadata(&temp_path).await?.len();
+    monitor.publish(DownloadProgress::new(
+        DownloadPhase::Verifying,
+        size,
+        Some(size),
+    ));
+    let checksum = file_sha256(&temp_path).await?;
+    info!("SHA256: {}", checksum);
+    if let Some(expected) = sha256 {
+        if !checksum.eq_ignore_ascii_case(expected) {
+            tokio::fs::remove_file(&temp_path).await?;
+            anyhow::bail!(
+                "Checksum mismatch for {}: expected {}, got {}. The corrupt download was deleted",
+                label,
+                expected,
+                checksum
+            );
+        }
+    }
+
+    // Rename temp file to final name
+    tokio::fs::rename(&temp_path, path)
+        .await
+        .context("Failed to rename downloaded file")
+}
+
+/// Transfer rate and throttling of the progress events of one transfer
+struct RateTracker {
+    started: Instant,
+    /// Bytes already on disk when the transfer started
+    resumed: u64,
+    last_event: Option<Instant>,
+}
+
+impl RateTracker {
+    fn new(resumed: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            resumed,
+            last_event: None,
+        }
+    }
+
+    /// Progress event for `downloaded` bytes, unless one was sent less than
+    /// [`PROGRESS_INTERVAL`] ago
+    fn progress(&mut self, downloaded: u64, total: Option<u64>) -> Option<DownloadProgress> {
+        let now = Instant::now();
+        if self
+            .last_event
+            .is_some_and(|last| now - last < PROGRESS_INTERVAL)
+        {
+            return None;
+        }
+        self.last_event = Some(now);
+
+        let elapsed = (now - self.started).as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            downloaded.saturating_sub(self.resumed) as f64 / elapsed
+        } else {
+            0.0
+        };
+        let eta = match total {
+            Some(total) if rate > 0.0 => Some(Duration::from_secs_f64(
+                total.saturating_sub(downloaded) as f64 / rate,
+            )),
+            _ => None,
+        };
 
-    // Create temporary file
-    let temp_path = model_path.with_extension("tmp");
-    let mut file = File::create(&temp_path)
+        Some(DownloadProgress {
+            rate,
+            eta,
+            ..DownloadProgress::new(DownloadPhase::Downloading, downloaded, total)
+        })
+    }
+}
+
+/// Download `url` into `temp_path`, continuing the partial file found there
+async fn fetch(
+    client: &Client,
+    url: &str,
+    temp_path: &Path,
+    label: &str,
+    monitor: &DownloadMonitor,
+) -> Result<()> {
+    let resume_from = tokio::fs::metadata(temp_path)
         .await
-        .context("Failed to create temporary file")?;
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={}-", resume_from));
+    }
+    let response = tokio::select! {
+        response = request.send() => response?,
+        _ = monitor.cancel.cancelled() => anyhow::bail!("Download cancelled"),
+    };
+
+    // Nothing left past the end of the partial file: the checksum tells
+    // whether it is complete
+    if resume_from > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        info!("{} already fully downloaded", label);
+        return Ok(());
+    }
+    let response = response.error_for_status()?;
+
+    let (mut file, start) = if response.status() == StatusCode::PARTIAL_CONTENT {
+        let range_start = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_start);
+        anyhow::ensure!(
+            range_start == Some(resume_from),
+            "Server sent a partial response not starting at byte {}",
+            resume_from
+        );
+        info!("Resuming download at {} bytes", resume_from);
+        let file = OpenOptions::new()
+            .append(true)
+            .open(temp_path)
+            .await
+            .context("Failed to open temporary file")?;
+        (file, resume_from)
+    } else {
+        if resume_from > 0 {
+            info!("Server does not support resuming, restarting download");
+        }
+        let file = File::create(temp_path)
+            .await
+            .context("Failed to create temporary file")?;
+        (file, 0)
+    };
+
+    let total_size = response.content_length().map(|length| start + length);
+    let mut tracker = RateTracker::new(start);
+    if let Some(progress) = tracker.progress(start, total_size) {
+        monitor.publish(progress);
+    }
 
     // Download with progress
-    let mut downloaded = 0u64;
-    let mut hasher = Sha256::new();
+    let mut downloaded = start;
     let mut stream = response.bytes_stream();
 
     use futures_util::StreamExt;
-    while let Some(chunk) = stream.next().await {
+    loop {
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            _ = monitor.cancel.cancelled() => {
+                // Keep what was received to resume later
+                file.flush().await?;
+                anyhow::bail!("Download cancelled");
+            }
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
         let chunk = chunk?;
         file.write_all(&chunk).await?;
-        hasher.update(&chunk);
         downloaded += chunk.len() as u64;
-        pb.set_position(downloaded);
+        if let Some(progress) = tracker.progress(downloaded, total_size) {
+            monitor.publish(progress);
+        }
     }
 
     file.flush().await?;
     drop(file);
 
-    pb.finish_with_message(format!("{} model downloaded successfully", model));
-
-    // Calculate checksum
-    let hash = hasher.finalize();
-    let checksum = format!("{:x}", hash);
-    info!("SHA256: {}", checksum);
+    if let Some(total_size) = total_size {
+        anyhow::ensure!(
+            downloaded == total_size,
+            "Connection closed after {} of {} bytes",
+            downloaded,
+            total_size
+        );
+    }
 
-    // Rename temp file to final name
-    tokio::fs::rename(&temp_path, &model_path)
-        .await
-        .context("Failed to rename downloaded file")?;
+    info!("{} downloaded successfully", label);
+    Ok(())
+}
 
-    info!("Model saved to: {:?}", model_path);
+/// First byte of a `Content-Range: bytes <first>-<last>/<length>` header
+fn content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
 
-    Ok(model_path)
+/// SHA256 of a file, as lowercase hex
+async fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
 /// Verify model file integrity
-#[allow(dead_code)]
-pub async fn verify_model(model: ModelSize) -> Result<bool> {
-    let model_path = get_model_path(model)?;
+///
+/// Checks the file size of the registry entry, then its SHA256. Models
+/// without a known size or checksum are only checked for what they have.
+pub async fn verify_model(model: &ModelInfo) -> Result<bool> {
+    verify_file(model, &get_model_path(model)?).await
+}
 
+async fn verify_file(model: &ModelInfo, model_path: &Path) -> Result<bool> {
     if !model_path.exists() {
         return Ok(false);
     }
 
-    // Check file size
-    let metadata = tokio::fs::metadata(&model_path).await?;
-    let actual_size = metadata.len();
-    let expected_size = model.file_size();
-
-    // Allow 10% variance in file size
-    let size_diff = (actual_size as i64 - expected_size as i64).abs();
-    let size_tolerance = (expected_size as f64 * 0.1) as i64;
-
-    if size_diff > size_tolerance {
+    let actual_size = tokio::fs::metadata(model_path).await?.len();
+    if model.size > 0 && actual_size != model.size {
         warn!(
-            "Model {} size mismatch: expected ~{} bytes, got {} bytes",
-            model, expected_size, actual_size
+            "Model {} size mismatch: expected {} bytes, got {} bytes",
+            model, model.size, actual_size
         );
         return Ok(false);
     }
 
+    if let Some(expected) = &model.sha256 {
+        let checksum = file_sha256(model_path).await?;
+        if !checksum.eq_ignore_ascii_case(expected) {
+            warn!(
+                "Model {} checksum mismatch: expected {}, got {}",
+                model, expected, checksum
+            );
+            return Ok(false);
+        }
+    }
+
     Ok(true)
 }
 
 /// Delete a downloaded model
-#[allow(dead_code)]
-pub async fn delete_model(model: ModelSize) -> Result<()> {
+pub async fn delete_model(model: &ModelInfo) -> Result<()> {
     let model_path = get_model_path(model)?;
 
     if model_path.exists() {
@@ -132,33 +494,620 @@ pub async fn delete_model(model: ModelSize) -> Result<()> {
     Ok(())
 }
 
-/// List all downloaded models
-#[allow(dead_code)]
-pub async fn list_downloaded_models() -> Result<Vec<ModelSize>> {
+/// List the models of `registry` that are downloaded
+pub async fn list_downloaded_models(registry: &ModelRegistry) -> Result<Vec<ModelInfo>> {
     let mut models = Vec::new();
 
-    for model in &[
-        ModelSize::Tiny,
-        ModelSize::Base,
-        ModelSize::Small,
-        ModelSize::Medium,
-    ] {
-        let model_path = get_model_path(*model)?;
+    for model in registry.all() {
+        let model_path = get_model_path(model)?;
         if model_path.exists() {
-            models.push(*model);
+            models.push(model.clone());
         }
     }
 
     Ok(models)
 }
 
+/// Magic number starting ggml model files ("ggml" as a little-endian u32)
+const GGML_MAGIC: [u8; 4] = *b"lmgg";
+
+/// Copy a ggml model file into the models directory
+///
+/// `name` defaults to the file name without its `ggml-` prefix and
+/// extension. A file imported under the name of a registry model must match
+/// its checksum and takes that model's file name; any other file is
+/// described from its name and contents, for the caller to add to the
+/// registry.
+pub async fn import_model(
+    source: &Path,
+    name: Option<&str>,
+    registry: &ModelRegistry,
+) -> Result<ModelInfo> {
+    import_model_into(source, name, registry, &get_models_dir()?).await
+}
+
+async fn import_model_into(
+    source: &Path,
+    name: Option<&str>,
+    registry: &ModelRegistry,
+    models_dir: &Path,
+) -> Result<ModelInfo> {
+    let mut magic = [0u8; 4];
+    File::open(source)
+        .await
+        .with_context(|| format!("Failed to open {:?}", source))?
+        .read_exact(&mut magic)
+        .await
+        .ok();
+    anyhow::ensure!(
+        magic == GGML_MAGIC,
+        "{:?} is not a ggml Whisper model",
+        source
+    );
+
+    let file = source
+        .file_name()
+        .and_then(|file| file.to_str())
+        .with_context(|| format!("Invalid model file name {:?}", source))?;
+    let name = name
+        .map(str::to_string)
+        .unwrap_or_else(|| default_model_name(file));
+    let size = tokio::fs::metadata(source).await?.len();
+    let checksum = file_sha256(source).await?;
+
+    let model = match registry.get(&name) {
+        Some(known) => {
+            anyhow::ensure!(
+                known.size == 0 || size == known.size,
+                "{:?} is not the {} model: it is {} bytes, expected {}",
+                source,
+                known,
+                size,
+                known.size
+            );
+            if let Some(expected) = &known.sha256 {
+                anyhow::ensure!(
+                    checksum.eq_ignore_ascii_case(expected),
+                    "{:?} is not the {} model: its SHA256 is {}, expected {}",
+                    source,
+                    known,
+                    checksum,
+                    expected
+                );
+            }
+            known.clone()
+        }
+        None => {
+            if let Some(other) = registry.all().iter().find(|model| model.file == file) {
+                anyhow::bail!(
+                    "{} is the file of the {} model; import it under that name or rename it",
+                    file,
+                    other
+                );
+            }
+            let mut model = ModelInfo::named(&name, file);
+            model.size = size;
+            model.sha256 = Some(checksum);
+            // whisper.cpp needs about 1.3 times the model size, plus its
+            // buffers
+            model.ram_mb = size / 1_000_000 * 13 / 10 + 200;
+            model
+        }
+    };
+
+    let destination = models_dir.join(&model.file);
+    let same_file = match (source.canonicalize(), destination.canonicalize()) {
+        (Ok(source), Ok(destination)) => source == destination,
+        _ => false,
+    };
+    if !same_file {
+        let temp_path = destination.with_extension("tmp");
+        tokio::fs::copy(source, &temp_path)
+            .await
+            .with_context(|| format!("Failed to copy {:?}", source))?;
+        tokio::fs::rename(&temp_path, &destination)
+            .await
+            .context("Failed to rename imported file")?;
+    }
+
+    info!("Imported {} model to {:?}", model, destination);
+    Ok(model)
+}
+
+/// Model name of a whisper.cpp file: "ggml-small.en-q5_1.bin" is
+/// "small.en-q5_1"
+fn default_model_name(file: &str) -> String {
+    let stem = file.strip_suffix(".bin").unwrap_or(file);
+    stem.strip_prefix("ggml-").unwrap_or(stem).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    /// HTTP server standing in for HuggingFace
+    ///
+    /// Serves `body` at `/model.bin` (404 elsewhere), honouring
+    /// `Range: bytes=N-` when `ranges` is set, and records the range
+    /// requested by each request ("" without one).
+    fn serve(body: Vec<u8>, ranges: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+        serve_with(body, ranges, None)
+    }
+
+    /// Like [`serve`], but hangs after sending `stall_after` bytes of the
+    /// body
+    fn serve_with(
+        body: Vec<u8>,
+        ranges: bool,
+        stall_after: Option<usize>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                respond(stream, &body, ranges, stall_after, &recorded);
+            }
+        });
+        (url, requests)
+    }
+
+    fn respond(
+        mut stream: TcpStream,
+        body: &[u8],
+        ranges: bool,
+        stall_after: Option<usize>,
+        requests: &Mutex<Vec<String>>,
+    ) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut range = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = value.trim().to_string();
+                }
+            }
+        }
+        requests.lock().unwrap().push(range.clone());
+
+        let (status, content) = if request_line.split_whitespace().nth(1) != Some("/model.bin") {
+            ("404 Not Found".to_string(), &b""[..])
+        } else {
+            match range
+                .strip_prefix("bytes=")
+                .and_then(|r| r.strip_suffix('-'))
+            {
+                Some(start) if ranges => {
+                    let start: usize = start.parse().unwrap();
+                    if start >= body.len() {
+                        ("416 Range Not Satisfiable".to_string(), &b""[..])
+                    } else {
+                        let status = format!(
+                            "206 Partial Content\r\nContent-Range: bytes {}-{}/{}",
+                            start,
+                            body.len() - 1,
+                            body.len()
+                        );
+                        (status, &body[start..])
+                    }
+                }
+                _ => ("200 OK".to_string(), body),
+            }
+        };
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        match stall_after {
+            Some(sent) => {
+                stream.write_all(&content[..sent]).unwrap();
+                stream.flush().unwrap();
+                std::thread::sleep(std::time::Duration::from_secs(30));
+            }
+            None => stream.write_all(content).unwrap(),
+        }
+    }
+
+    fn body() -> Vec<u8> {
+        (0..100_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn sha256(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    /// Destination path in a fresh temporary directory
+    fn destination(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("voxai-download-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("ggml-test.bin")
+    }
+
+    #[tokio::test]
+    async fn test_download_verifies_checksum() {
+        let body = body();
+        let (url, requests) = serve(body.clone(), true);
+        let path = destination("verified");
+
+        let urls = vec![format!("{}/model.bin", url)];
+        download_file(
+            &urls,
+            &path,
+            Some(&sha256(&body)),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!path.with_extension("tmp").exists());
+        assert_eq!(*requests.lock().unwrap(), vec![String::new()]);
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file() {
+        let body = body();
+        let (url, requests) = serve(body.clone(), true);
+        let path = destination("resume");
+        std::fs::write(path.with_extension("tmp"), &body[..40_000]).unwrap();
+
+        let urls = vec![format!("{}/model.bin", url)];
+        download_file(
+            &urls,
+            &path,
+            Some(&sha256(&body)),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert_eq!(*requests.lock().unwrap(), vec!["bytes=40000-".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_file_is_kept() {
+        let body = body();
+        let (url, _) = serve(body.clone(), true);
+        let path = destination("complete");
+        std::fs::write(path.with_extension("tmp"), &body).unwrap();
+
+        let urls = vec![format!("{}/model.bin", url)];
+        download_file(
+            &urls,
+            &path,
+            Some(&sha256(&body)),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_without_range_support() {
+        let body = body();
+        let (url, _) = serve(body.clone(), false);
+        let path = destination("restart");
+        std::fs::write(path.with_extension("tmp"), b"stale partial data").unwrap();
+
+        let urls = vec![format!("{}/model.bin", url)];
+        download_file(
+            &urls,
+            &path,
+            Some(&sha256(&body)),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_download_is_deleted() {
+        let (url, _) = serve(body(), true);
+        let path = destination("corrupt");
+
+        let urls = vec![format!("{}/model.bin", url)];
+        let result = download_file(
+            &urls,
+            &path,
+            Some(&sha256(b"other")),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Checksum mismatch"));
+        assert!(!path.exists());
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_falls_back_to_mirrors() {
+        let body = body();
+        let (url, requests) = serve(body.clone(), true);
+        let path = destination("mirrors");
+
+        // Nothing listens on a port that was just released
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = format!("http://{}/model.bin", closed.local_addr().unwrap());
+        drop(closed);
+
+        let urls = vec![
+            unreachable,
+            format!("{}/missing.bin", url),
+            format!("{}/model.bin", url),
+        ];
+        download_file(
+            &urls,
+            &path,
+            Some(&sha256(&body)),
+            "test model",
+            &DownloadMonitor::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_download_publishes_progress() {
+        let body = body();
+        let (url, _) = serve(body.clone(), true);
+        let path = destination("progress");
+
+        let (monitor, mut events) = DownloadMonitor::channel();
+        let urls = vec![format!("{}/model.bin", url)];
+        download_file(&urls, &path, Some(&sha256(&body)), "test model", &monitor)
+            .await
+            .unwrap();
+        drop(monitor);
+
+        let mut received = Vec::new();
+        while let Some(progress) = events.recv().await {
+            received.push(progress);
+        }
+        let phases: Vec<_> = received.iter().map(|p| p.phase.clone()).collect();
+        assert_eq!(phases[0], DownloadPhase::Downloading);
+        assert_eq!(
+            phases[phases.len() - 2..],
+            [DownloadPhase::Verifying, DownloadPhase::Done]
+        );
+        assert!(received
+            .windows(2)
+            .all(|w| w[0].downloaded <= w[1].downloaded));
+
+        let done = received.last().unwrap();
+        assert!(done.is_finished());
+        assert_eq!(done.downloaded, body.len() as u64);
+        assert_eq!(done.fraction(), Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_download_keeps_partial_file() {
+        let body = body();
+        let (url, _) = serve_with(body.clone(), true, Some(30_000));
+        let path = destination("cancel");
+
+        let (monitor, mut events) = DownloadMonitor::channel();
+        let cancel = monitor.cancel_token();
+        let urls = vec![format!("{}/model.bin", url)];
+        let checksum = sha256(&body);
+        let download = tokio::spawn({
+            let path = path.clone();
+            let monitor = monitor.clone();
+            async move { download_file(&urls, &path, Some(&checksum), "test model", &monitor).await }
+        });
+        drop(monitor);
+
+        let mut last = None;
+        while let Some(progress) = events.recv().await {
+            // The server hangs after sending part of the body
+            cancel.cancel();
+            last = Some(progress);
+        }
+
+        assert!(download.await.unwrap().is_err());
+        assert_eq!(last.unwrap().phase, DownloadPhase::Cancelled);
+        assert!(!path.exists());
+        let partial = std::fs::read(path.with_extension("tmp")).unwrap();
+        assert_eq!(partial, &body[..partial.len()]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_file() {
+        let body = body();
+        let path = destination("verify");
+        let mut model = ModelInfo::named("test", "ggml-test.bin");
+        assert!(!verify_file(&model, &path).await.unwrap());
+
+        std::fs::write(&path, &body).unwrap();
+        assert!(verify_file(&model, &path).await.unwrap());
+
+        // The size is checked without a checksum
+        model.size = body.len() as u64 + 1;
+        assert!(!verify_file(&model, &path).await.unwrap());
+        model.size = body.len() as u64;
+        assert!(verify_file(&model, &path).await.unwrap());
+
+        model.sha256 = Some(sha256(b"other"));
+        assert!(!verify_file(&model, &path).await.unwrap());
+        model.sha256 = Some(sha256(&body).to_uppercase());
+        assert!(verify_file(&model, &path).await.unwrap());
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        let progress = DownloadProgress::new(DownloadPhase::Downloading, 250, Some(1000));
+        assert_eq!(progress.fraction(), Some(0.25));
+        assert!(!progress.is_finished());
+        assert_eq!(
+            DownloadProgress::new(DownloadPhase::Downloading, 250, None).fraction(),
+            None
+        );
+        assert!(DownloadProgress::new(DownloadPhase::Cancelled, 0, None).is_finished());
+    }
+
+    #[test]
+    fn test_model_urls() {
+        let config = ModelsConfig {
+            base_url: "http://models.internal/whisper/".to_string(),
+            mirrors: vec!["https://mirror.example.org".to_string()],
+            ..Default::default()
+        };
+        let registry = ModelRegistry::builtin();
+        assert_eq!(
+            model_urls(registry.get("tiny").unwrap(), &config),
+            vec![
+                "http://models.internal/whisper/ggml-tiny.bin".to_string(),
+                "https://mirror.example.org/ggml-tiny.bin".to_string(),
+            ]
+        );
+
+        // Models published elsewhere have their own URL
+        let distil = registry.get("distil-large-v3").unwrap();
+        assert_eq!(model_urls(distil, &config).len(), 1);
+    }
+
+    /// ggml model file named `file` in a fresh temporary directory, and
+    /// the models directory next to it
+    fn ggml_file(test: &str, file: &str) -> (PathBuf, PathBuf) {
+        let dir = destination(test).parent().unwrap().to_path_buf();
+        let models_dir = dir.join("models");
+        std::fs::create_dir_all(&models_dir).unwrap();
+        let path = dir.join(file);
+        let mut data = GGML_MAGIC.to_vec();
+        data.extend(body());
+        std::fs::write(&path, data).unwrap();
+        (path, models_dir)
+    }
+
+    #[tokio::test]
+    async fn test_import_custom_model() {
+        let (path, models_dir) = ggml_file("import", "ggml-small.en-q5_1-custom.bin");
+        let registry = ModelRegistry::builtin();
+
+        let model = import_model_into(&path, None, &registry, &models_dir)
+            .await
+            .unwrap();
+        assert_eq!(model.name, "small.en-q5_1-custom");
+        assert_eq!(model.file, "ggml-small.en-q5_1-custom.bin");
+        assert_eq!(model.size, std::fs::metadata(&path).unwrap().len());
+        assert_eq!(
+            model.sha256.as_deref(),
+            Some(sha256(&std::fs::read(&path).unwrap()).as_str())
+        );
+        assert_eq!(model.languages, vec!["en".to_string()]);
+        assert!(models_dir.join(&model.file).exists());
+
+        let model = import_model_into(&path, Some("french"), &registry, &models_dir)
+            .await
+            .unwrap();
+        assert_eq!(model.name, "french");
+        assert!(model.is_multilingual());
+    }
+
+    #[tokio::test]
+    async fn test_import_checks_registry_models() {
+        let (path, models_dir) = ggml_file("import-known", "my-large.bin");
+        let registry = ModelRegistry::builtin();
+
+        // Known model without a checksum: takes the registry file name
+        let model = import_model_into(&path, Some("distil-large-v3"), &registry, &models_dir)
+            .await
+            .unwrap();
+        assert_eq!(model.file, "ggml-distil-large-v3.bin");
+        assert!(models_dir.join("ggml-distil-large-v3.bin").exists());
+
+        // Known model with a size and checksum: must match them
+        let result = import_model_into(&path, Some("base"), &registry, &models_dir).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("is not the base model"));
+        assert!(!models_dir.join("ggml-base.bin").exists());
+
+        let mut sized = ModelInfo::named("sized", "ggml-sized.bin");
+        sized.size = 1_000_000;
+        let mut hashed = ModelInfo::named("hashed", "ggml-hashed.bin");
+        hashed.sha256 = Some(sha256(b"another model"));
+        let registry = ModelRegistry::from_config(&ModelsConfig {
+            custom: vec![sized, hashed],
+            ..Default::default()
+        });
+
+        // Known model with only a size: must have that size
+        let result = import_model_into(&path, Some("sized"), &registry, &models_dir).await;
+        assert!(result.unwrap_err().to_string().contains("bytes"));
+        assert!(!models_dir.join("ggml-sized.bin").exists());
+
+        // Known model with only a checksum: must match it
+        let result = import_model_into(&path, Some("hashed"), &registry, &models_dir).await;
+        assert!(result.unwrap_err().to_string().contains("SHA256"));
+        assert!(!models_dir.join("ggml-hashed.bin").exists());
+
+        // Another name cannot take a registry model's file
+        let (path, models_dir) = ggml_file("import-clash", "ggml-tiny.bin");
+        let result = import_model_into(&path, Some("mine"), &registry, &models_dir).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_other_files() {
+        let (path, models_dir) = ggml_file("import-invalid", "notes.bin");
+        std::fs::write(&path, b"meeting notes").unwrap();
+        let result = import_model_into(&path, None, &ModelRegistry::builtin(), &models_dir).await;
+        assert!(result.unwrap_err().to_string().contains("not a ggml"));
+    }
+
+    #[test]
+    fn test_default_model_name() {
+        assert_eq!(
+            default_model_name("ggml-small.en-q5_1.bin"),
+            "small.en-q5_1"
+        );
+        assert_eq!(default_model_name("whisper-fr.bin"), "whisper-fr");
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 40000-99999/100000"), Some(40000));
+        assert_eq!(content_range_start("bytes */100000"), None);
+    }
 
     #[tokio::test]
     async fn test_list_models() {
-        let models = list_downloaded_models().await.unwrap();
+        let models = list_downloaded_models(&ModelRegistry::builtin())
+            .await
+            .unwrap();
         // Just ensure it doesn't crash
         println!("Downloaded models: {:?}", models);
     }
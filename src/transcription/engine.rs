@@ -0,0 +1,322 @@
+//! Transcription engine abstraction
+//!
+//! Decouples the pipeline from a concrete speech-to-text backend so that
+//! Whisper can be swapped for a deterministic mock in tests.
+
+use crate::config::Language;
+use anyhow::Result;
+use std::time::Duration;
+
+/// Sample rate expected by every engine (16 kHz mono)
+pub const ENGINE_SAMPLE_RATE: u32 = 16000;
+
+/// A decoded token with the model's probability for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    /// Token text (usually includes its leading space)
+    pub text: String,
+    /// Probability assigned by the decoder (0.0 - 1.0)
+    pub probability: f32,
+}
+
+impl Token {
+    /// Create a new token
+    pub fn new(text: impl Into<String>, probability: f32) -> Self {
+        Self {
+            text: text.into(),
+            probability,
+        }
+    }
+}
+
+/// A transcribed span of audio
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Transcribed text
+    pub text: String,
+    /// Start offset, relative to the first sample passed to the engine
+    pub start: Duration,
+    /// End offset, relative to the first sample passed to the engine
+    pub end: Duration,
+    /// Decoded tokens (empty if the engine does not report them)
+    pub tokens: Vec<Token>,
+}
+
+impl Segment {
+    /// Create a new segment without token information
+    pub fn new(text: impl Into<String>, start: Duration, end: Duration) -> Self {
+        Self {
+            text: text.into(),
+            start,
+            end,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Attach decoded tokens to the segment
+    pub fn with_tokens(mut self, tokens: Vec<Token>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// Mean token probability, or `None` if no tokens were reported
+    pub fn confidence(&self) -> Option<f32> {
+        if self.tokens.is_empty() {
+            return None;
+        }
+
+        let sum: f32 = self.tokens.iter().map(|t| t.probability).sum();
+        Some(sum / self.tokens.len() as f32)
+    }
+
+    /// Shift the segment by a fixed offset
+    pub fn offset_by(mut self, offset: Duration) -> Self {
+        self.start += offset;
+        self.end += offset;
+        self
+    }
+}
+
+/// Output of a single engine call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    /// Segments in chronological order
+    pub segments: Vec<Segment>,
+    /// ISO 639-1 code of the spoken language, if the engine detected it
+    pub language: Option<String>,
+}
+
+impl Transcript {
+    /// Create a transcript from segments, without language information
+    pub fn new(segments: Vec<Segment>) -> Self {
+        Self {
+            segments,
+            language: None,
+        }
+    }
+
+    /// Set the detected language
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
+/// Mean probability over every token in the segments
+///
+/// Returns 0.0 when no segment reports tokens, since there is no evidence
+/// that the transcript is reliable.
+pub fn aggregate_confidence(segments: &[Segment]) -> f32 {
+    let (sum, count) = segments
+        .iter()
+        .flat_map(|segment| segment.tokens.iter())
+        .fold((0.0f32, 0usize), |(sum, count), token| {
+            (sum + token.probability, count + 1)
+        });
+
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Speech-to-text backend used by the transcription pipeline
+///
+/// Implementations are called from a blocking worker thread, so they may
+/// perform CPU-heavy work synchronously.
+pub trait TranscriptionEngine: Send + Sync {
+    /// Transcribe 16kHz mono f32 samples
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Audio samples in range [-1.0, 1.0]
+    /// * `language` - Spoken language, or `Language::Auto` to detect it
+    /// * `prompt` - Optional text used to condition the decoder (e.g. the
+    ///   previous chunk's transcript)
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        language: &Language,
+        prompt: Option<&str>,
+    ) -> Result<Transcript>;
+
+    /// Human-readable engine name, used for logging
+    fn name(&self) -> &str;
+}
+
+/// Convert a sample count at 16kHz into a duration
+pub fn samples_to_duration(samples: usize) -> Duration {
+    Duration::from_secs_f64(samples as f64 / ENGINE_SAMPLE_RATE as f64)
+}
+
+/// Custom response function for [`MockEngine`]
+pub type MockResponder = Box<dyn Fn(&[f32]) -> Vec<Segment> + Send + Sync>;
+
+/// Deterministic engine for tests
+///
+/// By default it returns a single segment describing the audio length, with
+/// one fully-confident token per word. A custom responder can be supplied to
+/// script specific transcripts. The requested language is echoed back as the
+/// detected language unless it is `Language::Auto`.
+pub struct MockEngine {
+    delay: Duration,
+    responder: Option<MockResponder>,
+}
+
+impl MockEngine {
+    /// Create a mock engine with no artificial latency
+    pub fn new() -> Self {
+        Self {
+            delay: Duration::ZERO,
+            responder: None,
+        }
+    }
+
+    /// Simulate transcription latency
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Use a custom function to produce segments
+    pub fn with_responder<F>(mut self, responder: F) -> Self
+    where
+        F: Fn(&[f32]) -> Vec<Segment> + Send + Sync + 'static,
+    {
+        self.responder = Some(Box::new(responder));
+        self
+    }
+}
+
+impl Default for MockEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptionEngine for MockEngine {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        language: &Language,
+        _prompt: Option<&str>,
+    ) -> Result<Transcript> {
+        if !self.delay.is_zero() {
+            std::thread::sleep(self.delay);
+        }
+
+        let segments = match &self.responder {
+            Some(responder) => responder(samples),
+            None => {
+                let duration = samples_to_duration(samples.len());
+                let text = format!("[Mock] {:.1}s audio", duration.as_secs_f32());
+                let tokens = text
+                    .split_whitespace()
+                    .map(|word| Token::new(format!(" {}", word), 1.0))
+                    .collect();
+                vec![Segment::new(text, Duration::ZERO, duration).with_tokens(tokens)]
+            }
+        };
+
+        let transcript = Transcript::new(segments);
+        Ok(match language {
+            Language::Auto => transcript,
+            language => transcript.with_language(language.to_code()),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_to_duration() {
+        assert_eq!(samples_to_duration(16000), Duration::from_secs(1));
+        assert_eq!(samples_to_duration(8000), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_mock_engine_default_output() {
+        let engine = MockEngine::new();
+        let transcript = engine
+            .transcribe(&vec![0.0; 16000], &Language::Auto, None)
+            .unwrap();
+        let segments = transcript.segments;
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "[Mock] 1.0s audio");
+        assert_eq!(segments[0].start, Duration::ZERO);
+        assert_eq!(segments[0].end, Duration::from_secs(1));
+        assert_eq!(segments[0].tokens.len(), 3);
+        assert_eq!(segments[0].confidence(), Some(1.0));
+        assert_eq!(transcript.language, None);
+    }
+
+    #[test]
+    fn test_mock_engine_is_deterministic() {
+        let engine = MockEngine::new();
+        let samples = vec![0.1; 3200];
+
+        let first = engine
+            .transcribe(&samples, &Language::English, None)
+            .unwrap();
+        let second = engine
+            .transcribe(&samples, &Language::English, None)
+            .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_mock_engine_responder() {
+        let engine = MockEngine::new().with_responder(|samples| {
+            vec![Segment::new(
+                format!("{} samples", samples.len()),
+                Duration::ZERO,
+                samples_to_duration(samples.len()),
+            )]
+        });
+
+        let transcript = engine
+            .transcribe(&[0.0; 42], &Language::French, Some("prompt"))
+            .unwrap();
+        assert_eq!(transcript.segments[0].text, "42 samples");
+        assert_eq!(transcript.language.as_deref(), Some("fr"));
+        assert_eq!(engine.name(), "mock");
+    }
+
+    #[test]
+    fn test_segment_confidence() {
+        let segment = Segment::new("", Duration::ZERO, Duration::ZERO);
+        assert_eq!(segment.confidence(), None);
+
+        let segment = segment.with_tokens(vec![Token::new(" a", 0.5), Token::new(" b", 1.0)]);
+        assert_eq!(segment.confidence(), Some(0.75));
+    }
+
+    #[test]
+    fn test_aggregate_confidence() {
+        assert_eq!(aggregate_confidence(&[]), 0.0);
+
+        let segments = vec![
+            Segment::new("a", Duration::ZERO, Duration::from_secs(1))
+                .with_tokens(vec![Token::new(" a", 0.2)]),
+            Segment::new("b c", Duration::from_secs(1), Duration::from_secs(2))
+                .with_tokens(vec![Token::new(" b", 0.8), Token::new(" c", 0.5)]),
+        ];
+        assert!((aggregate_confidence(&segments) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_segment_offset() {
+        let segment = Segment::new("x", Duration::from_secs(1), Duration::from_secs(2))
+            .offset_by(Duration::from_secs(10));
+        assert_eq!(segment.start, Duration::from_secs(11));
+        assert_eq!(segment.end, Duration::from_secs(12));
+    }
+}
@@ -1,14 +1,23 @@
 //! Transcription module using Whisper.cpp
 //!
 //! This module handles:
+//! - Pluggable transcription engines (Whisper, mock)
 //! - Whisper context management
 //! - Model downloading and verification
+//! - Benchmark of the models on this machine
 //! - Transcription pipeline
-//! - Audio chunking
+//! - In-order delivery of concurrently transcribed chunks
+//! - Stitching of overlapping chunk transcripts
+//! - Audio chunking (fixed windows or VAD-driven utterances)
 
+pub mod bench;
 pub mod downloader;
+pub mod engine;
 pub mod models;
 pub mod pipeline;
+pub mod reorder;
+pub mod segmenter;
+pub mod stitcher;
 pub mod whisper;
 
 // Future modules (to be implemented)
@@ -16,5 +25,5 @@ pub mod whisper;
 
 // Re-exports for convenience (commented out until used to avoid warnings)
 // pub use downloader::{delete_model, download_model, list_downloaded_models, verify_model};
-// pub use models::{get_model_path, get_models_dir, is_model_downloaded, ModelSize};
+// pub use models::{get_model_path, get_models_dir, is_model_downloaded, ModelRegistry};
 // pub use whisper::WhisperContext;
@@ -1,122 +1,273 @@
 //! Whisper model management
 //!
-//! Handles different Whisper model sizes and their specifications
-
-use serde::{Deserialize, Serialize};
+//! The model registry lists the known Whisper models: the built-in
+//! whisper.cpp models (multilingual, English-only `.en` and quantized
+//! variants) and the entries added in the `models.custom` section of the
+//! config file. The `auto` model stands for the model selected for this
+//! machine.
+
+use super::bench::{select_model, BenchmarkCache, Machine};
+use crate::config::{Config, ModelInfo, ModelsConfig};
 use std::path::PathBuf;
-use sysinfo::System;
-
-/// Available Whisper model sizes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ModelSize {
-    /// Tiny model (~75 MB) - fastest, least accurate
-    Tiny,
-    /// Base model (~142 MB) - good balance
-    Base,
-    /// Small model (~466 MB) - better accuracy
-    Small,
-    /// Medium model (~1.5 GB) - high accuracy, requires GPU
-    Medium,
+
+/// Size and SHA256 of the model files published in the
+/// `ggerganov/whisper.cpp` HuggingFace repository
+const MANIFEST: &[(&str, u64, &str)] = &[
+    (
+        "ggml-tiny.bin",
+        77_691_713,
+        "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21",
+    ),
+    (
+        "ggml-base.bin",
+        147_951_465,
+        "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe",
+    ),
+    (
+        "ggml-small.bin",
+        487_601_967,
+        "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b",
+    ),
+    (
+        "ggml-medium.bin",
+        1_533_763_059,
+        "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208",
+    ),
+    (
+        "ggml-tiny.en.bin",
+        77_704_715,
+        "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
+    ),
+    (
+        "ggml-tiny-q5_1.bin",
+        32_152_673,
+        "818710568da3ca15689e31a743197b520007872ff9576237bda97bd1b469c3d7",
+    ),
+    (
+        "ggml-base.en.bin",
+        147_964_211,
+        "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
+    ),
+    (
+        "ggml-base-q5_1.bin",
+        59_707_625,
+        "422f1ae452ade6f30a004d7e5c6a43195e4433bc370bf23fac9cc591f01a8898",
+    ),
+    (
+        "ggml-small.en.bin",
+        487_614_201,
+        "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
+    ),
+    (
+        "ggml-small-q5_1.bin",
+        190_085_487,
+        "ae85e4a935d7a567bd102fe55afc16bb595bdb618e11b2fc7591bc08120411bb",
+    ),
+    (
+        "ggml-medium.en.bin",
+        1_533_774_781,
+        "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356",
+    ),
+    (
+        "ggml-medium-q5_0.bin",
+        539_212_467,
+        "19fea4b380c3a618ec4723c3eef2eb785ffba0d0538cf43f8f235e7b3b34220f",
+    ),
+    (
+        "ggml-large-v3.bin",
+        3_095_033_483,
+        "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
+    ),
+    (
+        "ggml-large-v3-q5_0.bin",
+        1_081_140_203,
+        "d75795ecff3f83b5faa89d1900604ad8c780abd5739fae406de19f23ecd98ad1",
+    ),
+    (
+        "ggml-large-v3-turbo.bin",
+        1_624_555_275,
+        "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69",
+    ),
+    (
+        "ggml-large-v3-turbo-q5_0.bin",
+        574_041_195,
+        "394221709cd5ad1f40c46e6031ca61bce88931e6e088c188294c6d5a55ffa7e2",
+    ),
+];
+
+/// Built-in models: name, parameters in millions, RAM requirement in MB,
+/// speed multiplier (times real time on an average CPU)
+const BUILTIN_MODELS: &[(&str, u64, u64, f32)] = &[
+    ("tiny", 39, 1024, 10.0),
+    ("tiny.en", 39, 1024, 10.0),
+    ("tiny-q5_1", 39, 512, 11.0),
+    ("base", 74, 1024, 7.0),
+    ("base.en", 74, 1024, 7.0),
+    ("base-q5_1", 74, 512, 8.0),
+    ("small", 244, 2048, 4.0),
+    ("small.en", 244, 2048, 4.0),
+    ("small-q5_1", 244, 1024, 4.5),
+    ("medium", 769, 5120, 2.0),
+    ("medium.en", 769, 5120, 2.0),
+    ("medium-q5_0", 769, 2048, 2.3),
+    ("large-v3", 1550, 10240, 1.0),
+    ("large-v3-q5_0", 1550, 4096, 1.2),
+    ("large-v3-turbo", 809, 6144, 3.0),
+    ("large-v3-turbo-q5_0", 809, 2048, 3.5),
+    ("distil-large-v3", 756, 6144, 3.0),
+];
+
+/// `transcription.model` value selecting the model automatically
+pub const AUTO_MODEL: &str = "auto";
+
+/// Distilled models are published outside the whisper.cpp repository, with
+/// no size or checksum in the manifest
+const DISTIL_LARGE_V3_URL: &str =
+    "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/main/ggml-distil-large-v3.bin";
+
+/// Known Whisper models
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    models: Vec<ModelInfo>,
 }
 
-impl ModelSize {
-    /// Get the filename for this model
-    pub fn filename(&self) -> &'static str {
-        match self {
-            Self::Tiny => "ggml-tiny.bin",
-            Self::Base => "ggml-base.bin",
-            Self::Small => "ggml-small.bin",
-            Self::Medium => "ggml-medium.bin",
-        }
+impl ModelRegistry {
+    /// The built-in whisper.cpp models
+    pub fn builtin() -> Self {
+        let models = BUILTIN_MODELS
+            .iter()
+            .map(|&(name, parameters_m, ram_mb, speed)| {
+                let mut model = ModelInfo::named(name, &format!("ggml-{}.bin", name));
+                model.parameters_m = parameters_m;
+                model.ram_mb = ram_mb;
+                model.speed_multiplier = Some(speed);
+                if let Some(&(_, size, sha256)) =
+                    MANIFEST.iter().find(|entry| entry.0 == model.file)
+                {
+                    model.size = size;
+                    model.sha256 = Some(sha256.to_string());
+                }
+                if name == "distil-large-v3" {
+                    model.url = Some(DISTIL_LARGE_V3_URL.to_string());
+                }
+                model
+            })
+            .collect();
+        Self { models }
     }
 
-    /// Get the expected file size in bytes (approximate)
-    pub fn file_size(&self) -> u64 {
-        match self {
-            Self::Tiny => 75_852_960,      // ~75 MB
-            Self::Base => 141_925_216,     // ~142 MB
-            Self::Small => 466_179_104,    // ~466 MB
-            Self::Medium => 1_533_628_064, // ~1.5 GB
+    /// The built-in models and the custom ones from the config file
+    pub fn from_config(config: &ModelsConfig) -> Self {
+        let mut registry = Self::builtin();
+        for custom in &config.custom {
+            match registry
+                .models
+                .iter_mut()
+                .find(|model| model.name.eq_ignore_ascii_case(&custom.name))
+            {
+                Some(model) => *model = custom.clone(),
+                None => registry.models.push(custom.clone()),
+            }
         }
+        registry
     }
 
-    /// Get the download URL for this model
-    pub fn download_url(&self) -> String {
-        format!(
-            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}",
-            self.filename()
-        )
+    /// All models, built-in ones first
+    pub fn all(&self) -> &[ModelInfo] {
+        &self.models
     }
 
-    /// Get the human-readable name
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            Self::Tiny => "Tiny",
-            Self::Base => "Base",
-            Self::Small => "Small",
-            Self::Medium => "Medium",
-        }
+    /// Model named `name` (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&ModelInfo> {
+        self.models
+            .iter()
+            .find(|model| model.name.eq_ignore_ascii_case(name))
     }
 
-    /// Get the expected SHA256 checksum for this model
-    /// Note: These are placeholder checksums. Real checksums should be obtained from
-    /// the official Whisper.cpp repository or HuggingFace.
-    pub fn expected_sha256(&self) -> Option<&'static str> {
-        // TODO: Add real SHA256 checksums from whisper.cpp repo
-        // For now, return None to skip verification
-        // In production, these should be verified checksums
-        None
+    /// Model named `name`, or an error listing the known models
+    pub fn resolve(&self, name: &str) -> anyhow::Result<&ModelInfo> {
+        self.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self
+                .models
+                .iter()
+                .map(|model| model.name.as_str())
+                .collect();
+            anyhow::anyhow!(
+                "Unknown model '{}'. Known models: {}",
+                name,
+                names.join(", ")
+            )
+        })
     }
+}
 
-    /// Get RAM requirement in GB
-    pub fn ram_requirement(&self) -> u64 {
-        match self {
-            Self::Tiny => 1,
-            Self::Base => 1,
-            Self::Small => 2,
-            Self::Medium => 5,
+impl ModelInfo {
+    /// Model stored in `file`, with the languages and quantization its
+    /// whisper.cpp name tells ("small.en-q5_1")
+    pub fn named(name: &str, file: &str) -> Self {
+        let quantization = name
+            .split('-')
+            .find(|part| is_quantization(part))
+            .map(str::to_string);
+        let languages = if name.ends_with(".en") || name.contains(".en-") {
+            vec!["en".to_string()]
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            name: name.to_string(),
+            file: file.to_string(),
+            size: 0,
+            sha256: None,
+            languages,
+            quantization,
+            url: None,
+            parameters_m: 0,
+            ram_mb: 0,
+            speed_multiplier: None,
         }
     }
 
-    /// Get expected speed multiplier (X times realtime on average CPU)
-    pub fn speed_multiplier(&self) -> f32 {
-        match self {
-            Self::Tiny => 10.0,
-            Self::Base => 7.0,
-            Self::Small => 4.0,
-            Self::Medium => 2.0,
-        }
+    /// Whether the model transcribes any language
+    pub fn is_multilingual(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    /// Whether the model transcribes `language` (a language code or "auto")
+    pub fn transcribes(&self, language: &str) -> bool {
+        self.is_multilingual() || self.languages.iter().any(|l| l == language)
+    }
+
+    /// Whether the model file can be downloaded again: it is a built-in
+    /// model file or has a URL of its own. Imported models only exist on
+    /// disk.
+    pub fn is_downloadable(&self) -> bool {
+        self.url.is_some()
+            || BUILTIN_MODELS
+                .iter()
+                .any(|&(name, ..)| self.file == format!("ggml-{}.bin", name))
     }
 
-    /// Get all available models
-    pub fn all() -> &'static [ModelSize] {
-        &[
-            ModelSize::Tiny,
-            ModelSize::Base,
-            ModelSize::Small,
-            ModelSize::Medium,
-        ]
+    /// Get the download URL for this model under `base_url`
+    pub fn download_url(&self, base_url: &str) -> String {
+        match &self.url {
+            Some(url) => url.clone(),
+            None => format!("{}/{}", base_url.trim_end_matches('/'), self.file),
+        }
     }
 }
 
-impl std::fmt::Display for ModelSize {
+impl std::fmt::Display for ModelInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.display_name())
+        write!(f, "{}", self.name)
     }
 }
 
-impl std::str::FromStr for ModelSize {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "tiny" => Ok(Self::Tiny),
-            "base" => Ok(Self::Base),
-            "small" => Ok(Self::Small),
-            "medium" => Ok(Self::Medium),
-            _ => Err(format!("Unknown model size: {}", s)),
-        }
-    }
+/// Quantization suffix of a ggml file name ("q5_0", "q8_0"...)
+fn is_quantization(suffix: &str) -> bool {
+    let mut chars = suffix.chars();
+    chars.next() == Some('q') && chars.next().is_some_and(|c| c.is_ascii_digit())
 }
 
 /// Get the default model directory path
@@ -135,56 +286,85 @@ pub fn get_models_dir() -> anyhow::Result<PathBuf> {
 }
 
 /// Get the full path for a specific model
-pub fn get_model_path(model: ModelSize) -> anyhow::Result<PathBuf> {
+pub fn get_model_path(model: &ModelInfo) -> anyhow::Result<PathBuf> {
     let models_dir = get_models_dir()?;
-    Ok(models_dir.join(model.filename()))
+    Ok(models_dir.join(&model.file))
 }
 
 /// Check if a model is already downloaded
-pub fn is_model_downloaded(model: ModelSize) -> anyhow::Result<bool> {
+pub fn is_model_downloaded(model: &ModelInfo) -> anyhow::Result<bool> {
     let model_path = get_model_path(model)?;
     Ok(model_path.exists())
 }
 
-/// Automatically select the best model based on system capabilities
-pub fn auto_select_model() -> ModelSize {
-    let mut sys = System::new_all();
-    sys.refresh_all();
+/// Whether `name` is the `auto` model
+pub fn is_auto_model(name: &str) -> bool {
+    name.eq_ignore_ascii_case(AUTO_MODEL)
+}
+
+/// Model named in `transcription.model`, or the one selected for this
+/// machine when it is `auto`
+pub fn configured_model(config: &Config) -> anyhow::Result<ModelInfo> {
+    if !is_auto_model(&config.transcription.model) {
+        return ModelRegistry::from_config(&config.models)
+            .resolve(&config.transcription.model)
+            .cloned();
+    }
 
-    let cpu_count = sys.cpus().len();
-    let total_ram_gb = sys.total_memory() / (1024 * 1024 * 1024);
+    let cache = BenchmarkCache::load().unwrap_or_else(|e| {
+        log::warn!("Ignoring benchmark results: {:#}", e);
+        BenchmarkCache::default()
+    });
+    let machine = Machine::detect(config.transcription.enable_gpu);
+    Ok(auto_select_model(config, &cache, &machine))
+}
+
+/// Automatically select the best model for `machine`
+///
+/// Picks the most accurate downloaded model whose real-time factor measured
+/// by `voxai bench` (the results of `cache`) fits
+/// `transcription.latency_budget`. Without benchmark results, the model is
+/// guessed from the CPU count and RAM.
+pub fn auto_select_model(config: &Config, cache: &BenchmarkCache, machine: &Machine) -> ModelInfo {
+    if let Some(model) = select_model(config, cache, machine) {
+        log::info!("Auto-selected model from benchmarks: {}", model);
+        return model;
+    }
 
+    let total_ram_gb = machine.memory_mb / 1024;
     log::info!(
-        "System capabilities: {} CPUs, {} GB RAM",
-        cpu_count,
-        total_ram_gb
+        "System capabilities: {} CPUs, {} GB RAM{}",
+        machine.cpus,
+        total_ram_gb,
+        if machine.gpu { ", GPU" } else { "" }
     );
 
-    // GPU detection (simple heuristic for now)
-    let has_gpu = detect_gpu_available();
-
     // Selection logic based on system capabilities
-    let selected_model = if has_gpu && total_ram_gb >= 8 {
+    let name = if machine.gpu && total_ram_gb >= 8 {
         // Powerful system with GPU - use Medium model
-        ModelSize::Medium
-    } else if cpu_count >= 8 && total_ram_gb >= 4 {
+        "medium"
+    } else if machine.cpus >= 8 && total_ram_gb >= 4 {
         // Powerful CPU, enough RAM - use Small model
-        ModelSize::Small
-    } else if cpu_count >= 4 && total_ram_gb >= 2 {
+        "small"
+    } else if machine.cpus >= 4 && total_ram_gb >= 2 {
         // Average system - use Base model
-        ModelSize::Base
+        "base"
     } else {
         // Low-end system - use Tiny model
-        ModelSize::Tiny
+        "tiny"
     };
+    let selected_model = ModelRegistry::builtin()
+        .get(name)
+        .cloned()
+        .expect("built-in model");
 
     log::info!("Auto-selected model: {}", selected_model);
     selected_model
 }
 
-/// Detect if GPU is available (simple heuristic)
-fn detect_gpu_available() -> bool {
-    // Check for CUDA (NVIDIA)
+/// Whether whisper.cpp can run on a GPU of this machine: an NVIDIA GPU in
+/// a CUDA build, or Apple Silicon
+pub(crate) fn detect_gpu_available() -> bool {
     #[cfg(feature = "cuda")]
     {
         if cuda_is_available() {
@@ -193,7 +373,6 @@ fn detect_gpu_available() -> bool {
         }
     }
 
-    // Check for Apple Silicon (Metal)
     #[cfg(target_os = "macos")]
     {
         if is_apple_silicon() {
@@ -202,7 +381,6 @@ fn detect_gpu_available() -> bool {
         }
     }
 
-    // TODO: Add OpenCL detection for AMD/Intel GPUs
     false
 }
 
@@ -223,12 +401,18 @@ fn is_apple_silicon() -> bool {
     false
 }
 
-/// Check if CUDA is available (placeholder)
+/// Check if an NVIDIA GPU and its driver are present
+///
+/// `nvidia-smi` ships with the driver on Linux and Windows and lists one
+/// line per GPU.
 #[cfg(feature = "cuda")]
 fn cuda_is_available() -> bool {
-    // TODO: Implement proper CUDA detection
-    // This would use cudart or similar to check for CUDA devices
-    false
+    std::process::Command::new("nvidia-smi")
+        .arg("-L")
+        .output()
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).contains("GPU")
+        })
 }
 
 #[cfg(test)]
@@ -236,18 +420,110 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_model_size_parsing() {
-        assert_eq!("base".parse::<ModelSize>().unwrap(), ModelSize::Base);
-        assert_eq!("TINY".parse::<ModelSize>().unwrap(), ModelSize::Tiny);
-        assert!("invalid".parse::<ModelSize>().is_err());
+    fn test_builtin_models() {
+        let registry = ModelRegistry::builtin();
+        let base = registry.get("BASE").unwrap();
+        assert_eq!(base.file, "ggml-base.bin");
+        assert!(base.is_multilingual());
+        assert_eq!(base.quantization, None);
+
+        let english = registry.get("small.en").unwrap();
+        assert_eq!(english.languages, vec!["en".to_string()]);
+        let quantized = registry.get("large-v3-q5_0").unwrap();
+        assert_eq!(quantized.file, "ggml-large-v3-q5_0.bin");
+        assert_eq!(quantized.quantization.as_deref(), Some("q5_0"));
+        assert!(registry.get("distil-large-v3").unwrap().url.is_some());
+
+        assert!(registry.resolve("medium").is_ok());
+        let error = registry.resolve("huge").unwrap_err().to_string();
+        assert!(error.contains("large-v3-turbo"));
+    }
+
+    #[test]
+    fn test_manifest_checksums() {
+        let registry = ModelRegistry::builtin();
+        for model in registry.all() {
+            if model.name == "distil-large-v3" {
+                assert_eq!(model.size, 0);
+                assert_eq!(model.sha256, None);
+                continue;
+            }
+            assert!(model.size > 0, "{} has no size", model);
+            let sha256 = model.sha256.as_deref().unwrap();
+            assert_eq!(sha256.len(), 64);
+            assert!(sha256.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+        assert_eq!(registry.get("base").unwrap().size, 147_951_465);
+        assert_eq!(registry.get("large-v3").unwrap().size, 3_095_033_483);
+    }
+
+    #[test]
+    fn test_custom_models_extend_registry() {
+        let mut base = ModelInfo::named("base", "ggml-base-finetuned.bin");
+        base.size = 10;
+        let config = ModelsConfig {
+            custom: vec![
+                ModelInfo::named("whisper-fr-q8_0", "ggml-whisper-fr-q8_0.bin"),
+                base,
+            ],
+            ..Default::default()
+        };
+
+        let registry = ModelRegistry::from_config(&config);
+        assert_eq!(
+            registry.all().len(),
+            ModelRegistry::builtin().all().len() + 1
+        );
+        assert_eq!(
+            registry.get("base").unwrap().file,
+            "ggml-base-finetuned.bin"
+        );
+        let custom = registry.get("whisper-fr-q8_0").unwrap();
+        assert_eq!(custom.quantization.as_deref(), Some("q8_0"));
     }
 
     #[test]
-    fn test_model_filenames() {
-        assert_eq!(ModelSize::Tiny.filename(), "ggml-tiny.bin");
-        assert_eq!(ModelSize::Base.filename(), "ggml-base.bin");
-        assert_eq!(ModelSize::Small.filename(), "ggml-small.bin");
-        assert_eq!(ModelSize::Medium.filename(), "ggml-medium.bin");
+    fn test_downloadable_models() {
+        assert!(ModelRegistry::builtin()
+            .all()
+            .iter()
+            .all(ModelInfo::is_downloadable));
+
+        let mut model = ModelInfo::named("base", "ggml-base-finetuned.bin");
+        assert!(!model.is_downloadable());
+        model.url = Some("https://example.com/ggml-base-finetuned.bin".to_string());
+        assert!(model.is_downloadable());
+        assert!(ModelInfo::named("mine", "ggml-small.bin").is_downloadable());
+    }
+
+    #[test]
+    fn test_model_name_parsing() {
+        let model = ModelInfo::named("tiny.en-q5_1", "ggml-tiny.en-q5_1.bin");
+        assert_eq!(model.languages, vec!["en".to_string()]);
+        assert_eq!(model.quantization.as_deref(), Some("q5_1"));
+
+        let model = ModelInfo::named("large-v3", "ggml-large-v3.bin");
+        assert!(model.is_multilingual());
+        assert_eq!(model.quantization, None);
+    }
+
+    #[test]
+    fn test_download_url() {
+        let registry = ModelRegistry::builtin();
+        assert_eq!(
+            registry
+                .get("base")
+                .unwrap()
+                .download_url("http://mirror.local/models/"),
+            "http://mirror.local/models/ggml-base.bin"
+        );
+        assert_eq!(
+            registry
+                .get("distil-large-v3")
+                .unwrap()
+                .download_url("http://mirror.local/models"),
+            DISTIL_LARGE_V3_URL
+        );
     }
 
     #[test]
@@ -258,24 +534,29 @@ mod tests {
 
     #[test]
     fn test_auto_select_model() {
-        // Should not panic and return a valid model
-        let model = auto_select_model();
-        println!("Auto-selected model: {}", model);
-        assert!(ModelSize::all().contains(&model));
+        let machine = |cpus, memory_mb, gpu| Machine {
+            cpu: "Test CPU".to_string(),
+            cpus,
+            memory_mb,
+            gpu,
+        };
+        // Without benchmark results for the machine, its specs decide
+        let config = Config::default();
+        let cache = BenchmarkCache::default();
+        let selected = |machine| auto_select_model(&config, &cache, &machine).name;
+        assert_eq!(selected(machine(2, 1024, false)), "tiny");
+        assert_eq!(selected(machine(4, 4096, false)), "base");
+        assert_eq!(selected(machine(8, 8192, false)), "small");
+        assert_eq!(selected(machine(8, 16_384, true)), "medium");
     }
 
     #[test]
     fn test_model_properties() {
-        assert_eq!(ModelSize::Tiny.ram_requirement(), 1);
-        assert_eq!(ModelSize::Medium.ram_requirement(), 5);
-        assert!(ModelSize::Tiny.speed_multiplier() > ModelSize::Medium.speed_multiplier());
-    }
-
-    #[test]
-    fn test_all_models() {
-        let all = ModelSize::all();
-        assert_eq!(all.len(), 4);
-        assert!(all.contains(&ModelSize::Tiny));
-        assert!(all.contains(&ModelSize::Medium));
+        let registry = ModelRegistry::builtin();
+        let tiny = registry.get("tiny").unwrap();
+        let medium = registry.get("medium").unwrap();
+        assert_eq!(tiny.ram_mb, 1024);
+        assert_eq!(medium.ram_mb, 5120);
+        assert!(tiny.speed_multiplier > medium.speed_multiplier);
     }
 }
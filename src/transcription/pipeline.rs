@@ -2,15 +2,35 @@
 //!
 //! This module implements the complete audio → transcription → text pipeline
 //! integrating audio capture, VAD, chunking, and Whisper transcription.
+//!
+//! Audio is normally transcribed once a chunk is complete (an utterance with
+//! VAD, a 10s window without). [`TranscriptionPipeline::flush`] and
+//! [`TranscriptionPipeline::finish`] force the audio buffered so far through,
+//! e.g. when the user stops dictating.
+//!
+//! With [`PipelineConfig::partial_interval`] set, the utterance being spoken
+//! is also re-transcribed as it grows. These [`ResultKind::Partial`] results
+//! are only a live preview: each is superseded by the next one, and finally
+//! by the [`ResultKind::Final`] result of the complete chunk.
 
 use anyhow::{Context, Result};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
 
-use super::models::ModelSize;
+use super::engine::{
+    aggregate_confidence, samples_to_duration, Segment, Token, Transcript, TranscriptionEngine,
+    ENGINE_SAMPLE_RATE,
+};
+use super::models::{configured_model, ModelRegistry};
+use super::reorder::ReorderBuffer;
+use super::segmenter::{spawn_vad_worker, SegmenterConfig};
+use super::stitcher::TranscriptStitcher;
+use crate::audio::preprocess::{spawn_denoise_worker, AudioMessage};
+use crate::config::{Config, Language, ModelInfo};
 
 /// Chunk size for transcription (10 seconds @ 16kHz)
 const CHUNK_SIZE_SAMPLES: usize = 16000 * 10;
@@ -18,6 +38,18 @@ const CHUNK_SIZE_SAMPLES: usize = 16000 * 10;
 /// Overlap size to avoid cutting words (2 seconds)
 const OVERLAP_SIZE_SAMPLES: usize = 16000 * 2;
 
+/// Default shortest remainder transcribed on flush
+const DEFAULT_MIN_FLUSH_DURATION: Duration = Duration::from_millis(300);
+
+/// Whether a chunk or result covers a complete chunk or a growing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultKind {
+    /// Provisional transcription of the audio heard so far, for preview only
+    Partial,
+    /// Transcription of a complete chunk
+    Final,
+}
+
 /// Audio chunk ready for transcription
 #[derive(Clone)]
 pub struct AudioChunk {
@@ -27,6 +59,23 @@ pub struct AudioChunk {
     pub timestamp: std::time::Instant,
     /// Chunk sequence number
     pub sequence: u64,
+    /// Position of the first sample in the audio stream
+    pub start_sample: u64,
+    /// Partial chunks share the sequence number of the final chunk they
+    /// preview
+    pub kind: ResultKind,
+}
+
+impl AudioChunk {
+    /// Offset of the chunk from the start of the audio stream
+    pub fn start(&self) -> Duration {
+        samples_to_duration(self.start_sample as usize)
+    }
+
+    /// Length of the chunk
+    pub fn duration(&self) -> Duration {
+        samples_to_duration(self.samples.len())
+    }
 }
 
 /// Transcription result
@@ -34,52 +83,163 @@ pub struct AudioChunk {
 pub struct TranscriptionResult {
     /// Transcribed text
     pub text: String,
+    /// Segments with offsets relative to the start of the audio stream
+    pub segments: Vec<Segment>,
+    /// Offset of the chunk from the start of the audio stream
+    pub start: Duration,
+    /// Offset of the chunk end from the start of the audio stream
+    pub end: Duration,
+    /// ISO 639-1 code of the detected language, if reported by the engine
+    pub language: Option<String>,
     /// Timestamp of transcription
     pub timestamp: std::time::Instant,
     /// Chunk sequence number
     pub sequence: u64,
-    /// Confidence score (0.0 - 1.0)
+    /// Mean token probability (0.0 - 1.0)
     pub confidence: f32,
+    /// Live preview or final text
+    pub kind: ResultKind,
+}
+
+impl TranscriptionResult {
+    /// Build a result from an engine transcript of the given chunk
+    ///
+    /// Segment offsets are shifted from chunk-relative to stream-relative.
+    pub fn from_transcript(chunk: &AudioChunk, transcript: Transcript) -> Self {
+        let start = chunk.start();
+        let segments: Vec<Segment> = transcript
+            .segments
+            .into_iter()
+            .map(|segment| segment.offset_by(start))
+            .collect();
+
+        let text = segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            text,
+            confidence: aggregate_confidence(&segments),
+            segments,
+            start,
+            end: start + chunk.duration(),
+            language: transcript.language,
+            timestamp: std::time::Instant::now(),
+            sequence: chunk.sequence,
+            kind: chunk.kind,
+        }
+    }
+
+    /// Whether this is the final text of its chunk
+    pub fn is_final(&self) -> bool {
+        self.kind == ResultKind::Final
+    }
+
+    /// Tokens whose probability is below the threshold
+    pub fn low_confidence_tokens(&self, threshold: f32) -> impl Iterator<Item = &Token> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.tokens.iter())
+            .filter(move |token| token.probability < threshold)
+    }
 }
 
 /// Transcription pipeline configuration
 #[derive(Clone)]
 pub struct PipelineConfig {
     /// Whisper model to use
-    pub model: ModelSize,
-    /// Enable Voice Activity Detection
+    pub model: ModelInfo,
+    /// Spoken language (or `Language::Auto` for detection)
+    pub language: Language,
+    /// Optional prompt used to condition the first chunk
+    pub initial_prompt: Option<String>,
+    /// Cut chunks at speech boundaries with Voice Activity Detection
+    /// instead of fixed windows
     pub enable_vad: bool,
-    /// Enable noise reduction
+    /// VAD aggressiveness (0-3)
+    pub vad_aggressiveness: u8,
+    /// Utterance segmentation settings (VAD only)
+    pub segmenter: SegmenterConfig,
+    /// Enable noise reduction (initial state, can be toggled while running)
     pub enable_noise_reduction: bool,
-    /// Maximum concurrent transcriptions
+    /// Maximum concurrent transcriptions (at least 1)
     pub max_concurrent: usize,
+    /// Shortest audio transcribed by a flush or at the end of the stream;
+    /// shorter remainders (clicks, key presses) are dropped
+    pub min_flush_duration: Duration,
+    /// Re-transcribe the growing chunk after this much new audio to emit
+    /// partial results (`None` = finals only)
+    pub partial_interval: Option<Duration>,
 }
 
 impl Default for PipelineConfig {
     fn default() -> Self {
         Self {
-            model: ModelSize::Base,
+            model: ModelRegistry::builtin()
+                .get("base")
+                .cloned()
+                .expect("built-in model"),
+            language: Language::Auto,
+            initial_prompt: None,
             enable_vad: true,
+            vad_aggressiveness: 1,
+            segmenter: SegmenterConfig::default(),
             enable_noise_reduction: true,
             max_concurrent: 2,
+            min_flush_duration: DEFAULT_MIN_FLUSH_DURATION,
+            partial_interval: None,
+        }
+    }
+}
+
+impl PipelineConfig {
+    /// Build a pipeline configuration from the user's settings
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let model = configured_model(config)?;
+        if !model.transcribes(&config.transcription.language) {
+            log::warn!(
+                "The {} model only transcribes {}, not '{}'",
+                model,
+                model.languages.join(", "),
+                config.transcription.language
+            );
         }
+
+        Ok(Self {
+            model,
+            language: Language::from_code(&config.transcription.language),
+            vad_aggressiveness: config.transcription.vad_aggressiveness,
+            enable_noise_reduction: config.audio.noise_reduction,
+            partial_interval: match config.transcription.partial_interval_ms {
+                0 => None,
+                ms => Some(Duration::from_millis(ms)),
+            },
+            ..Default::default()
+        })
     }
 }
 
 /// Real-time transcription pipeline
 pub struct TranscriptionPipeline {
     config: PipelineConfig,
+    engine: Arc<dyn TranscriptionEngine>,
     running: Arc<AtomicBool>,
+    noise_reduction: Arc<AtomicBool>,
     // Channels
-    audio_tx: Option<mpsc::UnboundedSender<Vec<f32>>>,
+    audio_tx: Option<mpsc::UnboundedSender<AudioMessage>>,
     text_rx: Option<mpsc::UnboundedReceiver<TranscriptionResult>>,
 }
 
 impl TranscriptionPipeline {
-    /// Create a new transcription pipeline
-    pub fn new(config: PipelineConfig) -> Result<Self> {
+    /// Create a new transcription pipeline backed by the given engine
+    pub fn new(config: PipelineConfig, engine: Arc<dyn TranscriptionEngine>) -> Result<Self> {
         Ok(Self {
+            noise_reduction: Arc::new(AtomicBool::new(config.enable_noise_reduction)),
             config,
+            engine,
             running: Arc::new(AtomicBool::new(false)),
             audio_tx: None,
             text_rx: None,
@@ -93,8 +253,9 @@ impl TranscriptionPipeline {
         }
 
         log::info!(
-            "Starting transcription pipeline with model: {}",
-            self.config.model
+            "Starting transcription pipeline with model: {} (engine: {})",
+            self.config.model,
+            self.engine.name()
         );
 
         // Create channels
@@ -107,10 +268,14 @@ impl TranscriptionPipeline {
 
         // Spawn pipeline task
         let config = self.config.clone();
+        let engine = Arc::clone(&self.engine);
         let running = Arc::clone(&self.running);
+        let noise_reduction = Arc::clone(&self.noise_reduction);
 
         tokio::spawn(async move {
-            if let Err(e) = run_pipeline(audio_rx, text_tx, config, running).await {
+            if let Err(e) =
+                run_pipeline(audio_rx, text_tx, config, engine, running, noise_reduction).await
+            {
                 log::error!("Pipeline error: {}", e);
             }
         });
@@ -120,19 +285,52 @@ impl TranscriptionPipeline {
     }
 
     /// Stop the transcription pipeline
+    ///
+    /// Audio not yet handed to the engine is discarded, but chunks already
+    /// being transcribed still complete: [`receive_text`] delivers their
+    /// results, then returns `None`.
+    ///
+    /// [`receive_text`]: TranscriptionPipeline::receive_text
     pub fn stop(&mut self) {
         if self.running.load(Ordering::Relaxed) {
             log::info!("Stopping transcription pipeline");
             self.running.store(false, Ordering::Relaxed);
             self.audio_tx = None;
-            self.text_rx = None;
+        }
+    }
+
+    /// Transcribe the audio buffered so far without waiting for the chunk
+    /// to complete
+    ///
+    /// Use this at the end of an utterance (e.g. when a push-to-talk key is
+    /// released). Remainders shorter than
+    /// [`PipelineConfig::min_flush_duration`] are dropped. The pipeline
+    /// keeps running and accepts more audio afterwards.
+    pub fn flush(&self) -> Result<()> {
+        if let Some(tx) = &self.audio_tx {
+            tx.send(AudioMessage::Flush)
+                .context("Failed to flush pipeline")?;
+        }
+        Ok(())
+    }
+
+    /// Signal that no more audio will be sent
+    ///
+    /// The audio still buffered is transcribed (subject to
+    /// [`PipelineConfig::min_flush_duration`]), then [`receive_text`]
+    /// returns `None` once every result has been delivered.
+    ///
+    /// [`receive_text`]: TranscriptionPipeline::receive_text
+    pub fn finish(&mut self) {
+        if self.audio_tx.take().is_some() {
+            log::info!("Finishing transcription pipeline input");
         }
     }
 
     /// Send audio samples to the pipeline
     pub fn send_audio(&self, samples: Vec<f32>) -> Result<()> {
         if let Some(tx) = &self.audio_tx {
-            tx.send(samples)
+            tx.send(AudioMessage::Samples(samples))
                 .context("Failed to send audio to pipeline")?;
         }
         Ok(())
@@ -151,6 +349,16 @@ impl TranscriptionPipeline {
     pub fn is_running(&self) -> bool {
         self.running.load(Ordering::Relaxed)
     }
+
+    /// Turn noise reduction on or off, also while the pipeline is running
+    pub fn set_noise_reduction(&self, enabled: bool) {
+        self.noise_reduction.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Check if noise reduction is enabled
+    pub fn is_noise_reduction_enabled(&self) -> bool {
+        self.noise_reduction.load(Ordering::Relaxed)
+    }
 }
 
 impl Drop for TranscriptionPipeline {
@@ -161,75 +369,214 @@ impl Drop for TranscriptionPipeline {
 
 /// Run the pipeline (internal task)
 async fn run_pipeline(
-    mut audio_rx: mpsc::UnboundedReceiver<Vec<f32>>,
+    audio_rx: mpsc::UnboundedReceiver<AudioMessage>,
     text_tx: mpsc::UnboundedSender<TranscriptionResult>,
     config: PipelineConfig,
+    engine: Arc<dyn TranscriptionEngine>,
     running: Arc<AtomicBool>,
+    noise_reduction: Arc<AtomicBool>,
 ) -> Result<()> {
     log::info!("Pipeline task started");
 
-    // TODO: VAD integration pending - webrtc-vad is not Send, needs separate thread
-    // For now, VAD is disabled in the async pipeline to allow compilation.
-    // Future implementation will use a dedicated VAD thread with channels.
+    // Denoising keeps samples aligned, so chunk positions still match the input
+    let (audio_rx, _) = spawn_denoise_worker(audio_rx, ENGINE_SAMPLE_RATE, noise_reduction)?;
+
+    // Audio is cut into chunks either at speech boundaries or in fixed windows
+    let (chunk_tx, mut chunk_rx) = mpsc::unbounded_channel();
     if config.enable_vad {
-        log::warn!("VAD is not yet supported in the async pipeline (webrtc-vad is not Send)");
-        log::warn!("VAD will be implemented in a separate thread in a future update");
+        // webrtc-vad is not Send, so segmentation runs on its own thread
+        spawn_vad_worker(audio_rx, chunk_tx, &config)?;
+    } else {
+        tokio::spawn(run_fixed_chunker(
+            audio_rx,
+            chunk_tx,
+            Arc::clone(&running),
+            config.min_flush_duration,
+            config.partial_interval,
+        ));
+    }
+
+    // Chunk results are put back in order and stitched before reaching the caller
+    let (raw_tx, raw_rx) = mpsc::unbounded_channel();
+    tokio::spawn(run_result_stage(raw_rx, text_tx));
+
+    // Bound the number of chunks transcribed in parallel. Previews have a
+    // slot of their own so that finals never wait for one
+    let permits = Arc::new(Semaphore::new(config.max_concurrent.max(1)));
+    let preview_permit = Arc::new(Semaphore::new(1));
+
+    while let Some(chunk) = chunk_rx.recv().await {
+        if !running.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let permit = match chunk.kind {
+            // Wait for a free slot; audio keeps queueing upstream meanwhile
+            ResultKind::Final => Arc::clone(&permits)
+                .acquire_owned()
+                .await
+                .context("Transcription semaphore closed")?,
+            // Previews are skipped while the previous one is running
+            ResultKind::Partial => match Arc::clone(&preview_permit).try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    log::trace!("Busy, skipping partial of chunk {}", chunk.sequence);
+                    continue;
+                }
+            },
+        };
+
+        // Transcribe chunk (spawn task for concurrency)
+        let tx = raw_tx.clone();
+        let engine = Arc::clone(&engine);
+        let language = config.language.clone();
+        let prompt = config.initial_prompt.clone();
+        tokio::spawn(async move {
+            let sequence = chunk.sequence;
+            let kind = chunk.kind;
+            let result = match transcribe_chunk(chunk, engine, language, prompt).await {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    log::error!("Transcription error on chunk {}: {}", sequence, e);
+                    None
+                }
+            };
+            drop(permit);
+
+            // A failed preview is simply not shown
+            if kind == ResultKind::Partial && result.is_none() {
+                return;
+            }
+
+            // Failed chunks are still reported so later ones are not held back
+            if let Err(e) = tx.send((sequence, result)) {
+                log::error!("Failed to send transcription result: {}", e);
+            }
+        });
     }
 
+    log::info!("Pipeline task stopped");
+    Ok(())
+}
+
+/// Cut the audio stream into fixed, overlapping windows (VAD disabled)
+async fn run_fixed_chunker(
+    mut audio_rx: mpsc::UnboundedReceiver<AudioMessage>,
+    chunk_tx: mpsc::UnboundedSender<AudioChunk>,
+    running: Arc<AtomicBool>,
+    min_flush: Duration,
+    partial_interval: Option<Duration>,
+) {
+    let to_samples = |d: Duration| (d.as_secs_f64() * ENGINE_SAMPLE_RATE as f64) as u64;
+    let min_flush_samples = to_samples(min_flush);
+    let partial_samples = partial_interval.map(to_samples);
+
     // Buffer for accumulating audio
     let mut buffer = Vec::new();
     let mut sequence = 0u64;
+    // Stream position of buffer[0]
+    let mut buffer_start = 0u64;
+    // Stream position up to which audio has been sent for transcription
+    let mut transcribed_until = 0u64;
+    // Stream position up to which the growing chunk has been previewed
+    let mut previewed_until = 0u64;
 
-    // Main pipeline loop
     while running.load(Ordering::Relaxed) {
         // Receive audio with timeout
         match tokio::time::timeout(std::time::Duration::from_millis(100), audio_rx.recv()).await {
-            Ok(Some(samples)) => {
+            Ok(Some(AudioMessage::Samples(samples))) => {
                 // Add to buffer
                 buffer.extend_from_slice(&samples);
 
-                // Check if we have enough for a chunk
-                if buffer.len() >= CHUNK_SIZE_SAMPLES {
+                // Cut as many chunks as the buffer holds
+                while buffer.len() >= CHUNK_SIZE_SAMPLES {
                     // Extract chunk with overlap
                     let chunk_end = CHUNK_SIZE_SAMPLES.min(buffer.len());
                     let chunk_samples = buffer[..chunk_end].to_vec();
+                    let chunk_start = buffer_start;
+                    transcribed_until = chunk_start + chunk_end as u64;
 
                     // Keep overlap for next chunk
-                    if buffer.len() > OVERLAP_SIZE_SAMPLES {
-                        buffer.drain(..(chunk_end - OVERLAP_SIZE_SAMPLES));
+                    let consumed = if buffer.len() > OVERLAP_SIZE_SAMPLES {
+                        chunk_end - OVERLAP_SIZE_SAMPLES
                     } else {
-                        buffer.clear();
-                    }
+                        buffer.len()
+                    };
+                    buffer.drain(..consumed);
+                    buffer_start += consumed as u64;
 
                     // Create chunk
                     let chunk = AudioChunk {
                         samples: chunk_samples,
                         timestamp: std::time::Instant::now(),
                         sequence,
+                        start_sample: chunk_start,
+                        kind: ResultKind::Final,
                     };
 
                     sequence += 1;
 
-                    // Transcribe chunk (spawn task for concurrency)
-                    let tx = text_tx.clone();
-                    let model = config.model;
-                    tokio::spawn(async move {
-                        match transcribe_chunk(chunk, model).await {
-                            Ok(result) => {
-                                if let Err(e) = tx.send(result) {
-                                    log::error!("Failed to send transcription result: {}", e);
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Transcription error: {}", e);
-                            }
+                    if chunk_tx.send(chunk).is_err() {
+                        return;
+                    }
+                }
+
+                // Preview the growing chunk once enough new audio came in
+                let buffer_end = buffer_start + buffer.len() as u64;
+                let since = previewed_until.max(transcribed_until);
+                if let Some(interval) = partial_samples {
+                    if buffer_end >= since + interval.max(1) {
+                        previewed_until = buffer_end;
+                        let chunk = AudioChunk {
+                            samples: buffer.clone(),
+                            timestamp: std::time::Instant::now(),
+                            sequence,
+                            start_sample: buffer_start,
+                            kind: ResultKind::Partial,
+                        };
+                        if chunk_tx.send(chunk).is_err() {
+                            return;
                         }
-                    });
+                    }
                 }
             }
-            Ok(None) => {
-                // Channel closed
-                break;
+            Ok(message) => {
+                // Flush, or input finished: transcribe what is left and
+                // start over after it
+                let finished = message.is_none();
+                let buffer_end = buffer_start + buffer.len() as u64;
+                let new_samples = buffer_end.saturating_sub(transcribed_until);
+                let samples = std::mem::take(&mut buffer);
+                let chunk_start = buffer_start;
+                buffer_start = buffer_end;
+                transcribed_until = buffer_end;
+
+                // Skip tails that are only the overlap of the last chunk, or
+                // too short to hold a word
+                if new_samples >= min_flush_samples.max(1) {
+                    let chunk = AudioChunk {
+                        samples,
+                        timestamp: std::time::Instant::now(),
+                        sequence,
+                        start_sample: chunk_start,
+                        kind: ResultKind::Final,
+                    };
+                    sequence += 1;
+
+                    if chunk_tx.send(chunk).is_err() {
+                        return;
+                    }
+                } else if new_samples > 0 {
+                    log::debug!(
+                        "Dropping {} samples shorter than {:?}",
+                        new_samples,
+                        min_flush
+                    );
+                }
+
+                if finished {
+                    break;
+                }
             }
             Err(_) => {
                 // Timeout - continue loop
@@ -237,68 +584,112 @@ async fn run_pipeline(
             }
         }
     }
-
-    log::info!("Pipeline task stopped");
-    Ok(())
 }
 
-/// Transcribe an audio chunk using Whisper
-async fn transcribe_chunk(chunk: AudioChunk, model: ModelSize) -> Result<TranscriptionResult> {
-    // TODO: Integrate real whisper-rs when CMake is configured
-    // For now, use placeholder transcription
+/// Restore chunk order, stitch results and forward the new text to the caller
+///
+/// Each chunk reports `(sequence, result)`, with `None` when transcription
+/// failed, so a missing chunk never stalls the ones after it. Partial results
+/// bypass the reordering and are trimmed without affecting later chunks.
+async fn run_result_stage(
+    mut raw_rx: mpsc::UnboundedReceiver<(u64, Option<TranscriptionResult>)>,
+    text_tx: mpsc::UnboundedSender<TranscriptionResult>,
+) {
+    let mut reorder = ReorderBuffer::new();
+    let mut stitcher = TranscriptStitcher::new();
 
-    log::debug!(
-        "Transcribing chunk {} ({} samples, {} sec) with model {}",
-        chunk.sequence,
-        chunk.samples.len(),
-        chunk.samples.len() as f32 / 16000.0,
-        model
-    );
+    while let Some((sequence, result)) = raw_rx.recv().await {
+        if let Some(partial) = result.as_ref().filter(|result| !result.is_final()) {
+            // Only preview the chunk that comes next: older partials were
+            // overtaken by their final, newer ones would show text out of order
+            if sequence != reorder.next_sequence() {
+                continue;
+            }
+            let partial = stitcher.preview(partial.clone());
+            if !partial.text.is_empty() && text_tx.send(partial).is_err() {
+                log::error!("Failed to send partial transcription result");
+            }
+            continue;
+        }
 
-    // Simulate transcription delay based on model
-    let delay_ms = match model {
-        ModelSize::Tiny => 100,
-        ModelSize::Base => 200,
-        ModelSize::Small => 500,
-        ModelSize::Medium => 1000,
-    };
+        for result in reorder.push(sequence, result) {
+            let result = stitcher.stitch(result);
 
-    tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            if result.text.is_empty() {
+                log::debug!("Chunk {} only repeated the overlap", result.sequence);
+                continue;
+            }
 
-    // Placeholder: Generate fake transcription
-    // TODO: Replace with real whisper-rs call:
-    // let whisper = WhisperContext::new(model)?;
-    // let text = whisper.transcribe(&chunk.samples)?;
+            if let Err(e) = text_tx.send(result) {
+                log::error!("Failed to send transcription result: {}", e);
+            }
+        }
+    }
+}
 
-    let text = format!(
-        "[Placeholder] Chunk {} transcribed ({:.1}s audio)",
+/// Transcribe an audio chunk on a blocking worker thread
+async fn transcribe_chunk(
+    chunk: AudioChunk,
+    engine: Arc<dyn TranscriptionEngine>,
+    language: Language,
+    prompt: Option<String>,
+) -> Result<TranscriptionResult> {
+    log::debug!(
+        "Transcribing chunk {} ({} samples, {:.1} sec) with engine {}",
         chunk.sequence,
-        chunk.samples.len() as f32 / 16000.0
+        chunk.samples.len(),
+        samples_to_duration(chunk.samples.len()).as_secs_f32(),
+        engine.name()
     );
 
-    Ok(TranscriptionResult {
-        text,
-        timestamp: std::time::Instant::now(),
-        sequence: chunk.sequence,
-        confidence: 0.85, // Placeholder confidence
+    let (chunk, transcript) = tokio::task::spawn_blocking(move || {
+        let transcript = engine.transcribe(&chunk.samples, &language, prompt.as_deref());
+        (chunk, transcript)
     })
+    .await
+    .context("Transcription worker panicked")?;
+
+    Ok(TranscriptionResult::from_transcript(&chunk, transcript?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transcription::engine::MockEngine;
+
+    fn mock_pipeline(config: PipelineConfig) -> TranscriptionPipeline {
+        TranscriptionPipeline::new(config, Arc::new(MockEngine::new())).unwrap()
+    }
 
     #[tokio::test]
     async fn test_pipeline_creation() {
         let config = PipelineConfig::default();
-        let pipeline = TranscriptionPipeline::new(config);
+        let pipeline = TranscriptionPipeline::new(config, Arc::new(MockEngine::new()));
         assert!(pipeline.is_ok());
     }
 
+    #[test]
+    fn test_pipeline_config_from_config() {
+        let mut config = Config::default();
+        config.transcription.model = "small".to_string();
+        config.transcription.language = "fr".to_string();
+        config.transcription.vad_aggressiveness = 3;
+        config.audio.noise_reduction = false;
+
+        let pipeline_config = PipelineConfig::from_config(&config).unwrap();
+        assert_eq!(pipeline_config.model.name, "small");
+        assert_eq!(pipeline_config.vad_aggressiveness, 3);
+        assert_eq!(pipeline_config.language, Language::French);
+        assert!(!pipeline_config.enable_noise_reduction);
+
+        config.transcription.model = "huge".to_string();
+        assert!(PipelineConfig::from_config(&config).is_err());
+    }
+
     #[tokio::test]
     async fn test_pipeline_start_stop() {
         let config = PipelineConfig::default();
-        let mut pipeline = TranscriptionPipeline::new(config).unwrap();
+        let mut pipeline = mock_pipeline(config);
 
         assert!(!pipeline.is_running());
 
@@ -309,10 +700,58 @@ mod tests {
         assert!(!pipeline.is_running());
     }
 
+    #[tokio::test]
+    async fn test_toggle_noise_reduction() {
+        // One second of white noise, which RNNoise strongly attenuates
+        fn noise() -> Vec<f32> {
+            let mut state = 12345u32;
+            (0..16000)
+                .map(|_| {
+                    state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (state >> 8) as f32 / (1u32 << 24) as f32 * 0.6 - 0.3
+                })
+                .collect()
+        }
+
+        // The mock tells whether it received the samples untouched
+        async fn transcribe_noise(noise_reduction: bool) -> String {
+            let engine = MockEngine::new().with_responder(|samples| {
+                let text = if samples == noise().as_slice() {
+                    " raw"
+                } else {
+                    " denoised"
+                };
+                vec![Segment::new(text, Duration::ZERO, Duration::from_secs(1))]
+            });
+            let config = PipelineConfig {
+                enable_vad: false,
+                ..Default::default()
+            };
+            let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+            assert!(pipeline.is_noise_reduction_enabled());
+
+            pipeline.start().await.unwrap();
+            pipeline.set_noise_reduction(noise_reduction);
+            assert_eq!(pipeline.is_noise_reduction_enabled(), noise_reduction);
+            pipeline.send_audio(noise()).unwrap();
+            pipeline.flush().unwrap();
+
+            let result = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+                .await
+                .unwrap()
+                .unwrap();
+            pipeline.stop();
+            result.text
+        }
+
+        assert_eq!(transcribe_noise(true).await, "denoised");
+        assert_eq!(transcribe_noise(false).await, "raw");
+    }
+
     #[tokio::test]
     async fn test_send_audio() {
         let config = PipelineConfig::default();
-        let mut pipeline = TranscriptionPipeline::new(config).unwrap();
+        let mut pipeline = mock_pipeline(config);
 
         pipeline.start().await.unwrap();
 
@@ -329,13 +768,13 @@ mod tests {
             enable_vad: false, // Disable VAD for testing
             ..Default::default()
         };
-        let mut pipeline = TranscriptionPipeline::new(config).unwrap();
+        let mut pipeline = mock_pipeline(config);
 
         pipeline.start().await.unwrap();
 
-        // Send a full chunk of audio
+        // Send a full chunk of audio, plus enough to cover noise reduction latency
         let chunk_size = 16000 * 10; // 10 seconds
-        let samples = vec![0.1; chunk_size];
+        let samples = vec![0.1; chunk_size + 16000 / 2];
         pipeline.send_audio(samples).unwrap();
 
         // Wait for transcription
@@ -351,31 +790,428 @@ mod tests {
         pipeline.stop();
     }
 
+    #[tokio::test]
+    async fn test_vad_skips_silence() {
+        let mut pipeline = mock_pipeline(PipelineConfig::default());
+        pipeline.start().await.unwrap();
+
+        // Longer than a fixed window, but nothing to transcribe
+        pipeline.send_audio(vec![0.0; 16000 * 12]).unwrap();
+
+        let result =
+            tokio::time::timeout(Duration::from_millis(500), pipeline.receive_text()).await;
+        assert!(result.is_err(), "silence should not be transcribed");
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_overlap_is_not_emitted_twice() {
+        use crate::transcription::engine::MockEngine;
+
+        // Each second of audio carries its index as sample value, and the mock
+        // "hears" one word per second: w0, w1, ...
+        let engine = MockEngine::new().with_responder(|samples| {
+            samples
+                .chunks(16000)
+                .enumerate()
+                .map(|(i, second)| {
+                    let word = format!(" w{}", first_second(second));
+                    Segment::new(
+                        word.clone(),
+                        Duration::from_secs(i as u64),
+                        Duration::from_secs(i as u64 + 1),
+                    )
+                    .with_tokens(vec![Token::new(word, 0.9)])
+                })
+                .collect()
+        });
+
+        let config = PipelineConfig {
+            enable_vad: false,
+            // The mock decodes exact sample values
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // 18 seconds = chunk 0 (0-10s) + chunk 1 (8-18s)
+        pipeline.send_audio(numbered_seconds(18)).unwrap();
+
+        let texts = receive_texts(&mut pipeline, 2).await;
+        assert_eq!(texts[0], "w0 w1 w2 w3 w4 w5 w6 w7 w8 w9");
+        assert_eq!(texts[1], "w10 w11 w12 w13 w14 w15 w16 w17");
+
+        pipeline.stop();
+    }
+
+    /// Audio where every sample of second `n` has the value `n / 1000`
+    fn numbered_seconds(seconds: u32) -> Vec<f32> {
+        (0..seconds)
+            .flat_map(|sec| vec![sec as f32 / 1000.0; 16000])
+            .collect()
+    }
+
+    /// Index of the second a chunk of [`numbered_seconds`] audio starts at
+    fn first_second(samples: &[f32]) -> u32 {
+        (samples[0] * 1000.0).round() as u32
+    }
+
+    /// Mock output naming the second a chunk starts at, e.g. "s8"
+    fn chunk_label(samples: &[f32]) -> Vec<Segment> {
+        let label = format!("s{}", first_second(samples));
+        vec![Segment::new(
+            label.clone(),
+            Duration::ZERO,
+            samples_to_duration(samples.len()),
+        )
+        .with_tokens(vec![Token::new(label, 0.9)])]
+    }
+
+    async fn receive_texts(pipeline: &mut TranscriptionPipeline, count: usize) -> Vec<String> {
+        let mut texts = Vec::new();
+        for _ in 0..count {
+            let result = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+                .await
+                .expect("timed out waiting for transcription")
+                .expect("pipeline closed");
+            texts.push(result.text);
+        }
+        texts
+    }
+
+    #[tokio::test]
+    async fn test_results_delivered_in_order() {
+        // Earlier chunks take longer, so they finish last
+        let engine = MockEngine::new().with_responder(|samples| {
+            let delay = 300u64.saturating_sub(first_second(samples) as u64 * 20);
+            std::thread::sleep(Duration::from_millis(delay));
+            chunk_label(samples)
+        });
+
+        let config = PipelineConfig {
+            enable_vad: false,
+            // The mock decodes exact sample values
+            enable_noise_reduction: false,
+            max_concurrent: 3,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // Chunks start at 0s, 8s and 16s
+        pipeline.send_audio(numbered_seconds(26)).unwrap();
+
+        assert_eq!(receive_texts(&mut pipeline, 3).await, ["s0", "s8", "s16"]);
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_is_enforced() {
+        use std::sync::atomic::AtomicUsize;
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let engine = {
+            let active = Arc::clone(&active);
+            let peak = Arc::clone(&peak);
+            MockEngine::new().with_responder(move |samples| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(50));
+                active.fetch_sub(1, Ordering::SeqCst);
+                chunk_label(samples)
+            })
+        };
+
+        let config = PipelineConfig {
+            enable_vad: false,
+            // The mock decodes exact sample values
+            enable_noise_reduction: false,
+            max_concurrent: 2,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // Five chunks starting every 8 seconds
+        pipeline.send_audio(numbered_seconds(42)).unwrap();
+
+        assert_eq!(
+            receive_texts(&mut pipeline, 5).await,
+            ["s0", "s8", "s16", "s24", "s32"]
+        );
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_finish_transcribes_remaining_audio() {
+        let engine = MockEngine::new().with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // One full chunk (0-10s), then a short tail starting at the overlap
+        pipeline.send_audio(numbered_seconds(13)).unwrap();
+        pipeline.finish();
+
+        assert_eq!(receive_texts(&mut pipeline, 2).await, ["s0", "s8"]);
+        assert!(pipeline.receive_text().await.is_none());
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_finish_skips_overlap_only_tail() {
+        let engine = MockEngine::new().with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // Exactly one chunk: what remains is the already transcribed overlap
+        pipeline.send_audio(numbered_seconds(10)).unwrap();
+        pipeline.finish();
+
+        assert_eq!(receive_texts(&mut pipeline, 1).await, ["s0"]);
+        assert!(pipeline.receive_text().await.is_none());
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_flush_transcribes_partial_chunk() {
+        let engine = MockEngine::new().with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // A 4 second dictation, far from a full chunk
+        pipeline.send_audio(numbered_seconds(4)).unwrap();
+        pipeline.flush().unwrap();
+        assert_eq!(receive_texts(&mut pipeline, 1).await, ["s0"]);
+
+        // The pipeline keeps going, and the next flush starts after the first
+        pipeline
+            .send_audio(numbered_seconds(7)[16000 * 4..].to_vec())
+            .unwrap();
+        pipeline.flush().unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.text, "s4");
+        assert_eq!(result.start, Duration::from_secs(4));
+        assert!(pipeline.is_running());
+
+        pipeline.stop();
+    }
+
+    #[tokio::test]
+    async fn test_flush_drops_short_remainder() {
+        let engine = MockEngine::new().with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            min_flush_duration: Duration::from_millis(500),
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        pipeline.send_audio(vec![0.0; 1600]).unwrap();
+        pipeline.flush().unwrap();
+        pipeline.send_audio(vec![0.0; 1600]).unwrap();
+        pipeline.finish();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+            .await
+            .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_partial_results_preview_growing_chunk() {
+        let engine = MockEngine::new().with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            partial_interval: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // Half a second is not enough for a preview, the next second is
+        pipeline
+            .send_audio(numbered_seconds(3)[..8000].to_vec())
+            .unwrap();
+        pipeline
+            .send_audio(numbered_seconds(3)[8000..24000].to_vec())
+            .unwrap();
+        let partial = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(partial.kind, ResultKind::Partial);
+        assert_eq!(partial.text, "s0");
+        assert_eq!(partial.end, Duration::from_millis(1500));
+
+        // The final covers everything and replaces the previews
+        pipeline
+            .send_audio(numbered_seconds(3)[24000..].to_vec())
+            .unwrap();
+        pipeline.finish();
+        let mut results = Vec::new();
+        while let Some(result) = pipeline.receive_text().await {
+            results.push(result);
+        }
+        let last = results.last().unwrap();
+        assert!(last.is_final());
+        assert_eq!(last.end, Duration::from_secs(3));
+        assert_eq!(results.iter().filter(|r| r.is_final()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stop_drains_in_flight_chunks() {
+        let engine = MockEngine::new()
+            .with_delay(Duration::from_millis(300))
+            .with_responder(chunk_label);
+        let config = PipelineConfig {
+            enable_vad: false,
+            enable_noise_reduction: false,
+            ..Default::default()
+        };
+        let mut pipeline = TranscriptionPipeline::new(config, Arc::new(engine)).unwrap();
+        pipeline.start().await.unwrap();
+
+        // The first chunk is being transcribed when the pipeline stops; the
+        // 4 seconds after it are discarded
+        pipeline.send_audio(numbered_seconds(12)).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        pipeline.stop();
+        assert!(!pipeline.is_running());
+
+        assert_eq!(receive_texts(&mut pipeline, 1).await, ["s0"]);
+        let end = tokio::time::timeout(Duration::from_secs(5), pipeline.receive_text())
+            .await
+            .unwrap();
+        assert!(end.is_none());
+    }
+
     #[tokio::test]
     async fn test_chunk_creation() {
         let chunk = AudioChunk {
             samples: vec![0.0; 1000],
             timestamp: std::time::Instant::now(),
             sequence: 42,
+            start_sample: 16000,
+            kind: ResultKind::Final,
         };
 
         assert_eq!(chunk.samples.len(), 1000);
         assert_eq!(chunk.sequence, 42);
+        assert_eq!(chunk.start(), Duration::from_secs(1));
     }
 
     #[tokio::test]
-    async fn test_transcribe_chunk_placeholder() {
+    async fn test_transcribe_chunk_mock() {
         let chunk = AudioChunk {
             samples: vec![0.1; 16000], // 1 second
             timestamp: std::time::Instant::now(),
             sequence: 0,
+            start_sample: 0,
+            kind: ResultKind::Final,
         };
 
-        let result = transcribe_chunk(chunk, ModelSize::Tiny).await;
+        let engine: Arc<dyn TranscriptionEngine> = Arc::new(MockEngine::new());
+        let result = transcribe_chunk(chunk, engine, Language::Auto, None).await;
         assert!(result.is_ok());
 
         let result = result.unwrap();
-        assert!(!result.text.is_empty());
+        assert_eq!(result.text, "[Mock] 1.0s audio");
         assert_eq!(result.sequence, 0);
+        assert_eq!(result.confidence, 1.0);
+        assert_eq!(result.end, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_chunk_joins_segments() {
+        let engine: Arc<dyn TranscriptionEngine> =
+            Arc::new(MockEngine::new().with_responder(|_| {
+                vec![
+                    Segment::new(" Hello", Duration::ZERO, Duration::from_millis(500)),
+                    Segment::new(
+                        " world. ",
+                        Duration::from_millis(500),
+                        Duration::from_secs(1),
+                    ),
+                ]
+            }));
+        let chunk = AudioChunk {
+            samples: vec![0.0; 16000],
+            timestamp: std::time::Instant::now(),
+            sequence: 3,
+            start_sample: 16000 * 24,
+            kind: ResultKind::Final,
+        };
+
+        let result = transcribe_chunk(chunk, engine, Language::English, None)
+            .await
+            .unwrap();
+        assert_eq!(result.text, "Hello world.");
+        assert_eq!(result.sequence, 3);
+        assert_eq!(result.language.as_deref(), Some("en"));
+
+        // Segment offsets are relative to the stream, not the chunk
+        assert_eq!(result.start, Duration::from_secs(24));
+        assert_eq!(result.segments[0].start, Duration::from_secs(24));
+        assert_eq!(result.segments[1].end, Duration::from_secs(25));
+        // No tokens reported, so there is no evidence of confidence
+        assert_eq!(result.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_low_confidence_tokens() {
+        let chunk = AudioChunk {
+            samples: vec![0.0; 16000],
+            timestamp: std::time::Instant::now(),
+            sequence: 0,
+            start_sample: 0,
+            kind: ResultKind::Final,
+        };
+        let transcript = Transcript::new(vec![Segment::new(
+            " the quick fox",
+            Duration::ZERO,
+            Duration::from_secs(1),
+        )
+        .with_tokens(vec![
+            Token::new(" the", 0.95),
+            Token::new(" quick", 0.30),
+            Token::new(" fox", 0.90),
+        ])]);
+
+        let result = TranscriptionResult::from_transcript(&chunk, transcript);
+        let low: Vec<&str> = result
+            .low_confidence_tokens(0.5)
+            .map(|token| token.text.as_str())
+            .collect();
+        assert_eq!(low, vec![" quick"]);
+        assert!((result.confidence - 0.7167).abs() < 1e-3);
     }
 }
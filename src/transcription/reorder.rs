@@ -0,0 +1,112 @@
+//! Reorder buffer for concurrently transcribed chunks
+//!
+//! Chunks are transcribed in parallel and may finish in any order. The
+//! buffer holds early arrivals until every preceding sequence number has
+//! been seen, so results are always released in sequence order.
+
+use std::collections::BTreeMap;
+
+/// Releases items in sequence order
+///
+/// Every sequence number must eventually be pushed, even if there is no item
+/// for it (`None`), otherwise later items are held back forever.
+pub struct ReorderBuffer<T> {
+    next_sequence: u64,
+    pending: BTreeMap<u64, Option<T>>,
+}
+
+impl<T> ReorderBuffer<T> {
+    /// Create a buffer expecting sequence 0 first
+    pub fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Create a buffer expecting the given sequence number first
+    pub fn starting_at(sequence: u64) -> Self {
+        Self {
+            next_sequence: sequence,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Add the outcome for a sequence number and return every item that is
+    /// now ready, in order
+    ///
+    /// Outcomes for sequence numbers that were already released are ignored.
+    pub fn push(&mut self, sequence: u64, item: Option<T>) -> Vec<T> {
+        if sequence < self.next_sequence {
+            log::warn!(
+                "Ignoring stale chunk {} (expecting {})",
+                sequence,
+                self.next_sequence
+            );
+            return Vec::new();
+        }
+
+        self.pending.insert(sequence, item);
+
+        let mut ready = Vec::new();
+        while let Some(item) = self.pending.remove(&self.next_sequence) {
+            ready.extend(item);
+            self.next_sequence += 1;
+        }
+        ready
+    }
+
+    /// Sequence number the buffer is waiting for
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Number of outcomes held back waiting for an earlier sequence
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<T> Default for ReorderBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_order_passes_through() {
+        let mut buffer = ReorderBuffer::new();
+        assert_eq!(buffer.push(0, Some("a")), vec!["a"]);
+        assert_eq!(buffer.push(1, Some("b")), vec!["b"]);
+        assert_eq!(buffer.pending(), 0);
+    }
+
+    #[test]
+    fn test_out_of_order_is_held_back() {
+        let mut buffer = ReorderBuffer::new();
+        assert!(buffer.push(2, Some("c")).is_empty());
+        assert!(buffer.push(1, Some("b")).is_empty());
+        assert_eq!(buffer.pending(), 2);
+
+        assert_eq!(buffer.push(0, Some("a")), vec!["a", "b", "c"]);
+        assert_eq!(buffer.next_sequence(), 3);
+    }
+
+    #[test]
+    fn test_missing_item_does_not_stall() {
+        let mut buffer = ReorderBuffer::new();
+        assert!(buffer.push(1, Some("b")).is_empty());
+
+        // Chunk 0 failed to transcribe
+        assert_eq!(buffer.push(0, None), vec!["b"]);
+    }
+
+    #[test]
+    fn test_stale_sequence_ignored() {
+        let mut buffer = ReorderBuffer::starting_at(5);
+        assert!(buffer.push(3, Some("old")).is_empty());
+        assert_eq!(buffer.pending(), 0);
+        assert_eq!(buffer.push(5, Some("new")), vec!["new"]);
+    }
+}
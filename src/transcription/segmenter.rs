@@ -0,0 +1,485 @@
+//! VAD-driven utterance segmentation
+//!
+//! Instead of cutting the stream into fixed windows, the segmenter follows
+//! the voice activity detector and emits one chunk per utterance. Silence
+//! between utterances is never sent to the engine.
+//!
+//! `webrtc-vad` is not `Send`, so the detector lives on a dedicated thread
+//! (see [`spawn_vad_worker`]) which feeds chunks back to the async pipeline.
+
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::engine::ENGINE_SAMPLE_RATE;
+use super::pipeline::{AudioChunk, PipelineConfig, ResultKind};
+use crate::audio::preprocess::AudioMessage;
+use crate::audio::vad::VoiceActivityDetector;
+
+/// VAD frame size (30ms @ 16kHz)
+pub const VAD_FRAME_SAMPLES: usize = 480;
+
+/// Segmentation settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmenterConfig {
+    /// Audio kept from before speech onset, so the first syllable is not clipped
+    pub pre_roll: Duration,
+    /// Silence that ends an utterance, kept as trailing padding
+    pub hang_over: Duration,
+    /// Longest utterance before it is split
+    pub max_utterance: Duration,
+    /// Audio repeated at the start of the next piece when an utterance is split
+    pub split_overlap: Duration,
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        Self {
+            pre_roll: Duration::from_millis(300),
+            hang_over: Duration::from_millis(600),
+            max_utterance: Duration::from_secs(15),
+            split_overlap: Duration::from_secs(2),
+        }
+    }
+}
+
+/// A span of speech cut from the stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    /// Audio samples (16kHz mono f32), including padding
+    pub samples: Vec<f32>,
+    /// Position of the first sample in the audio stream
+    pub start_sample: u64,
+}
+
+impl Utterance {
+    /// Turn the utterance into a chunk for transcription
+    pub fn into_chunk(self, sequence: u64) -> AudioChunk {
+        AudioChunk {
+            samples: self.samples,
+            timestamp: std::time::Instant::now(),
+            sequence,
+            start_sample: self.start_sample,
+            kind: ResultKind::Final,
+        }
+    }
+}
+
+/// Groups VAD frames into utterances
+///
+/// Frames are pushed together with the detector's decision. Silence is held
+/// in a short pre-roll buffer and discarded once it ages out.
+pub struct UtteranceSegmenter {
+    pre_roll_samples: usize,
+    hang_over_samples: usize,
+    max_samples: usize,
+    overlap_samples: usize,
+    /// Recent silence, prepended when speech starts
+    pre_roll: VecDeque<f32>,
+    /// Utterance being built
+    current: Vec<f32>,
+    /// Stream position of `current[0]`
+    current_start: u64,
+    /// Stream position of the next frame
+    position: u64,
+    in_speech: bool,
+    /// Samples of silence since the last speech frame
+    silence_run: usize,
+}
+
+impl UtteranceSegmenter {
+    /// Create a segmenter with the given settings
+    pub fn new(config: SegmenterConfig) -> Self {
+        let to_samples =
+            |d: Duration| (d.as_secs_f64() * ENGINE_SAMPLE_RATE as f64).round() as usize;
+        let max_samples = to_samples(config.max_utterance).max(1);
+
+        Self {
+            pre_roll_samples: to_samples(config.pre_roll),
+            hang_over_samples: to_samples(config.hang_over),
+            max_samples,
+            // The overlap must leave room for new audio in the next piece
+            overlap_samples: to_samples(config.split_overlap).min(max_samples / 2),
+            pre_roll: VecDeque::new(),
+            current: Vec::new(),
+            current_start: 0,
+            position: 0,
+            in_speech: false,
+            silence_run: 0,
+        }
+    }
+
+    /// Add a frame and return an utterance if this frame completed one
+    pub fn push_frame(&mut self, frame: &[f32], is_speech: bool) -> Option<Utterance> {
+        let frame_start = self.position;
+        self.position += frame.len() as u64;
+
+        if !self.in_speech {
+            if !is_speech {
+                self.pre_roll.extend(frame);
+                let excess = self.pre_roll.len().saturating_sub(self.pre_roll_samples);
+                self.pre_roll.drain(..excess);
+                return None;
+            }
+
+            // Speech onset
+            self.in_speech = true;
+            self.silence_run = 0;
+            self.current_start = frame_start - self.pre_roll.len() as u64;
+            self.current.clear();
+            self.current.extend(self.pre_roll.drain(..));
+        }
+
+        self.current.extend_from_slice(frame);
+        if is_speech {
+            self.silence_run = 0;
+        } else {
+            self.silence_run += frame.len();
+        }
+
+        if self.silence_run >= self.hang_over_samples {
+            self.in_speech = false;
+            return self.take_current();
+        }
+
+        if self.current.len() >= self.max_samples {
+            // Split a long utterance, repeating the tail so words cut at the
+            // boundary are heard whole in the next piece
+            let keep_from = self.current.len() - self.overlap_samples;
+            let tail = self.current[keep_from..].to_vec();
+            let utterance = self.take_current();
+            self.current = tail;
+            self.current_start = self.position - self.current.len() as u64;
+            return utterance;
+        }
+
+        None
+    }
+
+    /// Flush the utterance in progress, if any (end of stream)
+    pub fn finish(&mut self) -> Option<Utterance> {
+        self.pre_roll.clear();
+        if !self.in_speech {
+            return None;
+        }
+        self.in_speech = false;
+        self.take_current()
+    }
+
+    /// Copy of the utterance in progress, if any, for previewing
+    pub fn partial(&self) -> Option<Utterance> {
+        if !self.in_speech || self.current.is_empty() {
+            return None;
+        }
+        Some(Utterance {
+            samples: self.current.clone(),
+            start_sample: self.current_start,
+        })
+    }
+
+    /// Whether speech is currently being collected
+    pub fn in_speech(&self) -> bool {
+        self.in_speech
+    }
+
+    fn take_current(&mut self) -> Option<Utterance> {
+        self.silence_run = 0;
+        if self.current.is_empty() {
+            return None;
+        }
+
+        Some(Utterance {
+            samples: std::mem::take(&mut self.current),
+            start_sample: self.current_start,
+        })
+    }
+}
+
+/// Run VAD segmentation on a dedicated thread
+///
+/// The thread consumes `audio_rx` until it is closed, then flushes the last
+/// utterance and exits, which closes `chunk_tx`. An [`AudioMessage::Flush`]
+/// ends the utterance in progress right away. Utterances cut short by a
+/// flush or the end of the stream are only emitted if they last at least
+/// [`PipelineConfig::min_flush_duration`]. With
+/// [`PipelineConfig::partial_interval`] set, the utterance in progress is
+/// also sent as a partial chunk each time it grew by that much.
+///
+/// # Errors
+///
+/// Returns an error if the aggressiveness level is invalid or the thread
+/// cannot be spawned.
+pub fn spawn_vad_worker(
+    mut audio_rx: mpsc::UnboundedReceiver<AudioMessage>,
+    chunk_tx: mpsc::UnboundedSender<AudioChunk>,
+    config: &PipelineConfig,
+) -> Result<JoinHandle<()>> {
+    let vad_aggressiveness = config.vad_aggressiveness;
+    let to_samples = |d: Duration| (d.as_secs_f64() * ENGINE_SAMPLE_RATE as f64) as usize;
+    let min_flush = config.min_flush_duration;
+    let min_flush_samples = to_samples(min_flush);
+    let partial_samples = config.partial_interval.map(to_samples);
+    let segmenter_config = config.segmenter.clone();
+
+    // Validate settings here, where errors can still be reported to the caller
+    VoiceActivityDetector::with_aggressiveness(vad_aggressiveness, ENGINE_SAMPLE_RATE)?;
+
+    std::thread::Builder::new()
+        .name("voxai-vad".to_string())
+        .spawn(move || {
+            let mut vad = match VoiceActivityDetector::with_aggressiveness(
+                vad_aggressiveness,
+                ENGINE_SAMPLE_RATE,
+            ) {
+                Ok(vad) => vad,
+                Err(e) => {
+                    log::error!("Failed to create VAD: {}", e);
+                    return;
+                }
+            };
+            let mut segmenter = UtteranceSegmenter::new(segmenter_config);
+            let mut pending = Vec::new();
+            let mut sequence = 0u64;
+            // Length of the utterance in progress when it was last previewed
+            let mut previewed_len = 0usize;
+
+            let emit = |utterance: Utterance, sequence: &mut u64| {
+                log::debug!(
+                    "Utterance {} ({:.1}s)",
+                    sequence,
+                    utterance.samples.len() as f32 / ENGINE_SAMPLE_RATE as f32
+                );
+                let sent = chunk_tx.send(utterance.into_chunk(*sequence)).is_ok();
+                *sequence += 1;
+                sent
+            };
+
+            log::info!("VAD worker started");
+
+            while let Some(message) = audio_rx.blocking_recv() {
+                let samples = match message {
+                    AudioMessage::Samples(samples) => samples,
+                    AudioMessage::Flush => {
+                        // The partial frame left in `pending` starts the next one
+                        previewed_len = 0;
+                        let sent = match segmenter.finish() {
+                            Some(utterance) if utterance.samples.len() >= min_flush_samples => {
+                                emit(utterance, &mut sequence)
+                            }
+                            Some(_) => {
+                                log::debug!("Dropping utterance shorter than {:?}", min_flush);
+                                true
+                            }
+                            None => true,
+                        };
+                        if !sent {
+                            return;
+                        }
+                        continue;
+                    }
+                };
+                pending.extend_from_slice(&samples);
+
+                let whole = pending.len() - pending.len() % VAD_FRAME_SAMPLES;
+                for frame in pending[..whole].chunks_exact(VAD_FRAME_SAMPLES) {
+                    let is_speech = vad.is_speech_f32(frame).unwrap_or_else(|e| {
+                        log::warn!("VAD error: {}", e);
+                        false
+                    });
+
+                    if let Some(utterance) = segmenter.push_frame(frame, is_speech) {
+                        previewed_len = 0;
+                        if !emit(utterance, &mut sequence) {
+                            log::debug!("Pipeline closed, stopping VAD worker");
+                            return;
+                        }
+                    }
+                }
+                pending.drain(..whole);
+
+                // Preview the utterance in progress once it grew enough
+                if let (Some(interval), Some(utterance)) = (partial_samples, segmenter.partial()) {
+                    if utterance.samples.len() < previewed_len {
+                        // A long utterance was split since the last preview
+                        previewed_len = 0;
+                    }
+                    if utterance.samples.len() >= previewed_len + interval.max(1) {
+                        previewed_len = utterance.samples.len();
+                        let mut chunk = utterance.into_chunk(sequence);
+                        chunk.kind = ResultKind::Partial;
+                        if chunk_tx.send(chunk).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            if let Some(utterance) = segmenter.finish() {
+                if utterance.samples.len() >= min_flush_samples {
+                    emit(utterance, &mut sequence);
+                }
+            }
+
+            log::info!("VAD worker stopped");
+        })
+        .context("Failed to spawn VAD thread")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: usize = VAD_FRAME_SAMPLES;
+
+    fn test_config() -> SegmenterConfig {
+        SegmenterConfig {
+            pre_roll: samples(2),
+            hang_over: samples(3),
+            max_utterance: samples(20),
+            split_overlap: samples(4),
+        }
+    }
+
+    /// Duration of `frames` VAD frames
+    fn samples(frames: usize) -> Duration {
+        Duration::from_secs_f64((frames * FRAME) as f64 / ENGINE_SAMPLE_RATE as f64)
+    }
+
+    /// Feed a speech pattern (one bool per frame), each frame filled with its index
+    fn feed(segmenter: &mut UtteranceSegmenter, pattern: &[bool]) -> Vec<Utterance> {
+        let mut utterances = Vec::new();
+        for (i, &speech) in pattern.iter().enumerate() {
+            let frame = vec![i as f32; FRAME];
+            utterances.extend(segmenter.push_frame(&frame, speech));
+        }
+        utterances.extend(segmenter.finish());
+        utterances
+    }
+
+    /// Frame indices contained in an utterance
+    fn frames_of(utterance: &Utterance) -> Vec<usize> {
+        utterance
+            .samples
+            .chunks(FRAME)
+            .map(|frame| frame[0] as usize)
+            .collect()
+    }
+
+    #[test]
+    fn test_silence_is_dropped() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        assert!(feed(&mut segmenter, &[false; 50]).is_empty());
+    }
+
+    #[test]
+    fn test_pre_roll_and_hang_over() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        let mut pattern = vec![false; 5];
+        pattern.extend([true; 4]);
+        pattern.extend([false; 6]);
+
+        let utterances = feed(&mut segmenter, &pattern);
+        assert_eq!(utterances.len(), 1);
+
+        // 2 frames of pre-roll, 4 of speech, 3 of hang-over
+        assert_eq!(frames_of(&utterances[0]), vec![3, 4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(utterances[0].start_sample, (3 * FRAME) as u64);
+    }
+
+    #[test]
+    fn test_short_pause_keeps_utterance_together() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        let pattern = [true, true, false, false, true, true, false, false, false];
+
+        let utterances = feed(&mut segmenter, &pattern);
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(frames_of(&utterances[0]), (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_separate_utterances() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        let mut pattern = vec![true; 2];
+        pattern.extend([false; 10]);
+        pattern.extend([true; 2]);
+
+        let utterances = feed(&mut segmenter, &pattern);
+        assert_eq!(utterances.len(), 2);
+        assert_eq!(frames_of(&utterances[0]), vec![0, 1, 2, 3, 4]);
+        // The second one is flushed by finish()
+        assert_eq!(frames_of(&utterances[1]), vec![10, 11, 12, 13]);
+        assert_eq!(utterances[1].start_sample, (10 * FRAME) as u64);
+    }
+
+    #[test]
+    fn test_long_utterance_is_split_with_overlap() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        let utterances = feed(&mut segmenter, &[true; 30]);
+
+        assert_eq!(utterances.len(), 2);
+        assert_eq!(frames_of(&utterances[0]), (0..20).collect::<Vec<_>>());
+        // The next piece repeats the last 4 frames
+        assert_eq!(frames_of(&utterances[1]), (16..30).collect::<Vec<_>>());
+        assert_eq!(utterances[1].start_sample, (16 * FRAME) as u64);
+    }
+
+    #[test]
+    fn test_partial_copies_utterance_in_progress() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        assert!(segmenter.partial().is_none());
+
+        for (i, speech) in [false, true, true].into_iter().enumerate() {
+            segmenter.push_frame(&[i as f32; FRAME], speech);
+        }
+        let partial = segmenter.partial().unwrap();
+        assert_eq!(partial.samples.len(), 3 * FRAME);
+        assert_eq!(partial.start_sample, 0);
+
+        // Previewing leaves the utterance untouched
+        let utterance = segmenter.finish().unwrap();
+        assert_eq!(utterance, partial);
+        assert!(segmenter.partial().is_none());
+    }
+
+    #[test]
+    fn test_finish_without_speech() {
+        let mut segmenter = UtteranceSegmenter::new(test_config());
+        segmenter.push_frame(&[0.0; FRAME], false);
+        assert!(!segmenter.in_speech());
+        assert!(segmenter.finish().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_vad_worker_drops_silence() {
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (chunk_tx, mut chunk_rx) = mpsc::unbounded_channel();
+        let config = PipelineConfig {
+            vad_aggressiveness: 2,
+            partial_interval: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let handle = spawn_vad_worker(audio_rx, chunk_tx, &config).unwrap();
+
+        audio_tx
+            .send(AudioMessage::Samples(vec![0.0; 16000 * 3]))
+            .unwrap();
+        audio_tx.send(AudioMessage::Flush).unwrap();
+        drop(audio_tx);
+
+        assert!(chunk_rx.recv().await.is_none());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_vad_worker_rejects_invalid_level() {
+        let (_audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (chunk_tx, _chunk_rx) = mpsc::unbounded_channel();
+        let config = PipelineConfig {
+            vad_aggressiveness: 7,
+            ..Default::default()
+        };
+        assert!(spawn_vad_worker(audio_rx, chunk_tx, &config).is_err());
+    }
+}
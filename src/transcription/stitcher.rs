@@ -0,0 +1,541 @@
+//! Overlap-aware stitching of chunk transcripts
+//!
+//! Consecutive chunks share `OVERLAP_SIZE_SAMPLES` of audio, so the words
+//! spoken in that window are transcribed twice. The stitcher removes them
+//! from the newer chunk in two passes:
+//! 1. Timestamps: segments that end before the previously emitted speech
+//!    ended are dropped outright.
+//! 2. Words: when no segment was dropped, the longest word run starting the
+//!    current chunk that repeats words the previous chunk spoke in the
+//!    overlap window marks where new text begins.
+
+use std::time::Duration;
+
+use super::engine::{aggregate_confidence, Segment, Token};
+use super::pipeline::TranscriptionResult;
+
+/// Number of trailing words from the previous chunk used for alignment
+const DEFAULT_LOOKBACK_WORDS: usize = 40;
+
+/// Minimum run of matching words required to trust an alignment
+const DEFAULT_MIN_MATCH_WORDS: usize = 2;
+
+/// Slack applied when comparing segment timestamps (whisper uses 10ms steps)
+const TIMESTAMP_TOLERANCE: Duration = Duration::from_millis(100);
+
+/// Slack added before the overlap window when picking the previous chunk's
+/// words to align on; word times inside long segments are interpolated
+const WINDOW_SLACK: Duration = Duration::from_secs(2);
+
+/// Leading words of the current chunk an alignment may skip (a word cut at
+/// the chunk boundary is often transcribed differently)
+const MAX_HEAD_OFFSET: usize = 1;
+
+/// Removes duplicated overlap text from consecutive chunk results
+pub struct TranscriptStitcher {
+    /// Normalized trailing words of the previous chunk, with the stream
+    /// offset where each one ends
+    previous_words: Vec<(String, Duration)>,
+    /// Stream offset where the previous chunk's speech ended
+    previous_speech_end: Option<Duration>,
+    /// Stream offset where the previous chunk's audio ended
+    previous_chunk_end: Option<Duration>,
+    /// Sequence number of the previous chunk
+    previous_sequence: Option<u64>,
+    lookback_words: usize,
+    min_match_words: usize,
+}
+
+impl TranscriptStitcher {
+    /// Create a stitcher with default alignment settings
+    pub fn new() -> Self {
+        Self {
+            previous_words: Vec::new(),
+            previous_speech_end: None,
+            previous_chunk_end: None,
+            previous_sequence: None,
+            lookback_words: DEFAULT_LOOKBACK_WORDS,
+            min_match_words: DEFAULT_MIN_MATCH_WORDS,
+        }
+    }
+
+    /// Set the minimum number of matching words needed to align chunks
+    pub fn set_min_match_words(&mut self, words: usize) {
+        self.min_match_words = words.max(1);
+    }
+
+    /// Forget the previous chunk (e.g. after the stream was restarted)
+    pub fn reset(&mut self) {
+        self.previous_words.clear();
+        self.previous_speech_end = None;
+        self.previous_chunk_end = None;
+        self.previous_sequence = None;
+    }
+
+    /// Trim the part of `result` that repeats the previous chunk
+    ///
+    /// Results must be fed in sequence order. A result that does not directly
+    /// follow the previous one, or that does not overlap it in time, is passed
+    /// through unchanged.
+    pub fn stitch(&mut self, mut result: TranscriptionResult) -> TranscriptionResult {
+        let overlaps = self.overlaps(&result);
+
+        // Remember the full chunk for the next alignment, before trimming
+        let all_words: Vec<(String, Duration)> = timed_words(&result.segments)
+            .into_iter()
+            .filter(|(word, _)| !word.is_empty())
+            .collect();
+        let speech_end = result.segments.last().map(|segment| segment.end);
+
+        if overlaps {
+            self.trim_overlap(&mut result);
+        }
+
+        let keep_from = all_words.len().saturating_sub(self.lookback_words);
+        self.previous_words = all_words[keep_from..].to_vec();
+        self.previous_speech_end = speech_end.or(self.previous_speech_end);
+        self.previous_chunk_end = Some(result.end);
+        self.previous_sequence = Some(result.sequence);
+
+        result
+    }
+
+    /// Trim `result` like [`TranscriptStitcher::stitch`] without remembering
+    /// it, for partial results that will be superseded
+    pub fn preview(&self, mut result: TranscriptionResult) -> TranscriptionResult {
+        if self.overlaps(&result) {
+            self.trim_overlap(&mut result);
+        }
+        result
+    }
+
+    /// Whether `result` directly follows the previous chunk and shares audio
+    /// with it
+    fn overlaps(&self, result: &TranscriptionResult) -> bool {
+        self.previous_sequence.map(|seq| seq + 1) == Some(result.sequence)
+            && self
+                .previous_chunk_end
+                .is_some_and(|end| result.start < end)
+    }
+
+    /// Remove duplicated segments and words from the start of `result`
+    fn trim_overlap(&self, result: &mut TranscriptionResult) {
+        // Pass 1: drop segments fully contained in already-emitted speech
+        let segment_count = result.segments.len();
+        if let Some(speech_end) = self.previous_speech_end {
+            result
+                .segments
+                .retain(|segment| segment.end > speech_end + TIMESTAMP_TOLERANCE);
+        }
+
+        // Pass 2: without usable timestamps, align on the words of the
+        // overlap window
+        if result.segments.len() == segment_count {
+            let head: Vec<String> = result
+                .segments
+                .iter()
+                .flat_map(|segment| segment.text.split_whitespace())
+                .take(self.lookback_words)
+                .map(normalize_word)
+                .collect();
+
+            if let Some(skip) = self.align(&head, result.start) {
+                result.segments = skip_words(std::mem::take(&mut result.segments), skip);
+            }
+        }
+
+        result.text = join_segments(&result.segments);
+        result.confidence = aggregate_confidence(&result.segments);
+    }
+
+    /// Number of leading words of `head` that repeat what the previous
+    /// chunk said after `overlap_start`
+    fn align(&self, head: &[String], overlap_start: Duration) -> Option<usize> {
+        let window_start = overlap_start.saturating_sub(WINDOW_SLACK);
+        let window: Vec<String> = self
+            .previous_words
+            .iter()
+            .filter(|(_, end)| *end > window_start)
+            .map(|(word, _)| word.clone())
+            .collect();
+        let (prev_end, cur_end, len) = anchored_run(&window, head, MAX_HEAD_OFFSET)?;
+
+        // A short run is only trusted if it reaches the end of the previous
+        // chunk, or covers the whole head of the current one.
+        let reaches_tail = prev_end == window.len();
+        let covers_head = cur_end == head.len() && len == head.len();
+        if len >= self.min_match_words || (len > 0 && (reaches_tail || covers_head)) {
+            Some(cur_end)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for TranscriptStitcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lowercase a word and strip surrounding punctuation for comparison
+fn normalize_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Normalized words of `segments` with the stream offset where each one
+/// ends, interpolated within a segment
+fn timed_words(segments: &[Segment]) -> Vec<(String, Duration)> {
+    segments
+        .iter()
+        .flat_map(|segment| {
+            let words: Vec<&str> = segment.text.split_whitespace().collect();
+            let span = segment.end.saturating_sub(segment.start);
+            let count = words.len();
+            words.into_iter().enumerate().map(move |(i, word)| {
+                let end = segment.start + span.mul_f64((i + 1) as f64 / count as f64);
+                (normalize_word(word), end)
+            })
+        })
+        .collect()
+}
+
+/// Join segment texts the same way the pipeline does
+fn join_segments(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Longest run of words of `a` that `b` starts with, allowing `b` to
+/// start with up to `max_offset` unmatched words
+///
+/// Returns `(end in a, end in b, length)` of the best run, preferring the
+/// latest match in `a` on ties. Empty words never match.
+fn anchored_run(a: &[String], b: &[String], max_offset: usize) -> Option<(usize, usize, usize)> {
+    let mut best: Option<(usize, usize, usize)> = None;
+
+    for offset in 0..=max_offset.min(b.len()) {
+        for start in 0..a.len() {
+            let len = a[start..]
+                .iter()
+                .zip(&b[offset..])
+                .take_while(|(x, y)| !x.is_empty() && x == y)
+                .count();
+            let is_longer = match best {
+                Some((_, _, best_len)) => len > best_len || (len == best_len && offset == 0),
+                None => true,
+            };
+            if len > 0 && is_longer {
+                best = Some((start + len, offset + len, len));
+            }
+        }
+    }
+
+    best
+}
+
+/// Drop the first `count` words from a list of segments
+///
+/// Partially trimmed segments keep their end time; their start is moved
+/// forward proportionally to the number of words removed. Tokens are
+/// dropped word by word, using the leading space whisper puts on the first
+/// token of each word.
+fn skip_words(segments: Vec<Segment>, mut count: usize) -> Vec<Segment> {
+    let mut output = Vec::with_capacity(segments.len());
+
+    for segment in segments {
+        if count == 0 {
+            output.push(segment);
+            continue;
+        }
+
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        if words.len() <= count {
+            count -= words.len();
+            continue;
+        }
+
+        let span = segment.end.saturating_sub(segment.start);
+        let start = segment.start + span.mul_f64(count as f64 / words.len() as f64);
+        let text = words[count..].join(" ");
+        let tokens = skip_token_words(&segment.tokens, count);
+        count = 0;
+
+        output.push(Segment::new(text, start, segment.end).with_tokens(tokens));
+    }
+
+    output
+}
+
+/// Drop the tokens making up the first `count` words
+fn skip_token_words(tokens: &[Token], count: usize) -> Vec<Token> {
+    let mut words_seen = 0;
+    let mut started = false;
+
+    tokens
+        .iter()
+        .filter(|token| {
+            let starts_word = !started || token.text.starts_with(char::is_whitespace);
+            if !token.text.trim().is_empty() {
+                if starts_word {
+                    words_seen += 1;
+                }
+                started = true;
+            }
+            words_seen > count
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::Transcript;
+    use crate::transcription::pipeline::{AudioChunk, ResultKind};
+
+    /// Build a result for a chunk starting at `start_secs`, with one
+    /// segment per word, each one second long.
+    fn word_result(sequence: u64, start_secs: u64, words: &[&str]) -> TranscriptionResult {
+        let segments = words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                Segment::new(
+                    format!(" {}", word),
+                    Duration::from_secs(i as u64),
+                    Duration::from_secs(i as u64 + 1),
+                )
+                .with_tokens(vec![Token::new(format!(" {}", word), 0.9)])
+            })
+            .collect();
+
+        chunk_result(sequence, start_secs, 10, Transcript::new(segments))
+    }
+
+    fn chunk_result(
+        sequence: u64,
+        start_secs: u64,
+        len_secs: u64,
+        transcript: Transcript,
+    ) -> TranscriptionResult {
+        let chunk = AudioChunk {
+            samples: vec![0.0; 16000 * len_secs as usize],
+            timestamp: std::time::Instant::now(),
+            sequence,
+            start_sample: 16000 * start_secs,
+            kind: ResultKind::Final,
+        };
+        TranscriptionResult::from_transcript(&chunk, transcript)
+    }
+
+    #[test]
+    fn test_first_chunk_passes_through() {
+        let mut stitcher = TranscriptStitcher::new();
+        let result = stitcher.stitch(word_result(0, 0, &["hello", "world"]));
+        assert_eq!(result.text, "hello world");
+    }
+
+    #[test]
+    fn test_preview_does_not_advance() {
+        let mut stitcher = TranscriptStitcher::new();
+        stitcher.stitch(word_result(0, 0, &["a", "b", "c", "d"]));
+
+        // The preview of the next chunk is trimmed like the final will be...
+        let preview = stitcher.preview(word_result(1, 2, &["c", "d", "e"]));
+        assert_eq!(preview.text, "e");
+
+        // ...and the final is still aligned against the previous chunk
+        let result = stitcher.stitch(word_result(1, 2, &["c", "d", "e", "f"]));
+        assert_eq!(result.text, "e f");
+    }
+
+    #[test]
+    fn test_timestamp_alignment() {
+        let mut stitcher = TranscriptStitcher::new();
+        let words: Vec<String> = (0..10).map(|i| format!("w{}", i)).collect();
+        let first: Vec<&str> = words.iter().map(String::as_str).collect();
+        stitcher.stitch(word_result(0, 0, &first));
+
+        // Second chunk starts at 8s and re-transcribes w8, w9
+        let words: Vec<String> = (8..18).map(|i| format!("w{}", i)).collect();
+        let second: Vec<&str> = words.iter().map(String::as_str).collect();
+        let result = stitcher.stitch(word_result(1, 8, &second));
+
+        assert_eq!(result.text, "w10 w11 w12 w13 w14 w15 w16 w17");
+        assert_eq!(result.segments[0].start, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_word_alignment_without_timestamps() {
+        let mut stitcher = TranscriptStitcher::new();
+
+        let first = Transcript::new(vec![Segment::new(
+            " The meeting starts at noon and we will review the budget",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        stitcher.stitch(chunk_result(0, 0, 10, first));
+
+        // Whole-chunk segment, so only word alignment can find the overlap
+        let second = Transcript::new(vec![Segment::new(
+            " review the budget. Then lunch.",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        let result = stitcher.stitch(chunk_result(1, 8, 10, second));
+
+        assert_eq!(result.text, "Then lunch.");
+        // Start moved forward proportionally (3 of 5 words removed)
+        assert_eq!(result.segments[0].start, Duration::from_secs(14));
+    }
+
+    #[test]
+    fn test_alignment_ignores_case_and_punctuation() {
+        let mut stitcher = TranscriptStitcher::new();
+        let first = Transcript::new(vec![Segment::new(
+            " so, Hello World",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        stitcher.stitch(chunk_result(0, 0, 10, first));
+
+        let second = Transcript::new(vec![Segment::new(
+            " hello world! How are you?",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        let result = stitcher.stitch(chunk_result(1, 8, 10, second));
+        assert_eq!(result.text, "How are you?");
+    }
+
+    #[test]
+    fn test_no_overlap_keeps_text() {
+        let mut stitcher = TranscriptStitcher::new();
+        stitcher.stitch(word_result(0, 0, &["one", "two", "three"]));
+
+        // Unrelated words: nothing should be removed
+        let result = stitcher.stitch(word_result(1, 8, &["alpha", "beta"]));
+        // Timestamps: segments end at 9s and 10s, previous speech ended at 3s
+        assert_eq!(result.text, "alpha beta");
+    }
+
+    #[test]
+    fn test_non_consecutive_chunks_are_not_stitched() {
+        let mut stitcher = TranscriptStitcher::new();
+        stitcher.stitch(word_result(0, 0, &["hello", "world"]));
+
+        let result = stitcher.stitch(word_result(5, 8, &["hello", "world"]));
+        assert_eq!(result.text, "hello world");
+    }
+
+    #[test]
+    fn test_chunks_without_time_overlap_are_not_stitched() {
+        let mut stitcher = TranscriptStitcher::new();
+        stitcher.stitch(word_result(0, 0, &["yes", "yes"]));
+
+        // Starts at 10s, exactly where the previous chunk ended
+        let result = stitcher.stitch(word_result(1, 10, &["yes", "yes"]));
+        assert_eq!(result.text, "yes yes");
+    }
+
+    #[test]
+    fn test_fully_duplicated_chunk_becomes_empty() {
+        let mut stitcher = TranscriptStitcher::new();
+        let first = Transcript::new(vec![Segment::new(
+            " good morning everyone",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        stitcher.stitch(chunk_result(0, 0, 10, first));
+
+        let second = Transcript::new(vec![Segment::new(
+            " morning everyone",
+            Duration::ZERO,
+            Duration::from_secs(2),
+        )]);
+        let result = stitcher.stitch(chunk_result(1, 8, 10, second));
+        assert!(result.text.is_empty());
+        assert!(result.segments.is_empty());
+        assert_eq!(result.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_tokens_trimmed_with_words() {
+        let segment = Segment::new(" hello brave world", Duration::ZERO, Duration::from_secs(3))
+            .with_tokens(vec![
+                Token::new(" hel", 0.5),
+                Token::new("lo", 0.5),
+                Token::new(" brave", 0.8),
+                Token::new(" world", 1.0),
+            ]);
+
+        let trimmed = skip_words(vec![segment], 1);
+        assert_eq!(trimmed[0].text, "brave world");
+        assert_eq!(trimmed[0].tokens.len(), 2);
+        assert_eq!(trimmed[0].tokens[0].text, " brave");
+        assert_eq!(trimmed[0].start, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_repeated_phrase_outside_overlap_is_kept() {
+        let mut stitcher = TranscriptStitcher::new();
+        let words = [
+            "I", "think", "it", "is", "fine", "but", "we", "could", "ask", "them",
+        ];
+        stitcher.stitch(word_result(0, 0, &words));
+
+        // "I think it is" was said at the start of the previous chunk, far
+        // from the overlap: only "ask them" is duplicated
+        let second = Transcript::new(vec![Segment::new(
+            " ask them. I think it is late.",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        let result = stitcher.stitch(chunk_result(1, 8, 10, second));
+        assert_eq!(result.text, "I think it is late.");
+    }
+
+    #[test]
+    fn test_alignment_is_anchored_to_chunk_start() {
+        let mut stitcher = TranscriptStitcher::new();
+        let first = Transcript::new(vec![Segment::new(
+            " we should leave now",
+            Duration::from_secs(6),
+            Duration::from_secs(10),
+        )]);
+        stitcher.stitch(chunk_result(0, 0, 10, first));
+
+        // The repeated words are not at the start of the new chunk
+        let second = Transcript::new(vec![Segment::new(
+            " Okay. Then we said we should leave now",
+            Duration::ZERO,
+            Duration::from_secs(10),
+        )]);
+        let result = stitcher.stitch(chunk_result(1, 8, 10, second));
+        assert_eq!(result.text, "Okay. Then we said we should leave now");
+    }
+
+    #[test]
+    fn test_anchored_run() {
+        let words = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let a = words(&["a", "b", "c", "d"]);
+        assert_eq!(
+            anchored_run(&a, &words(&["c", "d", "e"]), 1),
+            Some((4, 2, 2))
+        );
+        // One garbled leading word is skipped
+        assert_eq!(
+            anchored_run(&a, &words(&["x", "c", "d"]), 1),
+            Some((4, 3, 2))
+        );
+        // Matches further into `b` are ignored
+        assert_eq!(anchored_run(&a, &words(&["x", "y", "c", "d"]), 1), None);
+        assert_eq!(anchored_run(&a, &words(&["x", "y"]), 1), None);
+    }
+}
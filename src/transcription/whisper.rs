@@ -1,123 +1,365 @@
 //! Whisper context wrapper
 //!
 //! This module provides a wrapper around whisper-rs for audio transcription.
-//! Note: whisper-rs requires CMake and proper build setup.
+//! Real inference is only compiled with the `whisper` cargo feature, since
+//! whisper-rs requires CMake to build whisper.cpp. Without it, the context
+//! returns placeholder segments so the rest of the app can still be exercised.
 
-use super::models::ModelSize;
+use super::engine::{Transcript, TranscriptionEngine};
+use crate::config::ModelInfo;
+use crate::config::{Language, TranscriptionConfig};
 use anyhow::Result;
 use std::path::PathBuf;
 
+/// Decoding strategy used by whisper.cpp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingStrategy {
+    /// Pick the most likely token at each step
+    Greedy,
+    /// Keep several hypotheses alive (slower, usually more accurate)
+    BeamSearch { beam_size: u32 },
+}
+
+/// Options applied to every `full()` call
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhisperOptions {
+    /// Decoding strategy
+    pub sampling: SamplingStrategy,
+    /// Number of inference threads (0 = automatic)
+    pub threads: usize,
+    /// Offload inference to the GPU when whisper.cpp was built with GPU support
+    pub use_gpu: bool,
+}
+
+impl WhisperOptions {
+    /// Build options from the user's transcription settings
+    pub fn from_config(config: &TranscriptionConfig) -> Self {
+        let sampling = match config.sampling.as_str() {
+            "beam_search" => SamplingStrategy::BeamSearch {
+                beam_size: config.beam_size,
+            },
+            _ => SamplingStrategy::Greedy,
+        };
+
+        Self {
+            sampling,
+            threads: config.threads as usize,
+            use_gpu: config.enable_gpu,
+        }
+    }
+
+    /// Resolve the thread count, picking a sensible default when set to 0
+    pub fn effective_threads(&self) -> usize {
+        if self.threads > 0 {
+            return self.threads;
+        }
+
+        // whisper.cpp scales poorly past 8 threads
+        std::thread::available_parallelism()
+            .map(|n| n.get().min(8))
+            .unwrap_or(4)
+    }
+}
+
+impl Default for WhisperOptions {
+    fn default() -> Self {
+        Self::from_config(&TranscriptionConfig::default())
+    }
+}
+
 /// Whisper transcription context
 ///
-/// This is a placeholder structure until whisper-rs is properly integrated.
-/// whisper-rs requires CMake and whisper.cpp to be built, which needs to be
-/// done in the actual build environment.
+/// The ggml model is loaded once in [`WhisperContext::with_options`] and the
+/// decoder state is reused for every chunk.
 #[allow(dead_code)]
 pub struct WhisperContext {
     model_path: PathBuf,
-    model_size: ModelSize,
+    model: ModelInfo,
+    options: WhisperOptions,
+    #[cfg(feature = "whisper")]
+    backend: backend::Backend,
 }
 
 #[allow(dead_code)]
 impl WhisperContext {
-    /// Create a new Whisper context with the specified model
+    /// Create a new Whisper context with the specified model and default options
     ///
     /// # Arguments
     ///
-    /// * `model` - The model size to use
+    /// * `model` - The model to use
     ///
     /// # Errors
     ///
     /// Returns an error if the model is not downloaded or cannot be loaded.
-    pub fn new(model: ModelSize) -> Result<Self> {
+    pub fn new(model: &ModelInfo) -> Result<Self> {
+        Self::with_options(model, WhisperOptions::default())
+    }
+
+    /// Create a new Whisper context with explicit decoding options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model is not downloaded or cannot be loaded.
+    pub fn with_options(model: &ModelInfo, options: WhisperOptions) -> Result<Self> {
         let model_path = super::models::get_model_path(model)?;
 
         if !model_path.exists() {
             anyhow::bail!("Model {} not found. Please download it first.", model);
         }
 
-        // TODO: Initialize actual whisper-rs context
-        // This requires whisper-rs to be added as a dependency
-        // and whisper.cpp to be compiled during the build process.
-        //
-        // Example (when whisper-rs is available):
-        // let ctx = whisper_rs::WhisperContext::new(&model_path)?;
+        #[cfg(feature = "whisper")]
+        let backend = backend::Backend::load(&model_path, &options)?;
+
+        #[cfg(not(feature = "whisper"))]
+        log::warn!("VoxAI was built without the `whisper` feature; transcripts are placeholders");
 
         Ok(Self {
             model_path,
-            model_size: model,
+            model: model.clone(),
+            options,
+            #[cfg(feature = "whisper")]
+            backend,
         })
     }
 
+    /// Get the model being used
+    pub fn model(&self) -> &ModelInfo {
+        &self.model
+    }
+
+    /// Get the model file path
+    pub fn model_path(&self) -> &PathBuf {
+        &self.model_path
+    }
+
+    /// Get the decoding options
+    pub fn options(&self) -> &WhisperOptions {
+        &self.options
+    }
+}
+
+impl TranscriptionEngine for WhisperContext {
     /// Transcribe audio samples
     ///
-    /// # Arguments
-    ///
-    /// * `audio` - Audio samples as f32 in range [-1.0, 1.0]
-    ///   Must be mono 16kHz PCM
-    ///
-    /// # Returns
-    ///
-    /// The transcribed text
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if transcription fails
-    pub fn transcribe(&self, audio: &[f32]) -> Result<String> {
-        // TODO: Implement actual transcription using whisper-rs
-        //
-        // Example (when whisper-rs is available):
-        // let mut params = whisper_rs::FullParams::new(whisper_rs::SamplingStrategy::Greedy);
-        // params.set_language(Some("en"));
-        // params.set_print_special(false);
-        // params.set_print_progress(false);
-        // params.set_print_realtime(false);
-        // params.set_print_timestamps(false);
-        //
-        // self.ctx.full(params, audio)?;
-        //
-        // let num_segments = self.ctx.full_n_segments()?;
-        // let mut text = String::new();
-        //
-        // for i in 0..num_segments {
-        //     let segment = self.ctx.full_get_segment_text(i)?;
-        //     text.push_str(&segment);
-        // }
-        //
-        // Ok(text)
-
-        log::warn!("Whisper transcription is not yet implemented");
-        log::info!(
-            "Would transcribe {} samples with {} model",
-            audio.len(),
-            self.model_size
-        );
+    /// `samples` must be mono 16kHz PCM in range [-1.0, 1.0].
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        language: &Language,
+        prompt: Option<&str>,
+    ) -> Result<Transcript> {
+        #[cfg(feature = "whisper")]
+        {
+            self.backend
+                .transcribe(samples, language, prompt, &self.options)
+        }
+
+        #[cfg(not(feature = "whisper"))]
+        {
+            use super::engine::{samples_to_duration, Segment};
+            use std::time::Duration;
+
+            log::debug!(
+                "Placeholder transcription of {} samples with {} model (language: {}, prompt: {})",
+                samples.len(),
+                self.model,
+                language.to_code(),
+                prompt.is_some()
+            );
 
-        // Placeholder response
-        Ok("[Transcription placeholder - whisper-rs not yet integrated]".to_string())
+            Ok(Transcript::new(vec![Segment::new(
+                "[Transcription placeholder - built without the `whisper` feature]",
+                Duration::ZERO,
+                samples_to_duration(samples.len()),
+            )]))
+        }
     }
 
-    /// Get the model size being used
-    pub fn model_size(&self) -> ModelSize {
-        self.model_size
+    fn name(&self) -> &str {
+        "whisper"
     }
+}
 
-    /// Get the model file path
-    pub fn model_path(&self) -> &PathBuf {
-        &self.model_path
+#[cfg(feature = "whisper")]
+mod backend {
+    //! whisper-rs bindings, compiled only with the `whisper` feature
+
+    use super::{SamplingStrategy, WhisperOptions};
+    use crate::config::Language;
+    use crate::transcription::engine::{Segment, Token, Transcript};
+    use anyhow::{Context, Result};
+    use std::path::Path;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use whisper_rs::{FullParams, WhisperContextParameters, WhisperState};
+
+    /// Loaded model plus a reusable decoder state
+    pub(super) struct Backend {
+        // The state holds its own reference to the model, but keeping the
+        // context makes the ownership explicit.
+        _context: whisper_rs::WhisperContext,
+        state: Mutex<WhisperState>,
+        /// Token ids at or above end-of-text are special (timestamps, language...)
+        token_eot: i32,
+    }
+
+    impl Backend {
+        /// Load the ggml model and allocate a decoder state
+        pub(super) fn load(model_path: &Path, options: &WhisperOptions) -> Result<Self> {
+            let path = model_path
+                .to_str()
+                .context("Model path is not valid UTF-8")?;
+
+            let mut params = WhisperContextParameters::default();
+            params.use_gpu(options.use_gpu);
+
+            log::info!("Loading Whisper model from {}", path);
+            let context = whisper_rs::WhisperContext::new_with_params(path, params)
+                .map_err(|e| anyhow::anyhow!("Failed to load Whisper model: {}", e))?;
+
+            let state = context
+                .create_state()
+                .map_err(|e| anyhow::anyhow!("Failed to create Whisper state: {}", e))?;
+
+            Ok(Self {
+                token_eot: context.token_eot(),
+                _context: context,
+                state: Mutex::new(state),
+            })
+        }
+
+        /// Run `full()` on the samples and collect the resulting segments
+        pub(super) fn transcribe(
+            &self,
+            samples: &[f32],
+            language: &Language,
+            prompt: Option<&str>,
+            options: &WhisperOptions,
+        ) -> Result<Transcript> {
+            let strategy = match options.sampling {
+                SamplingStrategy::Greedy => whisper_rs::SamplingStrategy::Greedy { best_of: 1 },
+                SamplingStrategy::BeamSearch { beam_size } => {
+                    whisper_rs::SamplingStrategy::BeamSearch {
+                        beam_size: beam_size as i32,
+                        patience: -1.0,
+                    }
+                }
+            };
+
+            let language_code = language.to_code();
+            let mut params = FullParams::new(strategy);
+            params.set_n_threads(options.effective_threads() as i32);
+            params.set_language(Some(&language_code));
+            params.set_print_special(false);
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_timestamps(false);
+            params.set_suppress_blank(true);
+            if let Some(prompt) = prompt {
+                params.set_initial_prompt(&prompt.replace('\0', ""));
+            }
+
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Whisper state lock poisoned"))?;
+
+            state
+                .full(params, samples)
+                .map_err(|e| anyhow::anyhow!("Whisper inference failed: {}", e))?;
+
+            let num_segments = state
+                .full_n_segments()
+                .map_err(|e| anyhow::anyhow!("Failed to read segments: {}", e))?;
+
+            let mut segments = Vec::with_capacity(num_segments as usize);
+            for i in 0..num_segments {
+                let text = state
+                    .full_get_segment_text_lossy(i)
+                    .map_err(|e| anyhow::anyhow!("Failed to read segment text: {}", e))?;
+                // whisper.cpp timestamps are in centiseconds
+                let t0 = state.full_get_segment_t0(i).unwrap_or(0).max(0) as u64;
+                let t1 = state.full_get_segment_t1(i).unwrap_or(0).max(0) as u64;
+
+                let num_tokens = state.full_n_tokens(i).unwrap_or(0);
+                let mut tokens = Vec::with_capacity(num_tokens as usize);
+                for j in 0..num_tokens {
+                    let Ok(data) = state.full_get_token_data(i, j) else {
+                        continue;
+                    };
+                    if data.id >= self.token_eot {
+                        continue;
+                    }
+                    if let Ok(token_text) = state.full_get_token_text_lossy(i, j) {
+                        tokens.push(Token::new(token_text, data.p));
+                    }
+                }
+
+                segments.push(
+                    Segment::new(
+                        text,
+                        Duration::from_millis(t0 * 10),
+                        Duration::from_millis(t1 * 10),
+                    )
+                    .with_tokens(tokens),
+                );
+            }
+
+            let mut transcript = Transcript::new(segments);
+            if let Some(code) = state
+                .full_lang_id_from_state()
+                .ok()
+                .and_then(whisper_rs::get_lang_str)
+            {
+                transcript = transcript.with_language(code);
+            }
+
+            Ok(transcript)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transcription::models::ModelRegistry;
 
     #[test]
     fn test_whisper_context_placeholder() {
         // This test just ensures the structure compiles
         // Actual testing requires a downloaded model
-        let result = WhisperContext::new(ModelSize::Base);
+        let registry = ModelRegistry::builtin();
+        let result = WhisperContext::new(registry.get("base").unwrap());
         // We expect an error since no model is downloaded in test environment
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_options_from_config() {
+        let mut config = TranscriptionConfig::default();
+        assert_eq!(
+            WhisperOptions::from_config(&config).sampling,
+            SamplingStrategy::Greedy
+        );
+
+        config.sampling = "beam_search".to_string();
+        config.beam_size = 3;
+        config.threads = 2;
+        let options = WhisperOptions::from_config(&config);
+        assert_eq!(
+            options.sampling,
+            SamplingStrategy::BeamSearch { beam_size: 3 }
+        );
+        assert_eq!(options.effective_threads(), 2);
+    }
+
+    #[test]
+    fn test_effective_threads_auto() {
+        let options = WhisperOptions {
+            threads: 0,
+            ..Default::default()
+        };
+        let threads = options.effective_threads();
+        assert!((1..=8).contains(&threads));
+    }
 }
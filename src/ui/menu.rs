@@ -4,13 +4,8 @@
 
 use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
 
-/// Application state for menu items
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum AppState {
-    Idle,
-    Recording,
-    Processing,
-}
+use crate::app::state::AppEvent;
+pub use crate::app::state::AppState;
 
 /// Menu identifiers
 pub struct MenuItems {
@@ -53,7 +48,7 @@ pub fn create_menu() -> anyhow::Result<(Menu, MenuItems)> {
 /// Update menu based on application state
 pub fn update_menu_for_state(items: &MenuItems, state: AppState) -> anyhow::Result<()> {
     match state {
-        AppState::Idle => {
+        AppState::Idle | AppState::Error => {
             items.start_recording.set_text("Start Recording");
             items.start_recording.set_enabled(true);
         }
@@ -90,6 +85,16 @@ pub enum MenuAction {
     Quit,
 }
 
+impl From<MenuAction> for AppEvent {
+    fn from(action: MenuAction) -> Self {
+        match action {
+            MenuAction::ToggleRecording => AppEvent::ToggleRecording,
+            MenuAction::OpenSettings => AppEvent::OpenSettings,
+            MenuAction::Quit => AppEvent::Quit,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,9 +2,15 @@
 //!
 //! Provides a graphical settings interface for configuring VoxAI
 
-use crate::config::{save_config, Config};
+use crate::config::{
+    save_config, ActivationMode, Config, HOLD_THRESHOLD_RANGE_MS, PARTIAL_INTERVAL_RANGE_MS,
+};
+use crate::transcription::downloader::{self, DownloadMonitor, DownloadPhase, DownloadProgress};
+use crate::transcription::models::{get_model_path, ModelRegistry, AUTO_MODEL};
 use anyhow::Result;
 use eframe::egui;
+use std::time::Duration;
+use tokio::sync::mpsc::{error::TryRecvError, UnboundedReceiver};
 
 /// Settings window application
 pub struct SettingsWindow {
@@ -12,6 +18,15 @@ pub struct SettingsWindow {
     has_changes: bool,
     status_message: Option<String>,
     show_advanced: bool,
+    download: Option<ModelDownload>,
+}
+
+/// Model download running in the background
+struct ModelDownload {
+    model: String,
+    monitor: DownloadMonitor,
+    events: UnboundedReceiver<DownloadProgress>,
+    latest: Option<DownloadProgress>,
 }
 
 impl SettingsWindow {
@@ -22,6 +37,7 @@ impl SettingsWindow {
             has_changes: false,
             status_message: None,
             show_advanced: false,
+            download: None,
         }
     }
 
@@ -57,6 +73,114 @@ impl SettingsWindow {
         }
     }
 
+    /// Download the selected model on a background thread
+    fn start_download(&mut self) {
+        let registry = ModelRegistry::from_config(&self.config.models);
+        let model = match registry.resolve(&self.config.transcription.model) {
+            Ok(model) => model.clone(),
+            Err(e) => {
+                self.status_message = Some(format!("Cannot download model: {}", e));
+                return;
+            }
+        };
+
+        let (monitor, events) = DownloadMonitor::channel();
+        self.download = Some(ModelDownload {
+            model: model.name.clone(),
+            monitor: monitor.clone(),
+            events,
+            latest: None,
+        });
+        self.status_message = None;
+
+        let models_config = self.config.models.clone();
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    log::error!("Failed to start download runtime: {}", e);
+                    return;
+                }
+            };
+            // The outcome reaches the window as the last progress event
+            if let Err(e) =
+                runtime.block_on(downloader::download_model(&model, &models_config, &monitor))
+            {
+                log::error!("Failed to download {} model: {:#}", model, e);
+            }
+        });
+    }
+
+    /// Apply the progress events received since the last frame
+    fn poll_download(&mut self) {
+        let Some(download) = &mut self.download else {
+            return;
+        };
+        let stopped = loop {
+            match download.events.try_recv() {
+                Ok(progress) => download.latest = Some(progress),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+
+        // A download thread that stopped without a last event failed
+        // before it started
+        let phase = match &download.latest {
+            Some(progress) if progress.is_finished() => progress.phase.clone(),
+            _ if stopped => DownloadPhase::Failed("download stopped".to_string()),
+            _ => return,
+        };
+        self.status_message = Some(match phase {
+            DownloadPhase::Done => format!("{} model downloaded", download.model),
+            DownloadPhase::Failed(e) => {
+                format!("Error downloading {} model: {}", download.model, e)
+            }
+            _ => format!(
+                "Download of {} model cancelled; it resumes on the next download",
+                download.model
+            ),
+        });
+        self.download = None;
+    }
+
+    /// Download button of the selected model, or the progress of its
+    /// download
+    fn model_download_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(download) = &self.download {
+            let progress = download.latest.as_ref();
+            let bar = egui::ProgressBar::new(progress.and_then(|p| p.fraction()).unwrap_or(0.0))
+                .text(progress.map_or("Connecting…".to_string(), progress_text));
+            ui.horizontal(|ui| {
+                ui.label(format!("Downloading {}:", download.model));
+                ui.add(bar);
+                if ui.button("Cancel").clicked() {
+                    download.monitor.cancel();
+                }
+            });
+            // Keep polling the events while the user does nothing
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+
+        let registry = ModelRegistry::from_config(&self.config.models);
+        let Some(model) = registry.get(&self.config.transcription.model) else {
+            return;
+        };
+        let downloaded = get_model_path(model).is_ok_and(|path| path.exists());
+        if !downloaded {
+            ui.horizontal(|ui| {
+                ui.label(format!("The {} model is not downloaded yet.", model.name));
+                if ui.button("Download").clicked() {
+                    self.start_download();
+                }
+            });
+        }
+    }
+
     /// Reset to default configuration
     fn reset_to_defaults(&mut self) {
         self.config = Config::default();
@@ -67,6 +191,8 @@ impl SettingsWindow {
 
 impl eframe::App for SettingsWindow {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_download();
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("VoxAI Settings");
             ui.separator();
@@ -110,13 +236,27 @@ impl eframe::App for SettingsWindow {
                     egui::ComboBox::from_label("")
                         .selected_text(&self.config.transcription.model)
                         .show_ui(ui, |ui| {
-                            let models = ["tiny", "base", "small", "medium"];
-                            for model in models {
+                            if ui
+                                .selectable_value(
+                                    &mut self.config.transcription.model,
+                                    AUTO_MODEL.to_string(),
+                                    "auto (best for this machine)",
+                                )
+                                .clicked()
+                            {
+                                self.has_changes = true;
+                            }
+                            let registry = ModelRegistry::from_config(&self.config.models);
+                            for model in registry.all() {
+                                let label = match model.size {
+                                    0 => model.name.clone(),
+                                    size => format!("{} ({} MB)", model.name, size / 1_000_000),
+                                };
                                 if ui
                                     .selectable_value(
                                         &mut self.config.transcription.model,
-                                        model.to_string(),
-                                        model,
+                                        model.name.clone(),
+                                        label,
                                     )
                                     .clicked()
                                 {
@@ -125,6 +265,7 @@ impl eframe::App for SettingsWindow {
                             }
                         });
                 });
+                self.model_download_ui(ui);
 
                 ui.horizontal(|ui| {
                     ui.label("Language:");
@@ -160,6 +301,77 @@ impl eframe::App for SettingsWindow {
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Live Preview Interval:");
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut self.config.transcription.partial_interval_ms,
+                                0..=*PARTIAL_INTERVAL_RANGE_MS.end(),
+                            )
+                            .step_by(100.0)
+                            .suffix(" ms"),
+                        )
+                        .changed()
+                    {
+                        self.has_changes = true;
+                    }
+                    ui.label("(0 = disabled)");
+                });
+
+                ui.checkbox(&mut self.show_advanced, "Show Advanced Options");
+
+                if self.show_advanced {
+                    ui.horizontal(|ui| {
+                        ui.label("Decoding:");
+                        egui::ComboBox::from_id_source("sampling")
+                            .selected_text(&self.config.transcription.sampling)
+                            .show_ui(ui, |ui| {
+                                for sampling in ["greedy", "beam_search"] {
+                                    if ui
+                                        .selectable_value(
+                                            &mut self.config.transcription.sampling,
+                                            sampling.to_string(),
+                                            sampling,
+                                        )
+                                        .clicked()
+                                    {
+                                        self.has_changes = true;
+                                    }
+                                }
+                            });
+                    });
+
+                    if self.config.transcription.sampling == "beam_search" {
+                        ui.horizontal(|ui| {
+                            ui.label("Beam Size:");
+                            if ui
+                                .add(egui::Slider::new(
+                                    &mut self.config.transcription.beam_size,
+                                    1..=16,
+                                ))
+                                .changed()
+                            {
+                                self.has_changes = true;
+                            }
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Threads:");
+                        if ui
+                            .add(egui::Slider::new(
+                                &mut self.config.transcription.threads,
+                                0..=16,
+                            ))
+                            .changed()
+                        {
+                            self.has_changes = true;
+                        }
+                        ui.label("(0 = automatic)");
+                    });
+                }
+
                 ui.separator();
 
                 // Hotkeys Settings
@@ -174,6 +386,44 @@ impl eframe::App for SettingsWindow {
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Activation:");
+                    egui::ComboBox::from_id_source("activation_mode")
+                        .selected_text(self.config.hotkeys.activation_mode.display_name())
+                        .show_ui(ui, |ui| {
+                            for mode in ActivationMode::all() {
+                                if ui
+                                    .selectable_value(
+                                        &mut self.config.hotkeys.activation_mode,
+                                        *mode,
+                                        mode.display_name(),
+                                    )
+                                    .clicked()
+                                {
+                                    self.has_changes = true;
+                                }
+                            }
+                        });
+                });
+
+                if self.config.hotkeys.activation_mode == ActivationMode::Hybrid {
+                    ui.horizontal(|ui| {
+                        ui.label("Hold for push-to-talk after:");
+                        if ui
+                            .add(
+                                egui::Slider::new(
+                                    &mut self.config.hotkeys.hold_threshold_ms,
+                                    HOLD_THRESHOLD_RANGE_MS,
+                                )
+                                .suffix(" ms"),
+                            )
+                            .changed()
+                        {
+                            self.has_changes = true;
+                        }
+                    });
+                }
+
                 ui.separator();
 
                 // UI Settings
@@ -205,6 +455,26 @@ impl eframe::App for SettingsWindow {
                 {
                     self.has_changes = true;
                 }
+
+                if ui
+                    .checkbox(
+                        &mut self.config.ui.remove_fillers,
+                        "Remove Filler Words (um, uh...)",
+                    )
+                    .changed()
+                {
+                    self.has_changes = true;
+                }
+
+                if ui
+                    .checkbox(
+                        &mut self.config.ui.normalize_numbers,
+                        "Write Numbers as Digits",
+                    )
+                    .changed()
+                {
+                    self.has_changes = true;
+                }
             });
 
             ui.separator();
@@ -232,6 +502,32 @@ impl eframe::App for SettingsWindow {
     }
 }
 
+/// Text of the download progress bar: "45% · 3.2 MB/s · 20 s left"
+fn progress_text(progress: &DownloadProgress) -> String {
+    match progress.phase {
+        DownloadPhase::Verifying => return "Verifying…".to_string(),
+        DownloadPhase::Downloading => {}
+        _ => return String::new(),
+    }
+
+    let mut parts = vec![match progress.fraction() {
+        Some(fraction) => format!("{:.0}%", fraction * 100.0),
+        None => format!("{:.1} MB", progress.downloaded as f64 / 1e6),
+    }];
+    if progress.rate > 0.0 {
+        parts.push(format!("{:.1} MB/s", progress.rate / 1e6));
+    }
+    if let Some(eta) = progress.eta {
+        let secs = eta.as_secs();
+        parts.push(if secs >= 60 {
+            format!("{} min left", (eta.as_secs_f64() / 60.0).ceil())
+        } else {
+            format!("{} s left", secs)
+        });
+    }
+    parts.join(" · ")
+}
+
 /// Launch settings window (non-blocking)
 pub fn launch_settings_window(config: Config) -> Result<()> {
     log::info!("Launching settings window");
@@ -260,6 +556,33 @@ mod tests {
         assert!(!settings.has_changes);
         assert!(settings.status_message.is_none());
         assert!(!settings.show_advanced);
+        assert!(settings.download.is_none());
+    }
+
+    #[test]
+    fn test_progress_text() {
+        let progress = DownloadProgress {
+            phase: DownloadPhase::Downloading,
+            downloaded: 45_000_000,
+            total: Some(100_000_000),
+            rate: 3_200_000.0,
+            eta: Some(Duration::from_secs(20)),
+        };
+        assert_eq!(progress_text(&progress), "45% · 3.2 MB/s · 20 s left");
+
+        let progress = DownloadProgress {
+            total: None,
+            rate: 0.0,
+            eta: Some(Duration::from_secs(150)),
+            ..progress
+        };
+        assert_eq!(progress_text(&progress), "45.0 MB · 3 min left");
+
+        let progress = DownloadProgress {
+            phase: DownloadPhase::Verifying,
+            ..progress
+        };
+        assert_eq!(progress_text(&progress), "Verifying…");
     }
 
     #[test]
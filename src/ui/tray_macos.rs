@@ -3,9 +3,13 @@
 //! Provides menu bar functionality for macOS
 
 use anyhow::{Context, Result};
+use tray_icon::menu::MenuEvent;
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
-use super::menu::{create_menu, AppState, MenuItems};
+use super::menu::{create_menu, handle_menu_event, AppState, MenuAction, MenuItems};
+
+/// Default tooltip of the tray icon
+const TOOLTIP: &str = "VoxAI - Voice Transcription";
 
 /// macOS menu bar manager
 pub struct MacOSTray {
@@ -33,7 +37,7 @@ impl MacOSTray {
         // Build menu bar icon
         let tray = TrayIconBuilder::new()
             .with_menu(Box::new(menu))
-            .with_tooltip("VoxAI - Voice Transcription")
+            .with_tooltip(TOOLTIP)
             .with_icon(icon_idle.clone())
             .with_icon_as_template(true) // Enable template mode for macOS
             .build()
@@ -89,7 +93,8 @@ impl MacOSTray {
 
         // Update icon
         let icon = match state {
-            AppState::Idle => &self.icon_idle,
+            // No dedicated error icon yet, the notification tells the user
+            AppState::Idle | AppState::Error => &self.icon_idle,
             AppState::Recording => &self.icon_recording,
             AppState::Processing => &self.icon_processing,
         };
@@ -110,6 +115,22 @@ impl MacOSTray {
         self.current_state
     }
 
+    /// Show live dictation text in the tooltip, or restore the default one
+    pub fn set_preview(&self, text: Option<&str>) -> Result<()> {
+        let tooltip = match text {
+            Some(text) => format!("VoxAI - {}", text),
+            None => TOOLTIP.to_string(),
+        };
+        self.tray
+            .set_tooltip(Some(tooltip))
+            .context("Failed to update tray tooltip")
+    }
+
+    /// Action for a menu event, if it belongs to this tray's menu
+    pub fn menu_action(&self, event: MenuEvent) -> Option<MenuAction> {
+        handle_menu_event(event, &self.menu_items)
+    }
+
     /// Show a notification
     pub fn show_notification(&self, title: &str, message: &str) -> Result<()> {
         log::info!("macOS notification: {} - {}", title, message);
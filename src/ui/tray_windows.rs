@@ -3,9 +3,13 @@
 //! Provides system tray functionality for Windows 10/11
 
 use anyhow::{Context, Result};
+use tray_icon::menu::MenuEvent;
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
-use super::menu::{create_menu, AppState, MenuItems};
+use super::menu::{create_menu, handle_menu_event, AppState, MenuAction, MenuItems};
+
+/// Default tooltip of the tray icon
+const TOOLTIP: &str = "VoxAI - Voice Transcription";
 
 /// Windows system tray manager
 pub struct WindowsTray {
@@ -33,7 +37,7 @@ impl WindowsTray {
         // Build tray icon
         let tray = TrayIconBuilder::new()
             .with_menu(Box::new(menu))
-            .with_tooltip("VoxAI - Voice Transcription")
+            .with_tooltip(TOOLTIP)
             .with_icon(icon_idle.clone())
             .build()
             .context("Failed to build tray icon")?;
@@ -85,7 +89,8 @@ impl WindowsTray {
 
         // Update icon
         let icon = match state {
-            AppState::Idle => &self.icon_idle,
+            // No dedicated error icon yet, the notification tells the user
+            AppState::Idle | AppState::Error => &self.icon_idle,
             AppState::Recording => &self.icon_recording,
             AppState::Processing => &self.icon_processing,
         };
@@ -106,6 +111,22 @@ impl WindowsTray {
         self.current_state
     }
 
+    /// Show live dictation text in the tooltip, or restore the default one
+    pub fn set_preview(&self, text: Option<&str>) -> Result<()> {
+        let tooltip = match text {
+            Some(text) => format!("VoxAI - {}", text),
+            None => TOOLTIP.to_string(),
+        };
+        self.tray
+            .set_tooltip(Some(tooltip))
+            .context("Failed to update tray tooltip")
+    }
+
+    /// Action for a menu event, if it belongs to this tray's menu
+    pub fn menu_action(&self, event: MenuEvent) -> Option<MenuAction> {
+        handle_menu_event(event, &self.menu_items)
+    }
+
     /// Show a notification
     pub fn show_notification(&self, title: &str, message: &str) -> Result<()> {
         log::info!("Windows notification: {} - {}", title, message);